@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prints the items contained in a raw `sallyport` block dump in human-readable form.
+//!
+//! Usage:
+//!
+//! - `sallyport-dump BLOCK` prints the items contained in `BLOCK`.
+//! - `sallyport-dump BEFORE AFTER` prints the items contained in `AFTER` along with
+//!   the changes of their return values compared to `BEFORE`.
+//! - `sallyport-dump RECORDING` prints each exchange contained in a recording produced
+//!   by [`Recorder`](sallyport::host::Recorder).
+//!
+//! Raw block dumps contain the `usize` elements of a block in native byte order.
+//!
+//! The binary requires `std` and is therefore only built with the `dump` feature enabled,
+//! for example, using `cargo run --features dump --bin sallyport-dump BLOCK`.
+
+use sallyport::item::dump::{write_block, write_diff};
+use sallyport::item::record::{Reader, MAGIC};
+
+use std::mem::size_of;
+use std::process::exit;
+use std::{env, fs};
+
+fn read(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|e| {
+        eprintln!("failed to read `{}`: {}", path, e);
+        exit(1)
+    })
+}
+
+fn words(bytes: &[u8]) -> Vec<usize> {
+    bytes
+        .chunks_exact(size_of::<usize>())
+        .map(|chunk| usize::from_ne_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+fn dump_recording(recording: &[u8]) -> Result<String, String> {
+    let reader =
+        Reader::new(recording).map_err(|e| format!("invalid recording header: error {}", e))?;
+    let mut out = String::new();
+    for (i, exchange) in reader.enumerate() {
+        let exchange = exchange.map_err(|e| format!("invalid exchange {}: error {}", i, e))?;
+        let mut before: Vec<_> = exchange.before().collect();
+        let mut after: Vec<_> = exchange.after().collect();
+        out += &format!("# exchange {}\n", i);
+        write_diff(&mut out, &mut before, &mut after).unwrap();
+    }
+    Ok(out)
+}
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let out = match args.as_slice() {
+        [path] => {
+            let bytes = read(path);
+            if bytes.starts_with(&MAGIC) {
+                dump_recording(&bytes)
+            } else {
+                let mut out = String::new();
+                write_block(&mut out, &mut words(&bytes)).unwrap();
+                Ok(out)
+            }
+        }
+        [before, after] => {
+            let mut out = String::new();
+            write_diff(
+                &mut out,
+                &mut words(&read(before)),
+                &mut words(&read(after)),
+            )
+            .unwrap();
+            Ok(out)
+        }
+        _ => {
+            eprintln!("usage: sallyport-dump BLOCK [AFTER]\n       sallyport-dump RECORDING");
+            exit(2)
+        }
+    };
+    match out {
+        Ok(out) => print!("{}", out),
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1)
+        }
+    }
+}
@@ -345,6 +345,13 @@ pub trait Collect {
     fn collect(self, col: &impl Collector) -> Self::Item;
 }
 
+impl Collect for () {
+    type Item = ();
+
+    #[inline]
+    fn collect(self, _: &impl Collector) {}
+}
+
 impl<T: Collect> Collect for Option<T> {
     type Item = Option<T::Item>;
 
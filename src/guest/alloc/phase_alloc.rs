@@ -63,6 +63,22 @@ impl<'a> Alloc<'a, phase::Init> {
         }
     }
 
+    /// Constructs and returns a new allocator over the block pointed to by `block`.
+    ///
+    /// # Safety
+    ///
+    /// `block` must be valid for reads and writes and not be accessed by anything but the
+    /// allocator and the calls allocated within it for `'a`.
+    #[inline]
+    pub(crate) unsafe fn from_raw(block: NonNull<[usize]>) -> Self {
+        Self {
+            ptr: NonNull::slice_from_raw_parts(block.cast(), block.len() * size_of::<usize>()),
+            offset: 0,
+
+            phase: PhantomData,
+        }
+    }
+
     /// Begins allocation by transitioning the allocator into stage phase.
     #[inline]
     pub fn stage(&mut self) -> Alloc<'a, phase::Stage> {
@@ -36,6 +36,18 @@ pub(crate) mod kind {
     impl Kind for Enarxcall {
         const ITEM: item::Kind = item::Kind::Enarxcall;
     }
+
+    #[repr(transparent)]
+    pub struct Hello;
+    impl Kind for Hello {
+        const ITEM: item::Kind = item::Kind::Hello;
+    }
+
+    #[repr(transparent)]
+    pub struct Link;
+    impl Kind for Link {
+        const ITEM: item::Kind = item::Kind::Link;
+    }
 }
 
 /// A generic call, which can be allocated within the block.
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::alloc::kind;
+use super::enarxcall::types::Result as EnarxcallResult;
+use super::Alloc;
+use crate::guest::alloc::{
+    Allocator, Collect, Collector, Commit, Committer, InOutRef, Input, OutRef, Output,
+};
+use crate::item::hello::Reply;
+use crate::libc::{ENOSYS, EPROTO};
+use crate::{Result, REQUIRES, VERSION};
+
+/// Protocol version handshake call, which passes the [`VERSION`] and [`REQUIRES`] of the guest
+/// to the host and writes the [reply](Reply) of the host in `reply` field.
+pub struct Hello<'a> {
+    pub reply: &'a mut Reply,
+}
+
+/// Staged [`Hello`] call.
+pub struct StagedHello<'a> {
+    argv: Input<'a, [usize; 5], [usize; 5]>,
+    ret_ref: InOutRef<'a, usize>,
+    version: Input<'a, [u8], &'static [u8]>,
+    requires: Input<'a, [u8], &'static [u8]>,
+    reply: Output<'a, Reply, &'a mut Reply>,
+}
+
+impl<'a> Commit for StagedHello<'a> {
+    type Item = CommittedHello<'a>;
+
+    #[inline]
+    fn commit(mut self, com: &impl Committer) -> Self::Item {
+        self.argv.commit(com);
+        self.ret_ref.copy_from(com, -ENOSYS as usize);
+        self.version.commit(com);
+        self.requires.commit(com);
+        Self::Item {
+            ret_ref: self.ret_ref.commit(com),
+            reply: self.reply.commit(com),
+        }
+    }
+}
+
+/// Committed [`Hello`] call.
+pub struct CommittedHello<'a> {
+    ret_ref: OutRef<'a, usize>,
+    reply: Output<'a, Reply, &'a mut Reply>,
+}
+
+impl<'a> Collect for CommittedHello<'a> {
+    type Item = Result<()>;
+
+    #[inline]
+    fn collect(self, col: &impl Collector) -> Self::Item {
+        let mut ret = 0usize;
+        self.ret_ref.copy_to(col, &mut ret);
+        let res: Result<()> = EnarxcallResult::from(ret).into();
+        if matches!(res, Ok(()) | Err(EPROTO)) {
+            self.reply.collect(col);
+        }
+        res
+    }
+}
+
+impl<'a> Alloc<'a, kind::Hello> for Hello<'a> {
+    type Staged = StagedHello<'a>;
+    type Committed = CommittedHello<'a>;
+    type Collected = Result<()>;
+
+    #[inline]
+    fn stage(self, alloc: &mut impl Allocator) -> Result<Self::Staged> {
+        let argv_ref = alloc.allocate_input()?;
+        let ret_ref = alloc.allocate_inout()?;
+        let ((version, requires, reply), _) = alloc.section(|alloc| {
+            let version = Input::stage_slice(alloc, VERSION.as_bytes())?;
+            let requires = Input::stage_slice(alloc, &REQUIRES[..])?;
+            let reply = Output::stage(alloc, self.reply)?;
+            Ok((version, requires, reply))
+        })?;
+        Ok(Self::Staged {
+            argv: argv_ref.stage([
+                version.offset(),
+                version.len(),
+                requires.offset(),
+                requires.len(),
+                reply.offset(),
+            ]),
+            ret_ref,
+            version,
+            requires,
+            reply,
+        })
+    }
+}
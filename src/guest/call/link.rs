@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::alloc::kind;
+use super::Alloc;
+use crate::guest::alloc::{Allocator, Input};
+use crate::Result;
+
+/// Link call, which instructs the host to replace the argument at index `arg` of the next call
+/// by the first return value of the call `back` calls before it.
+///
+/// This allows execution of dependent calls within a single [`sally`](crate::guest::Handler::sally),
+/// for example, `(Open { .. }, Link { back: 1, arg: 0 }, Read { fd: -1, .. })` reads from
+/// the opened file. If the referenced call fails, the linked call is not executed and fails with
+/// [`ECANCELED`](crate::libc::ECANCELED).
+pub struct Link {
+    pub back: usize,
+    pub arg: usize,
+}
+
+impl<'a> Alloc<'a, kind::Link> for Link {
+    type Staged = Input<'a, [usize; 2], [usize; 2]>;
+    type Committed = ();
+    type Collected = ();
+
+    #[inline]
+    fn stage(self, alloc: &mut impl Allocator) -> Result<Self::Staged> {
+        let argv_ref = alloc.allocate_input()?;
+        Ok(argv_ref.stage([self.back, self.arg]))
+    }
+}
@@ -9,10 +9,14 @@ pub mod gdbcall;
 pub mod syscall;
 pub mod types;
 
+mod hello;
+mod link;
 mod maybe_alloc;
 mod stub;
 
 pub use alloc::Alloc;
+pub use hello::*;
+pub use link::*;
 pub use maybe_alloc::*;
 pub use stub::*;
 
@@ -22,10 +26,14 @@ use crate::Result;
 /// Call kinds.
 pub mod kind {
     use super::alloc;
+    use crate::item;
 
     use core::marker::PhantomData;
 
-    pub trait Kind {}
+    pub trait Kind {
+        /// Whether the call performs the protocol version handshake.
+        const HELLO: bool = false;
+    }
 
     #[repr(transparent)]
     pub struct Stub;
@@ -35,7 +43,12 @@ pub mod kind {
     pub struct Alloc<K>(PhantomData<K>)
     where
         K: alloc::kind::Kind;
-    impl<K> Kind for Alloc<K> where K: alloc::kind::Kind {}
+    impl<K> Kind for Alloc<K>
+    where
+        K: alloc::kind::Kind,
+    {
+        const HELLO: bool = matches!(K::ITEM, item::Kind::Hello);
+    }
 
     #[repr(transparent)]
     pub struct MaybeAlloc<K>(PhantomData<K>)
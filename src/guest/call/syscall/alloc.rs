@@ -96,6 +96,16 @@ pub unsafe trait Alloc<'a> {
     /// For example, [`Option<Result<libc::size_t>>`].
     type Collected;
 
+    /// Returns the number of the syscall staged as `staged`.
+    ///
+    /// Defaults to [`Self::NUM`], but may be overridden by syscalls falling back to another
+    /// syscall depending on the capacity of the block.
+    #[inline]
+    fn num(staged: &Self::Staged) -> c_long {
+        let _ = staged;
+        Self::NUM
+    }
+
     /// Allocate dynamic data, if necessary and return resulting argument vector registers
     /// and opaque [staged value](Self::Staged) on success.
     fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)>;
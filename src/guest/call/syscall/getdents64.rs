@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::super::types::Argv;
+use super::Alloc;
+use crate::guest::alloc::{Allocator, Collector, Output};
+use crate::libc::SYS_getdents64;
+use crate::Result;
+
+use core::ffi::{c_int, c_long, c_size_t};
+use core::mem::size_of;
+
+/// Offset of `d_reclen` within a `linux_dirent64` record.
+const RECLEN_OFFSET: usize = 16;
+
+/// Offset of `d_name` within a `linux_dirent64` record.
+const NAME_OFFSET: usize = 19;
+
+pub struct Getdents64<'a> {
+    pub fd: c_int,
+    pub dirp: &'a mut [u8],
+}
+
+unsafe impl<'a> Alloc<'a> for Getdents64<'a> {
+    const NUM: c_long = SYS_getdents64;
+
+    type Argv = Argv<3>;
+    type Ret = c_size_t;
+
+    type Staged = Output<'a, [u8], &'a mut [u8]>;
+    type Committed = Self::Staged;
+    type Collected = Option<Result<c_size_t>>;
+
+    fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
+        let (dirp, _) = Output::stage_slice_max(alloc, self.dirp)?;
+        Ok((Argv([self.fd as _, dirp.offset(), dirp.len()]), dirp))
+    }
+
+    fn collect(
+        dirp: Self::Committed,
+        ret: Result<Self::Ret>,
+        col: &impl Collector,
+    ) -> Self::Collected {
+        match ret {
+            Ok(ret) if ret > dirp.len() => None,
+            res @ Ok(ret) => {
+                let dirp = unsafe { dirp.collect_range(col, 0..ret) };
+                is_valid_dirents(&dirp[..ret]).then_some(res)
+            }
+            err => Some(err),
+        }
+    }
+}
+
+/// Returns `true` if `dirents` returned by the host consists of well-formed `linux_dirent64`
+/// records only, i.e. each record is 8-byte aligned, lies within `dirents` and contains
+/// a nul-terminated name.
+fn is_valid_dirents(mut dirents: &[u8]) -> bool {
+    while !dirents.is_empty() {
+        let reclen = match dirents.get(RECLEN_OFFSET..RECLEN_OFFSET + size_of::<u16>()) {
+            Some(reclen) => u16::from_ne_bytes([reclen[0], reclen[1]]) as usize,
+            None => return false,
+        };
+        if reclen <= NAME_OFFSET || reclen % 8 != 0 || reclen > dirents.len() {
+            return false;
+        }
+        let (record, rest) = dirents.split_at(reclen);
+        if !record[NAME_OFFSET..].contains(&0) {
+            return false;
+        }
+        dirents = rest;
+    }
+    true
+}
@@ -16,20 +16,32 @@ mod epoll_ctl;
 mod epoll_pwait;
 mod epoll_wait;
 mod fcntl;
+mod fstat;
+mod getdents64;
 mod getsockname;
 mod ioctl;
+mod mkdirat;
 mod nanosleep;
+mod newfstatat;
 mod open;
+mod openat;
 mod passthrough;
 mod poll;
+mod pread64;
+mod pwrite64;
 mod read;
 mod readv;
 mod recv;
 mod recvfrom;
+mod recvmsg;
+mod renameat2;
 mod send;
+mod sendmsg;
 mod sendto;
 mod setsockopt;
+mod statx;
 mod stub;
+mod unlinkat;
 mod write;
 mod writev;
 
@@ -46,22 +58,39 @@ pub use epoll_ctl::*;
 pub use epoll_pwait::EpollPwait;
 pub use epoll_wait::*;
 pub use fcntl::Fcntl;
+pub use fstat::*;
+pub use getdents64::*;
 pub use getsockname::*;
 pub use ioctl::*;
+pub use mkdirat::*;
 pub use nanosleep::*;
+pub use newfstatat::*;
 pub use open::*;
+pub use openat::*;
 pub use passthrough::*;
 pub use poll::*;
+pub use pread64::*;
+pub use pwrite64::*;
 pub use read::*;
-pub use readv::Readv;
+pub use readv::{Preadv, Readv};
 pub use recv::*;
 pub use recvfrom::*;
+pub use recvmsg::*;
+pub use renameat2::*;
 pub use send::*;
+pub use sendmsg::*;
 pub use sendto::*;
 pub use setsockopt::*;
+pub use statx::*;
 pub use stub::*;
+pub use unlinkat::*;
 pub use write::*;
-pub use writev::Writev;
+pub use writev::{Pwritev, Writev};
+
+use crate::guest::alloc::Allocator;
+use crate::libc::iovec;
+
+use core::mem::{align_of, size_of};
 
 /// Computes the sum of length of all `iovec` elements in a `iov`.
 pub(super) fn iov_len<'a, T, U>(iter: &'a T) -> usize
@@ -72,3 +101,52 @@ where
 {
     iter.into_iter().map(|iov| iov.as_ref().len()).sum()
 }
+
+/// Returns the count and the total length of the leading elements of `iter`, for which `alloc`
+/// can allocate `iovec` elements along with at least one byte of data, or `None` if these do not
+/// reference any data, while `iter` does.
+pub(super) fn iov_fit<'a, T, U>(alloc: &impl Allocator, iter: &'a T) -> Option<(usize, usize)>
+where
+    T: ?Sized,
+    &'a T: IntoIterator<Item = U>,
+    U: AsRef<[u8]>,
+{
+    // Room is left for the padding of the `iovec` array and a single byte of data.
+    let max = alloc.free::<u8>().saturating_sub(align_of::<iovec>()) / size_of::<iovec>();
+    let (count, len) = iter
+        .into_iter()
+        .take(max)
+        .fold((0, 0), |(count, len), iov| {
+            (count + 1, len + iov.as_ref().len())
+        });
+    if len == 0 && iov_len(iter) > 0 {
+        None
+    } else {
+        Some((count, len))
+    }
+}
+
+/// Returns an iterator over single-element arrays of `iovec` elements referencing consecutive
+/// ranges of a buffer of `len` bytes at `offset` within the data section, which preserve the
+/// boundaries of `iter` up to `len`.
+pub(super) fn iovecs<'a, T, U>(
+    iter: &'a T,
+    mut offset: usize,
+    mut len: usize,
+) -> impl Iterator<Item = [iovec; 1]> + 'a
+where
+    T: ?Sized,
+    &'a T: IntoIterator<Item = U>,
+    U: AsRef<[u8]>,
+{
+    iter.into_iter().map(move |iov| {
+        let iov_len = iov.as_ref().len().min(len);
+        let iov = iovec {
+            iov_base: offset as _,
+            iov_len,
+        };
+        offset += iov_len;
+        len -= iov_len;
+        [iov]
+    })
+}
@@ -4,8 +4,9 @@ use super::super::types::Argv;
 use super::Alloc;
 use crate::guest::alloc::{Allocator, Collector};
 use crate::libc::{
-    SYS_close, SYS_dup, SYS_dup2, SYS_dup3, SYS_epoll_create1, SYS_eventfd2, SYS_exit,
-    SYS_exit_group, SYS_listen, SYS_socket, SYS_sync,
+    mode_t, off_t, SYS_close, SYS_dup, SYS_dup2, SYS_dup3, SYS_epoll_create1, SYS_eventfd2,
+    SYS_exit, SYS_exit_group, SYS_fchmod, SYS_ftruncate, SYS_listen, SYS_lseek, SYS_socket,
+    SYS_sync,
 };
 use crate::Result;
 
@@ -207,6 +208,38 @@ unsafe impl PassthroughAlloc for ExitGroup {
     }
 }
 
+pub struct Fchmod {
+    pub fd: c_int,
+    pub mode: mode_t,
+}
+
+unsafe impl PassthroughAlloc for Fchmod {
+    const NUM: c_long = SYS_fchmod;
+
+    type Argv = Argv<2>;
+    type Ret = ();
+
+    fn stage(self) -> Self::Argv {
+        Argv([self.fd as _, self.mode as _])
+    }
+}
+
+pub struct Ftruncate {
+    pub fd: c_int,
+    pub length: off_t,
+}
+
+unsafe impl PassthroughAlloc for Ftruncate {
+    const NUM: c_long = SYS_ftruncate;
+
+    type Argv = Argv<2>;
+    type Ret = ();
+
+    fn stage(self) -> Self::Argv {
+        Argv([self.fd as _, self.length as _])
+    }
+}
+
 pub struct Listen {
     pub sockfd: c_int,
     pub backlog: c_int,
@@ -223,6 +256,23 @@ unsafe impl PassthroughAlloc for Listen {
     }
 }
 
+pub struct Lseek {
+    pub fd: c_int,
+    pub offset: off_t,
+    pub whence: c_int,
+}
+
+unsafe impl PassthroughAlloc for Lseek {
+    const NUM: c_long = SYS_lseek;
+
+    type Argv = Argv<3>;
+    type Ret = off_t;
+
+    fn stage(self) -> Self::Argv {
+        Argv([self.fd as _, self.offset as _, self.whence as _])
+    }
+}
+
 pub struct Socket {
     pub domain: c_int,
     pub typ: c_int,
@@ -1,9 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::super::types::Argv;
-use super::{iov_len, Alloc};
-use crate::guest::alloc::{Allocator, Collector, CommitPassthrough, OutRef};
-use crate::libc::SYS_read;
+use super::{iov_fit, iov_len, iovecs, Alloc};
+use crate::guest::alloc::{Allocator, Collector, Commit, Committer, InRef, OutRef};
+use crate::libc::{iovec, off_t, SYS_pread64, SYS_preadv, SYS_read, SYS_readv};
 use crate::Result;
 
 use core::ffi::{c_int, c_long, c_size_t};
@@ -13,12 +13,123 @@ pub struct Readv<T> {
     pub iovs: T,
 }
 
+pub struct Preadv<T> {
+    pub fd: c_int,
+    pub iovs: T,
+    pub offset: off_t,
+}
+
 pub struct StagedReadv<'a, T> {
+    /// `iovec` array, unless the data is read into a single buffer.
+    pub(super) iovecs: Option<InRef<'a, [iovec]>>,
     buf: OutRef<'a, [u8]>,
     iovs: T,
 }
 
-impl<T> CommitPassthrough for StagedReadv<'_, T> {}
+pub struct CommittedReadv<'a, T> {
+    buf: OutRef<'a, [u8]>,
+    iovs: T,
+}
+
+impl<'a, T: ?Sized, U> StagedReadv<'a, &'a mut T>
+where
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    U: AsRef<[u8]>,
+{
+    /// Allocates an `iovec` array of as many leading `iovs` as leave room for at least one byte
+    /// of data followed by a buffer of at most their total length depending on capacity.
+    ///
+    /// If the leading `iovs` fitting do not reference any data, only a buffer of at most the
+    /// total length of `iovs` is allocated to read the data into instead.
+    pub(super) fn stage(alloc: &mut impl Allocator, iovs: &'a mut T) -> Result<Self> {
+        let (iovecs, len) = match iov_fit(alloc, &*iovs) {
+            Some((count, len)) => (Some(alloc.allocate_input_slice(count)?), len),
+            None => (None, iov_len(&*iovs)),
+        };
+        let buf = alloc.allocate_output_slice_max(len)?;
+        Ok(Self { iovecs, buf, iovs })
+    }
+
+    /// Returns the offset and the length of the `iovec` array or, if there is none, of the buffer.
+    fn args(&self) -> (usize, usize) {
+        match &self.iovecs {
+            Some(iovecs) => (iovecs.offset(), iovecs.len()),
+            None => (self.buf.offset(), self.buf.len()),
+        }
+    }
+}
+
+impl<'a, T: ?Sized, U> Commit for StagedReadv<'a, &'a mut T>
+where
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    U: AsRef<[u8]>,
+{
+    type Item = CommittedReadv<'a, &'a mut T>;
+
+    fn commit(mut self, com: &impl Committer) -> Self::Item {
+        if let Some(array) = &mut self.iovecs {
+            let iovs = iovecs(&*self.iovs, self.buf.offset(), self.buf.len()).take(array.len());
+            unsafe { array.copy_from_iter_unchecked(com, iovs) };
+        }
+        CommittedReadv {
+            buf: self.buf,
+            iovs: self.iovs,
+        }
+    }
+}
+
+/// Copies the count of bytes read `ret` from `buf` into `iovs`.
+pub(super) fn collect<'a, T: ?Sized, V>(
+    CommittedReadv { buf, iovs }: CommittedReadv<'a, &'a mut T>,
+    ret: Result<c_size_t>,
+    col: &impl Collector,
+) -> Option<Result<c_size_t>>
+where
+    for<'b> &'b mut T: IntoIterator<Item = &'b mut V>,
+    V: AsMut<[u8]>,
+{
+    #[inline]
+    fn collect_iovs<'a, T, V>(
+        col: &impl Collector,
+        iovs: &'a mut T,
+        buf: OutRef<'a, [u8]>,
+        mut capacity: usize,
+    ) where
+        for<'b> &'b mut T: IntoIterator<Item = &'b mut V>,
+        T: ?Sized,
+        V: AsMut<[u8]>,
+    {
+        unsafe {
+            buf.copy_to_iter_unchecked(
+                col,
+                iovs.into_iter().map_while(|iov| {
+                    if capacity == 0 {
+                        return None;
+                    }
+                    let iov = iov.as_mut();
+                    let len = iov.len();
+                    if len <= capacity {
+                        capacity -= len;
+                        Some(iov)
+                    } else {
+                        let mid = capacity;
+                        capacity = 0;
+                        Some(iov.split_at_mut(mid).0)
+                    }
+                }),
+            )
+        }
+    }
+
+    match ret {
+        Ok(ret) if ret > buf.len() => None,
+        res @ Ok(ret) => {
+            collect_iovs(col, iovs, buf, ret);
+            Some(res)
+        }
+        err => Some(err),
+    }
+}
 
 unsafe impl<'a, T: ?Sized, U, V> Alloc<'a> for Readv<&'a mut T>
 where
@@ -27,71 +138,73 @@ where
     U: AsRef<[u8]>,
     V: AsMut<[u8]>,
 {
-    const NUM: c_long = SYS_read;
+    const NUM: c_long = SYS_readv;
 
     type Argv = Argv<3>;
     type Ret = c_size_t;
 
     type Staged = StagedReadv<'a, &'a mut T>;
-    type Committed = Self::Staged;
+    type Committed = CommittedReadv<'a, &'a mut T>;
     type Collected = Option<Result<c_size_t>>;
 
+    fn num(staged: &Self::Staged) -> c_long {
+        if staged.iovecs.is_some() {
+            SYS_readv
+        } else {
+            SYS_read
+        }
+    }
+
     fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
-        let buf = alloc.allocate_output_slice_max(iov_len(self.iovs as &T))?;
-        Ok((
-            Argv([self.fd as _, buf.offset(), buf.len()]),
-            StagedReadv {
-                iovs: self.iovs,
-                buf,
-            },
-        ))
+        let staged = StagedReadv::stage(alloc, self.iovs)?;
+        let (offset, len) = staged.args();
+        Ok((Argv([self.fd as _, offset, len]), staged))
     }
 
     fn collect(
-        Self::Committed { iovs, buf }: Self::Committed,
+        committed: Self::Committed,
         ret: Result<Self::Ret>,
         col: &impl Collector,
     ) -> Self::Collected {
-        #[inline]
-        fn collect_iovs<'a, T, V>(
-            col: &impl Collector,
-            iovs: &'a mut T,
-            buf: OutRef<'a, [u8]>,
-            mut capacity: usize,
-        ) where
-            for<'b> &'b mut T: IntoIterator<Item = &'b mut V>,
-            T: ?Sized,
-            V: AsMut<[u8]>,
-        {
-            unsafe {
-                buf.copy_to_iter_unchecked(
-                    col,
-                    iovs.into_iter().map_while(|iov| {
-                        if capacity == 0 {
-                            return None;
-                        }
-                        let iov = iov.as_mut();
-                        let len = iov.len();
-                        if len <= capacity {
-                            capacity -= len;
-                            Some(iov)
-                        } else {
-                            let mid = capacity;
-                            capacity = 0;
-                            Some(iov.split_at_mut(mid).0)
-                        }
-                    }),
-                )
-            }
-        }
+        collect(committed, ret, col)
+    }
+}
 
-        match ret {
-            Ok(ret) if ret > buf.len() => None,
-            res @ Ok(ret) => {
-                collect_iovs(col, iovs, buf, ret);
-                Some(res)
-            }
-            err => Some(err),
+unsafe impl<'a, T: ?Sized, U, V> Alloc<'a> for Preadv<&'a mut T>
+where
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    for<'b> &'b mut T: IntoIterator<Item = &'b mut V>,
+    U: AsRef<[u8]>,
+    V: AsMut<[u8]>,
+{
+    const NUM: c_long = SYS_preadv;
+
+    type Argv = Argv<4>;
+    type Ret = c_size_t;
+
+    type Staged = StagedReadv<'a, &'a mut T>;
+    type Committed = CommittedReadv<'a, &'a mut T>;
+    type Collected = Option<Result<c_size_t>>;
+
+    fn num(staged: &Self::Staged) -> c_long {
+        if staged.iovecs.is_some() {
+            SYS_preadv
+        } else {
+            SYS_pread64
         }
     }
+
+    fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
+        let staged = StagedReadv::stage(alloc, self.iovs)?;
+        let (offset, len) = staged.args();
+        Ok((Argv([self.fd as _, offset, len, self.offset as _]), staged))
+    }
+
+    fn collect(
+        committed: Self::Committed,
+        ret: Result<Self::Ret>,
+        col: &impl Collector,
+    ) -> Self::Collected {
+        collect(committed, ret, col)
+    }
 }
@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::super::types::Argv;
+use super::readv::{self, CommittedReadv, StagedReadv};
+use super::types::{MsghdrOutput, SockaddrOutput};
+use super::Alloc;
+use crate::guest::alloc::{Allocator, Collect, Collector, Commit, Committer, InOut, Output};
+use crate::item::syscall::Cmsgs;
+use crate::libc::{
+    cmsghdr, msghdr, sockaddr_storage, socklen_t, ucred, SYS_recvmsg, ENOMEM, EOVERFLOW,
+    MSG_CTRUNC, SCM_CREDENTIALS, SCM_RIGHTS, SOL_SOCKET,
+};
+use crate::{Result, NULL};
+
+use core::alloc::Layout;
+use core::ffi::{c_int, c_long, c_size_t};
+use core::mem::{align_of, size_of};
+
+pub struct Recvmsg<'a, T: ?Sized> {
+    pub sockfd: c_int,
+    pub msg: MsghdrOutput<'a, T>,
+    pub flags: c_int,
+}
+
+/// Staged address buffer along with the address length to write back.
+type StagedName<'a> = (Output<'a, [u8], &'a mut [u8]>, &'a mut socklen_t);
+
+pub struct StagedRecvmsg<'a, T: ?Sized> {
+    msg: InOut<'a, msghdr, msghdr>,
+    name: Option<StagedName<'a>>,
+    control: Option<Output<'a, [u8], &'a mut [u8]>>,
+    controllen: &'a mut c_size_t,
+    flags: &'a mut c_int,
+    iov: StagedReadv<'a, &'a mut T>,
+}
+
+pub struct CommittedRecvmsg<'a, T: ?Sized> {
+    msg: Output<'a, msghdr, msghdr>,
+    name: Option<StagedName<'a>>,
+    control: Option<Output<'a, [u8], &'a mut [u8]>>,
+    controllen: &'a mut c_size_t,
+    flags: &'a mut c_int,
+    iov: CommittedReadv<'a, &'a mut T>,
+}
+
+impl<'a, T, U> Commit for StagedRecvmsg<'a, T>
+where
+    T: ?Sized,
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    U: AsRef<[u8]>,
+{
+    type Item = CommittedRecvmsg<'a, T>;
+
+    fn commit(self, com: &impl Committer) -> Self::Item {
+        CommittedRecvmsg {
+            msg: self.msg.commit(com),
+            name: self.name,
+            control: self.control,
+            controllen: self.controllen,
+            flags: self.flags,
+            iov: self.iov.commit(com),
+        }
+    }
+}
+
+/// Allocates an output buffer of the length of `buf` aligned to `align`.
+fn stage_aligned<'a>(
+    alloc: &mut impl Allocator,
+    buf: &'a mut [u8],
+    align: usize,
+) -> Result<Output<'a, [u8], &'a mut [u8]>> {
+    let layout = Layout::from_size_align(buf.len(), align).map_err(|_| EOVERFLOW)?;
+    let data_ref = alloc.allocate_output_layout(layout)?;
+    Ok(unsafe { Output::new_unchecked(data_ref, buf) })
+}
+
+/// Returns `true` if the control messages in `control` returned by the host are well-formed,
+/// i.e. they fit into `control` and the data of `SCM_RIGHTS` and `SCM_CREDENTIALS` messages
+/// has the expected size. Messages may only be shorter if they were `truncated`.
+fn is_valid_control(control: &[u8], truncated: bool) -> bool {
+    Cmsgs::new(control).all(|cmsg| match cmsg {
+        Ok((
+            cmsghdr {
+                cmsg_level: SOL_SOCKET,
+                cmsg_type: SCM_RIGHTS,
+                ..
+            },
+            data,
+        )) => data.len() % size_of::<c_int>() == 0,
+        Ok((
+            cmsghdr {
+                cmsg_level: SOL_SOCKET,
+                cmsg_type: SCM_CREDENTIALS,
+                ..
+            },
+            data,
+        )) => data.len() == size_of::<ucred>() || truncated && data.len() < size_of::<ucred>(),
+        Ok(_) => true,
+        Err(_) => false,
+    })
+}
+
+unsafe impl<'a, T, U, V> Alloc<'a> for Recvmsg<'a, T>
+where
+    T: ?Sized,
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    for<'b> &'b mut T: IntoIterator<Item = &'b mut V>,
+    U: AsRef<[u8]>,
+    V: AsMut<[u8]>,
+{
+    const NUM: c_long = SYS_recvmsg;
+
+    type Argv = Argv<3>;
+    type Ret = c_size_t;
+
+    type Staged = StagedRecvmsg<'a, T>;
+    type Committed = CommittedRecvmsg<'a, T>;
+    type Collected = Option<Result<c_size_t>>;
+
+    fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
+        let MsghdrOutput {
+            name,
+            iov,
+            control,
+            controllen,
+            flags,
+        } = self.msg;
+        let msg = alloc.allocate_inout()?;
+        let name = match name {
+            Some(SockaddrOutput { addr, addrlen }) => Some((
+                stage_aligned(alloc, addr, align_of::<sockaddr_storage>())?,
+                addrlen,
+            )),
+            None => None,
+        };
+        let control = match control {
+            [] => None,
+            control => Some(stage_aligned(alloc, control, align_of::<cmsghdr>())?),
+        };
+        let iov = StagedReadv::stage(alloc, iov)?;
+        // Messages are only received into `iovec` elements.
+        let iovecs = iov.iovecs.as_ref().ok_or(ENOMEM)?;
+        let (name_offset, namelen) = name
+            .as_ref()
+            .map_or((NULL, 0), |(name, _)| (name.offset(), name.len()));
+        let (control_offset, controllen_max) = control
+            .as_ref()
+            .map_or((NULL, 0), |control| (control.offset(), control.len()));
+        let msg = msg.stage(msghdr {
+            msg_name: name_offset as _,
+            msg_namelen: namelen as _,
+            msg_iov: iovecs.offset() as _,
+            msg_iovlen: iovecs.len(),
+            msg_control: control_offset as _,
+            msg_controllen: controllen_max,
+            msg_flags: 0,
+        });
+        Ok((
+            Argv([self.sockfd as _, msg.offset(), self.flags as _]),
+            StagedRecvmsg {
+                msg,
+                name,
+                control,
+                controllen,
+                flags,
+                iov,
+            },
+        ))
+    }
+
+    fn collect(
+        CommittedRecvmsg {
+            msg,
+            name,
+            control,
+            controllen,
+            flags,
+            iov,
+        }: Self::Committed,
+        ret: Result<Self::Ret>,
+        col: &impl Collector,
+    ) -> Self::Collected {
+        let ret = match readv::collect(iov, ret, col)? {
+            Ok(ret) => ret,
+            err => return Some(err),
+        };
+        let msg = msg.collect(col);
+        let len = msg.msg_controllen;
+        let truncated = msg.msg_flags & MSG_CTRUNC != 0;
+        match control {
+            Some(control) if len <= control.len() => {
+                let control = unsafe { control.collect_range(col, 0..len) };
+                if !is_valid_control(&control[..len], truncated) {
+                    return None;
+                }
+            }
+            None if len == 0 => {}
+            _ => return None,
+        }
+        if let Some((addr, addrlen)) = name {
+            let len = addr.len().min(msg.msg_namelen as _);
+            unsafe { addr.collect_range(col, 0..len) };
+            *addrlen = msg.msg_namelen;
+        }
+        *controllen = len;
+        *flags = msg.msg_flags;
+        Some(Ok(ret))
+    }
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::super::types::Argv;
+use super::Alloc;
+use crate::guest::alloc::{Allocator, Collector, Input};
+use crate::libc::SYS_renameat2;
+use crate::Result;
+
+use core::ffi::{c_int, c_long, c_uint};
+
+pub struct Renameat2<'a> {
+    pub olddirfd: c_int,
+    pub oldpath: &'a [u8],
+    pub newdirfd: c_int,
+    pub newpath: &'a [u8],
+    pub flags: c_uint,
+}
+
+unsafe impl<'a> Alloc<'a> for Renameat2<'a> {
+    const NUM: c_long = SYS_renameat2;
+
+    type Argv = Argv<5>;
+    type Ret = ();
+
+    type Staged = (
+        Input<'a, [u8], &'a [u8]>, // oldpath
+        Input<'a, [u8], &'a [u8]>, // newpath
+    );
+    type Committed = ((), ());
+    type Collected = Result<()>;
+
+    fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
+        // The argument vector has no room for the path lengths, hence the paths are passed as
+        // offsets of nul-terminated strings only.
+        let oldpath = Input::stage_slice(alloc, self.oldpath)?;
+        let newpath = Input::stage_slice(alloc, self.newpath)?;
+        Ok((
+            Argv([
+                self.olddirfd as _,
+                oldpath.offset(),
+                self.newdirfd as _,
+                newpath.offset(),
+                self.flags as _,
+            ]),
+            (oldpath, newpath),
+        ))
+    }
+
+    fn collect(_: Self::Committed, ret: Result<Self::Ret>, _: &impl Collector) -> Self::Collected {
+        ret
+    }
+}
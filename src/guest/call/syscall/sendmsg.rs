@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::super::types::Argv;
+use super::types::{MsghdrInput, StagedSockaddrInput};
+use super::writev::{self, StagedWritev};
+use super::Alloc;
+use crate::guest::alloc::{Allocator, Collector, Commit, Committer, Input, Stage};
+use crate::libc::{cmsghdr, msghdr, SYS_sendmsg, ENOMEM, EOVERFLOW};
+use crate::{Result, NULL};
+
+use core::alloc::Layout;
+use core::ffi::{c_int, c_long, c_size_t};
+use core::mem::align_of;
+
+pub struct Sendmsg<'a, T: ?Sized> {
+    pub sockfd: c_int,
+    pub msg: MsghdrInput<'a, T>,
+    pub flags: c_int,
+}
+
+pub struct StagedSendmsg<'a, T: ?Sized> {
+    msg: Input<'a, msghdr, msghdr>,
+    name: Option<StagedSockaddrInput<'a>>,
+    control: Option<Input<'a, [u8], &'a [u8]>>,
+    iov: StagedWritev<'a, &'a T>,
+}
+
+impl<'a, T, U> Commit for StagedSendmsg<'a, T>
+where
+    T: ?Sized,
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    U: AsRef<[u8]>,
+{
+    type Item = c_size_t;
+
+    fn commit(self, com: &impl Committer) -> Self::Item {
+        self.msg.commit(com);
+        self.name.commit(com);
+        self.control.commit(com);
+        self.iov.commit(com)
+    }
+}
+
+unsafe impl<'a, T, U> Alloc<'a> for Sendmsg<'a, T>
+where
+    T: ?Sized,
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    U: AsRef<[u8]>,
+{
+    const NUM: c_long = SYS_sendmsg;
+
+    type Argv = Argv<3>;
+    type Ret = c_size_t;
+
+    type Staged = StagedSendmsg<'a, T>;
+    type Committed = c_size_t;
+    type Collected = Option<Result<c_size_t>>;
+
+    fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
+        let MsghdrInput { name, iov, control } = self.msg;
+        let msg = alloc.allocate_input()?;
+        let name = name.stage(alloc)?;
+        let control = match control {
+            [] => None,
+            control => {
+                let layout = Layout::from_size_align(control.len(), align_of::<cmsghdr>())
+                    .map_err(|_| EOVERFLOW)?;
+                let data_ref = alloc.allocate_input_layout(layout)?;
+                Some(unsafe { Input::new_unchecked(data_ref, control) })
+            }
+        };
+        let iov = StagedWritev::stage(alloc, iov)?;
+        // Messages are only sent from `iovec` elements.
+        let iovecs = iov.iovecs.as_ref().ok_or(ENOMEM)?;
+        let (name_offset, namelen) = name
+            .as_ref()
+            .map_or((NULL, 0), |name| (name.offset(), name.len()));
+        let (control_offset, controllen) = control
+            .as_ref()
+            .map_or((NULL, 0), |control| (control.offset(), control.len()));
+        let msg = msg.stage(msghdr {
+            msg_name: name_offset as _,
+            msg_namelen: namelen as _,
+            msg_iov: iovecs.offset() as _,
+            msg_iovlen: iovecs.len(),
+            msg_control: control_offset as _,
+            msg_controllen: controllen,
+            msg_flags: 0,
+        });
+        Ok((
+            Argv([self.sockfd as _, msg.offset(), self.flags as _]),
+            StagedSendmsg {
+                msg,
+                name,
+                control,
+                iov,
+            },
+        ))
+    }
+
+    fn collect(
+        count: Self::Committed,
+        ret: Result<Self::Ret>,
+        _: &impl Collector,
+    ) -> Self::Collected {
+        writev::collect(count, ret)
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::super::types::Argv;
+use super::Alloc;
+use crate::guest::alloc::{Allocator, Collect, Collector, Commit, Committer, Input, Output};
+use crate::libc::{
+    mode_t, statx, SYS_statx, STATX_TYPE, S_IFBLK, S_IFCHR, S_IFDIR, S_IFIFO, S_IFLNK, S_IFMT,
+    S_IFREG, S_IFSOCK,
+};
+use crate::Result;
+
+use core::ffi::{c_int, c_long, c_uint};
+
+pub struct Statx<'a> {
+    pub dirfd: c_int,
+    pub pathname: &'a [u8],
+    pub flags: c_int,
+    pub mask: c_uint,
+    pub statxbuf: &'a mut statx,
+}
+
+pub struct StagedStatx<'a> {
+    pathname: Input<'a, [u8], &'a [u8]>,
+    statxbuf: Output<'a, statx, &'a mut statx>,
+    mask: c_uint,
+}
+
+impl<'a> Commit for StagedStatx<'a> {
+    type Item = (Output<'a, statx, &'a mut statx>, c_uint);
+
+    fn commit(self, com: &impl Committer) -> Self::Item {
+        self.pathname.commit(com);
+        (self.statxbuf.commit(com), self.mask)
+    }
+}
+
+unsafe impl<'a> Alloc<'a> for Statx<'a> {
+    const NUM: c_long = SYS_statx;
+
+    type Argv = Argv<6>;
+    type Ret = ();
+
+    type Staged = StagedStatx<'a>;
+    type Committed = (Output<'a, statx, &'a mut statx>, c_uint);
+    type Collected = Option<Result<()>>;
+
+    fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
+        let pathname = Input::stage_slice(alloc, self.pathname)?;
+        let statxbuf = Output::stage(alloc, self.statxbuf)?;
+        Ok((
+            Argv([
+                self.dirfd as _,
+                pathname.offset(),
+                pathname.len(),
+                self.flags as _,
+                self.mask as _,
+                statxbuf.offset(),
+            ]),
+            StagedStatx {
+                pathname,
+                statxbuf,
+                mask: self.mask,
+            },
+        ))
+    }
+
+    fn collect(
+        (statxbuf, mask): Self::Committed,
+        ret: Result<Self::Ret>,
+        col: &impl Collector,
+    ) -> Self::Collected {
+        match ret {
+            Ok(()) => {
+                let statx = statxbuf.collect(col);
+                // Linux reports some fields even if not requested, hence these are discarded.
+                statx.stx_mask &= mask;
+                is_valid_statx(statx).then_some(Ok(()))
+            }
+            err => Some(err),
+        }
+    }
+}
+
+/// Returns `true` if `statx` returned by the host is sane, i.e. its file type is known if
+/// reported and its nanosecond fields are less than a second.
+fn is_valid_statx(statx: &statx) -> bool {
+    const NSEC_PER_SEC: u32 = 1_000_000_000;
+
+    (statx.stx_mask & STATX_TYPE == 0
+        || matches!(
+            statx.stx_mode as mode_t & S_IFMT,
+            S_IFBLK | S_IFCHR | S_IFDIR | S_IFIFO | S_IFLNK | S_IFREG | S_IFSOCK
+        ))
+        && [
+            statx.stx_atime,
+            statx.stx_btime,
+            statx.stx_ctime,
+            statx.stx_mtime,
+        ]
+        .iter()
+        .all(|ts| ts.tv_nsec < NSEC_PER_SEC)
+}
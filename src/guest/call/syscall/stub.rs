@@ -3,13 +3,12 @@
 use super::super::Stub;
 use crate::guest::alloc::Collector;
 use crate::libc::{
-    gid_t, pid_t, sigset_t, stack_t, stat, uid_t, utsname, EAGAIN, EBADFD, EINVAL, ENOENT,
-    GRND_NONBLOCK, GRND_RANDOM, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, S_IFIFO,
+    gid_t, pid_t, sigset_t, stack_t, uid_t, utsname, EAGAIN, EINVAL, ENOENT, GRND_NONBLOCK,
+    GRND_RANDOM,
 };
 use crate::Result;
 
 use core::ffi::{c_char, c_int, c_size_t, c_uint};
-use core::mem;
 
 /// Fake GID returned by enarx.
 pub const FAKE_GID: gid_t = 1000;
@@ -23,61 +22,6 @@ pub const FAKE_TID: pid_t = 1;
 /// Fake UID returned by enarx.
 pub const FAKE_UID: uid_t = 1000;
 
-pub struct Fstat<'a> {
-    pub fd: c_int,
-    pub statbuf: &'a mut stat,
-}
-
-impl<'a> Stub for Fstat<'a> {
-    type Ret = Result<()>;
-
-    fn collect(self, _: &impl Collector) -> Self::Ret {
-        match self.fd {
-            STDIN_FILENO | STDOUT_FILENO | STDERR_FILENO => {
-                #[allow(clippy::integer_arithmetic)]
-                const fn makedev(x: u64, y: u64) -> u64 {
-                    (((x) & 0xffff_f000u64) << 32)
-                        | (((x) & 0x0000_0fffu64) << 8)
-                        | (((y) & 0xffff_ff00u64) << 12)
-                        | ((y) & 0x0000_00ffu64)
-                }
-
-                let mut p: stat = unsafe { mem::zeroed() };
-
-                p.st_dev = makedev(
-                    0,
-                    match self.fd {
-                        0 => 0x19,
-                        _ => 0xc,
-                    },
-                );
-                p.st_ino = 3;
-                p.st_mode = S_IFIFO | 0o600;
-                p.st_nlink = 1;
-                p.st_uid = 1000;
-                p.st_gid = 5;
-                p.st_blksize = 4096;
-                p.st_blocks = 0;
-                p.st_rdev = makedev(0x88, 0);
-                p.st_size = 0;
-
-                p.st_atime = 1_579_507_218 /* 2020-01-21T11:45:08.467721685+0100 */;
-                p.st_atime_nsec = 0;
-                p.st_mtime = 1_579_507_218 /* 2020-01-21T11:45:07.467721685+0100 */;
-                p.st_mtime_nsec = 0;
-                p.st_ctime = 1_579_507_218 /* 2020-01-20T09:00:18.467721685+0100 */;
-                p.st_ctime_nsec = 0;
-
-                *self.statbuf = p;
-                Ok(())
-            }
-            // TODO: Support `fstat` on files.
-            // https://github.com/enarx/sallyport/issues/45
-            _ => Err(EBADFD),
-        }
-    }
-}
-
 pub struct Getegid;
 
 impl Stub for Getegid {
@@ -7,11 +7,14 @@ use crate::guest::syscall::types::SockaddrOutput;
 use crate::guest::Call;
 use crate::item;
 use crate::item::syscall;
-use crate::libc::socklen_t;
+use crate::libc::{
+    socklen_t, stat, statx, SYS_getdents64, SYS_read, SYS_readv, SYS_statx, SYS_write, SYS_writev,
+    AT_EMPTY_PATH, STATX_BASIC_STATS, S_IFREG,
+};
 use crate::NULL;
 
-use core::mem::size_of;
-use libc::{SYS_exit, SYS_recvfrom, AF_INET, ENOSYS};
+use core::mem::{self, size_of};
+use libc::{SYS_exit, SYS_fstat, SYS_recvfrom, AF_INET, ENOSYS};
 
 fn assert_call<'a, K: kind::Kind, T: Call<'a, K>, const N: usize>(
     call: T,
@@ -58,6 +61,192 @@ fn exit() {
     )
 }
 
+#[test]
+fn fstat() {
+    const HEADER: usize = 2 + syscall::USIZE_COUNT;
+    const STAT: usize = size_of::<stat>() / size_of::<usize>();
+
+    let fd = 42;
+    let mut committed = [0; HEADER + STAT];
+    committed[..HEADER].copy_from_slice(&[
+        syscall::USIZE_COUNT * size_of::<usize>() + size_of::<stat>(),
+        item::Kind::Syscall as _,
+        SYS_fstat as _,
+        fd as _,
+        0,
+        NULL,
+        NULL,
+        NULL,
+        NULL,
+        -ENOSYS as _,
+        0,
+    ]);
+
+    let mut collect = committed;
+    collect[HEADER - 2] = 0;
+    collect[HEADER + 3] = S_IFREG as _; // st_mode
+    collect[HEADER + 6] = 5; // st_size
+
+    let mut statbuf: stat = unsafe { mem::zeroed() };
+    assert_call(
+        Fstat {
+            fd,
+            statbuf: &mut statbuf,
+        },
+        committed,
+        collect,
+        Some(Ok(())),
+    );
+    assert_eq!((statbuf.st_mode, statbuf.st_size), (S_IFREG, 5));
+
+    collect[HEADER + 6] = -1isize as _;
+    assert_call(
+        Fstat {
+            fd,
+            statbuf: &mut statbuf,
+        },
+        committed,
+        collect,
+        None,
+    );
+}
+
+#[test]
+fn getdents64() {
+    const HEADER: usize = 2 + syscall::USIZE_COUNT;
+    const DIRENT: usize = 3 * size_of::<usize>();
+
+    let fd = 42;
+    let mut committed = [0; HEADER + 4];
+    committed[..HEADER].copy_from_slice(&[
+        syscall::USIZE_COUNT * size_of::<usize>() + 4 * size_of::<usize>(),
+        item::Kind::Syscall as _,
+        SYS_getdents64 as _,
+        fd as _,
+        0,
+        4 * size_of::<usize>(),
+        NULL,
+        NULL,
+        NULL,
+        -ENOSYS as _,
+        0,
+    ]);
+
+    // `d_ino`, `d_off` and `d_reclen`, `d_type` and `d_name` of a single record named "a".
+    let mut collect = committed;
+    collect[HEADER - 2] = DIRENT;
+    collect[HEADER] = 1;
+    collect[HEADER + 1] = 2;
+    collect[HEADER + 2] = DIRENT | (4 << 16) | ((b'a' as usize) << 24);
+
+    let mut dirp = [0xff; 4 * size_of::<usize>()];
+    assert_call(
+        Getdents64 {
+            fd,
+            dirp: &mut dirp,
+        },
+        committed,
+        collect,
+        Some(Ok(DIRENT)),
+    );
+    assert_eq!(dirp[16..21], [DIRENT as u8, 0, 4, b'a', 0]);
+
+    // `d_reclen` pointing past the returned length.
+    collect[HEADER + 2] = (2 * DIRENT) | (4 << 16) | ((b'a' as usize) << 24);
+    assert_call(
+        Getdents64 {
+            fd,
+            dirp: &mut dirp,
+        },
+        committed,
+        collect,
+        None,
+    );
+
+    // `d_reclen` too short to contain the name.
+    collect[HEADER + 2] = 16 | (4 << 16) | ((b'a' as usize) << 24);
+    assert_call(
+        Getdents64 {
+            fd,
+            dirp: &mut dirp,
+        },
+        committed,
+        collect,
+        None,
+    );
+}
+
+#[test]
+fn readv() {
+    const HEADER: usize = 2 + syscall::USIZE_COUNT;
+
+    // The block only fits 2 of 3 `iovec` elements along with the data they reference.
+    let fd = 42;
+    let mut committed = [0; HEADER + 5];
+    committed[..HEADER].copy_from_slice(&[
+        syscall::USIZE_COUNT * size_of::<usize>() + 5 * size_of::<usize>(),
+        item::Kind::Syscall as _,
+        SYS_readv as _,
+        fd as _,
+        0,
+        2,
+        NULL,
+        NULL,
+        NULL,
+        -ENOSYS as _,
+        0,
+    ]);
+    committed[HEADER..HEADER + 4].copy_from_slice(&[32, 3, 35, 2]);
+
+    let mut collect = committed;
+    collect[HEADER - 2] = 4;
+    collect[HEADER + 4] = u64::from_le_bytes(*b"wxyz\0\0\0\0") as _;
+
+    let (mut one, mut two, mut three) = ([0u8; 3], [0u8; 2], [0u8; 3]);
+    assert_call(
+        Readv {
+            fd,
+            iovs: &mut [&mut one[..], &mut two[..], &mut three[..]],
+        },
+        committed,
+        collect,
+        Some(Ok(4)),
+    );
+    assert_eq!((&one, &two, &three), (b"wxy", b"z\0", b"\0\0\0"));
+
+    // The block does not fit a single `iovec` element along with any data.
+    let mut committed = [0; HEADER + 1];
+    committed[..HEADER].copy_from_slice(&[
+        syscall::USIZE_COUNT * size_of::<usize>() + size_of::<usize>(),
+        item::Kind::Syscall as _,
+        SYS_read as _,
+        fd as _,
+        0,
+        8,
+        NULL,
+        NULL,
+        NULL,
+        -ENOSYS as _,
+        0,
+    ]);
+
+    let mut collect = committed;
+    collect[HEADER - 2] = 4;
+    collect[HEADER] = u64::from_le_bytes(*b"wxyz\0\0\0\0") as _;
+
+    let (mut one, mut two, mut three) = ([0u8; 3], [0u8; 2], [0u8; 3]);
+    assert_call(
+        Readv {
+            fd,
+            iovs: &mut [&mut one[..], &mut two[..], &mut three[..]],
+        },
+        committed,
+        collect,
+        Some(Ok(4)),
+    );
+    assert_eq!((&one, &two, &three), (b"wxy", b"z\0", b"\0\0\0"));
+}
+
 #[test]
 fn recv() {
     let sockfd = 42;
@@ -143,3 +332,107 @@ fn recvfrom() {
     assert_eq!(addrlen, 0x42);
     assert_eq!(buf, [0xfe, 0xed]);
 }
+
+#[test]
+fn statx() {
+    const HEADER: usize = 2 + syscall::USIZE_COUNT;
+    const STATX: usize = size_of::<statx>() / size_of::<usize>();
+
+    let dirfd = 42;
+    let mut committed = [0; HEADER + 1 + STATX];
+    committed[..HEADER].copy_from_slice(&[
+        syscall::USIZE_COUNT * size_of::<usize>() + size_of::<usize>() + size_of::<statx>(),
+        item::Kind::Syscall as _,
+        SYS_statx as _,
+        dirfd as _,
+        0,
+        1,
+        AT_EMPTY_PATH as _,
+        STATX_BASIC_STATS as _,
+        size_of::<usize>(),
+        -ENOSYS as _,
+        0,
+    ]);
+
+    let mut collect = committed;
+    collect[HEADER - 2] = 0;
+    collect[HEADER + 1] = 0x17ff; // stx_mask
+    collect[HEADER + 4] = (S_IFREG as usize) << 32; // stx_mode
+
+    let mut statxbuf: statx = unsafe { mem::zeroed() };
+    let mut invalid_mode = collect;
+    invalid_mode[HEADER + 4] = 0;
+    let mut invalid_nsec = collect;
+    invalid_nsec[HEADER + 10] = 1_000_000_000; // stx_atime.tv_nsec
+    for (collect, collected) in [
+        (collect, Some(Ok(()))),
+        (invalid_mode, None),
+        (invalid_nsec, None),
+    ] {
+        assert_call(
+            Statx {
+                dirfd,
+                pathname: b"\0",
+                flags: AT_EMPTY_PATH,
+                mask: STATX_BASIC_STATS,
+                statxbuf: &mut statxbuf,
+            },
+            committed,
+            collect,
+            collected,
+        );
+        if collected.is_some() {
+            assert_eq!(statxbuf.stx_mask, STATX_BASIC_STATS);
+            assert_eq!(statxbuf.stx_mode as u32, S_IFREG);
+        }
+    }
+}
+
+#[test]
+fn writev() {
+    const HEADER: usize = 2 + syscall::USIZE_COUNT;
+    const IOVS: [&[u8]; 3] = [b"abc", b"de", b"fgh"];
+
+    // The block only fits 2 of 3 `iovec` elements along with the data they reference.
+    let fd = 42;
+    let mut committed = [0; HEADER + 5];
+    committed[..HEADER].copy_from_slice(&[
+        syscall::USIZE_COUNT * size_of::<usize>() + 5 * size_of::<usize>(),
+        item::Kind::Syscall as _,
+        SYS_writev as _,
+        fd as _,
+        0,
+        2,
+        NULL,
+        NULL,
+        NULL,
+        -ENOSYS as _,
+        0,
+    ]);
+    committed[HEADER..].copy_from_slice(&[32, 3, 35, 2, u64::from_le_bytes(*b"abcde\0\0\0") as _]);
+
+    let mut collect = committed;
+    collect[HEADER - 2] = 4;
+    assert_call(Writev { fd, iovs: &IOVS }, committed, collect, Some(Ok(4)));
+
+    // The block does not fit a single `iovec` element along with any data.
+    let mut committed = [0; HEADER + 1];
+    committed[..HEADER].copy_from_slice(&[
+        syscall::USIZE_COUNT * size_of::<usize>() + size_of::<usize>(),
+        item::Kind::Syscall as _,
+        SYS_write as _,
+        fd as _,
+        0,
+        8,
+        NULL,
+        NULL,
+        NULL,
+        -ENOSYS as _,
+        0,
+    ]);
+    committed[HEADER] = u64::from_le_bytes(*b"abcdefgh") as _;
+
+    let mut collect = committed;
+    collect[HEADER - 2] = 8;
+    assert_call(Writev { fd, iovs: &IOVS }, committed, collect, Some(Ok(8)));
+}
@@ -18,7 +18,7 @@ impl<'a, T: Alloc<'a>> Commit for StagedAlloc<'a, T> {
 
     #[inline]
     fn commit(mut self, com: &impl Committer) -> Self::Item {
-        self.num_ref.copy_from(com, T::NUM as usize);
+        self.num_ref.copy_from(com, T::num(&self.staged) as usize);
         self.argv.commit(com);
         self.ret_ref.copy_from(com, [-ENOSYS as usize, 0]);
         Self::Item {
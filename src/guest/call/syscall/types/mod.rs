@@ -4,12 +4,14 @@
 
 mod alloc;
 mod bytes;
+mod msghdr;
 mod result;
 mod sockaddr;
 mod sockopt;
 
 pub use alloc::*;
 pub use bytes::*;
+pub use msghdr::*;
 pub use result::*;
 pub use sockaddr::*;
 pub use sockopt::*;
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{SockaddrInput, SockaddrOutput};
+
+use core::ffi::{c_int, c_size_t};
+
+/// Message sent by [`sendmsg`](crate::guest::Handler::sendmsg) akin to
+/// [`msghdr`](crate::libc::msghdr).
+pub struct MsghdrInput<'a, T: ?Sized> {
+    pub name: Option<SockaddrInput<'a>>,
+    pub iov: &'a T,
+    pub control: &'a [u8],
+}
+
+/// Message received by [`recvmsg`](crate::guest::Handler::recvmsg) akin to
+/// [`msghdr`](crate::libc::msghdr).
+///
+/// The length of the control messages received is written into `controllen` and the flags
+/// of the message into `flags`.
+pub struct MsghdrOutput<'a, T: ?Sized> {
+    pub name: Option<SockaddrOutput<'a>>,
+    pub iov: &'a mut T,
+    pub control: &'a mut [u8],
+    pub controllen: &'a mut c_size_t,
+    pub flags: &'a mut c_int,
+}
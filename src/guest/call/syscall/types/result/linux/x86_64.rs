@@ -57,6 +57,16 @@ impl From<Result<usize>> for crate::Result<usize> {
     }
 }
 
+impl From<Result<i64>> for crate::Result<i64> {
+    #[inline]
+    fn from(res: Result<i64>) -> Self {
+        match res.0 {
+            [errno @ ERRNO_START..=usize::MAX, _] => Err(-(errno as c_int)),
+            [ret, _] => Ok(ret as _),
+        }
+    }
+}
+
 impl From<Result<isize>> for crate::Result<usize> {
     #[inline]
     fn from(res: Result<isize>) -> Self {
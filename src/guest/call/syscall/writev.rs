@@ -1,9 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::super::types::Argv;
-use super::{iov_len, Alloc};
+use super::{iov_fit, iov_len, iovecs, Alloc};
 use crate::guest::alloc::{Allocator, Collector, Commit, Committer, InRef};
-use crate::libc::SYS_write;
+use crate::libc::{iovec, off_t, SYS_pwrite64, SYS_pwritev, SYS_write, SYS_writev};
 use crate::Result;
 
 use core::ffi::{c_int, c_long, c_size_t};
@@ -13,11 +13,48 @@ pub struct Writev<T> {
     pub iovs: T,
 }
 
+pub struct Pwritev<T> {
+    pub fd: c_int,
+    pub iovs: T,
+    pub offset: off_t,
+}
+
 pub struct StagedWritev<'a, T> {
+    /// `iovec` array, unless the data is written from a single buffer.
+    pub(super) iovecs: Option<InRef<'a, [iovec]>>,
     buf: InRef<'a, [u8]>,
     iovs: T,
 }
 
+impl<'a, T, U> StagedWritev<'a, &'a T>
+where
+    T: ?Sized,
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    U: AsRef<[u8]>,
+{
+    /// Allocates an `iovec` array of as many leading `iovs` as leave room for at least one byte
+    /// of data followed by a buffer of at most their total length depending on capacity.
+    ///
+    /// If the leading `iovs` fitting do not reference any data, only a buffer of at most the
+    /// total length of `iovs` is allocated to write the data from instead.
+    pub(super) fn stage(alloc: &mut impl Allocator, iovs: &'a T) -> Result<Self> {
+        let (iovecs, len) = match iov_fit(alloc, iovs) {
+            Some((count, len)) => (Some(alloc.allocate_input_slice(count)?), len),
+            None => (None, iov_len(iovs)),
+        };
+        let buf = alloc.allocate_input_slice_max(len)?;
+        Ok(Self { iovecs, buf, iovs })
+    }
+
+    /// Returns the offset and the length of the `iovec` array or, if there is none, of the buffer.
+    fn args(&self) -> (usize, usize) {
+        match &self.iovecs {
+            Some(iovecs) => (iovecs.offset(), iovecs.len()),
+            None => (self.buf.offset(), self.buf.len()),
+        }
+    }
+}
+
 impl<'a, T, U> Commit for StagedWritev<'a, &'a T>
 where
     T: ?Sized,
@@ -29,6 +66,10 @@ where
     fn commit(mut self, com: &impl Committer) -> Self::Item {
         let mut capacity = self.buf.len();
         unsafe {
+            if let Some(array) = &mut self.iovecs {
+                let iovs = iovecs(self.iovs, self.buf.offset(), self.buf.len()).take(array.len());
+                array.copy_from_iter_unchecked(com, iovs);
+            }
             self.buf.copy_from_iter_unchecked(
                 com,
                 self.iovs.into_iter().map_while(|iov| {
@@ -52,13 +93,23 @@ where
     }
 }
 
+/// Validates the count of bytes written `ret` against the count of bytes committed `count`.
+#[inline]
+pub(super) fn collect(count: c_size_t, ret: Result<c_size_t>) -> Option<Result<c_size_t>> {
+    match ret {
+        Ok(ret) if ret > count => None,
+        res @ Ok(_) => Some(res),
+        err => Some(err),
+    }
+}
+
 unsafe impl<'a, T, U> Alloc<'a> for Writev<&'a T>
 where
     T: ?Sized,
     for<'b> &'b T: IntoIterator<Item = &'b U>,
     U: AsRef<[u8]>,
 {
-    const NUM: c_long = SYS_write;
+    const NUM: c_long = SYS_writev;
 
     type Argv = Argv<3>;
     type Ret = c_size_t;
@@ -67,15 +118,18 @@ where
     type Committed = c_size_t;
     type Collected = Option<Result<c_size_t>>;
 
+    fn num(staged: &Self::Staged) -> c_long {
+        if staged.iovecs.is_some() {
+            SYS_writev
+        } else {
+            SYS_write
+        }
+    }
+
     fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
-        let buf = alloc.allocate_input_slice_max(iov_len(self.iovs))?;
-        Ok((
-            Argv([self.fd as _, buf.offset(), buf.len()]),
-            StagedWritev {
-                iovs: self.iovs,
-                buf,
-            },
-        ))
+        let staged = StagedWritev::stage(alloc, self.iovs)?;
+        let (offset, len) = staged.args();
+        Ok((Argv([self.fd as _, offset, len]), staged))
     }
 
     fn collect(
@@ -83,10 +137,44 @@ where
         ret: Result<Self::Ret>,
         _: &impl Collector,
     ) -> Self::Collected {
-        match ret {
-            Ok(ret) if ret > count => None,
-            res @ Ok(_) => Some(res),
-            err => Some(err),
+        collect(count, ret)
+    }
+}
+
+unsafe impl<'a, T, U> Alloc<'a> for Pwritev<&'a T>
+where
+    T: ?Sized,
+    for<'b> &'b T: IntoIterator<Item = &'b U>,
+    U: AsRef<[u8]>,
+{
+    const NUM: c_long = SYS_pwritev;
+
+    type Argv = Argv<4>;
+    type Ret = c_size_t;
+
+    type Staged = StagedWritev<'a, &'a T>;
+    type Committed = c_size_t;
+    type Collected = Option<Result<c_size_t>>;
+
+    fn num(staged: &Self::Staged) -> c_long {
+        if staged.iovecs.is_some() {
+            SYS_pwritev
+        } else {
+            SYS_pwrite64
         }
     }
+
+    fn stage(self, alloc: &mut impl Allocator) -> Result<(Self::Argv, Self::Staged)> {
+        let staged = StagedWritev::stage(alloc, self.iovs)?;
+        let (offset, len) = staged.args();
+        Ok((Argv([self.fd as _, offset, len, self.offset as _]), staged))
+    }
+
+    fn collect(
+        count: Self::Committed,
+        ret: Result<Self::Ret>,
+        _: &impl Collector,
+    ) -> Self::Collected {
+        collect(count, ret)
+    }
 }
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validation of the results of the `cpuid` instruction executed by the host.
+
+use crate::libc::ENOTSUP;
+use crate::Result;
+
+use core::arch::x86_64::CpuidResult;
+
+/// Feature bits expected in the result of the `cpuid` instruction for a leaf and sub-leaf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuidFeatures {
+    pub leaf: u32,
+    pub sub_leaf: u32,
+
+    /// Bits, which must be set.
+    pub required: CpuidResult,
+
+    /// Bits, which must be clear.
+    pub forbidden: CpuidResult,
+}
+
+impl CpuidFeatures {
+    /// Validates `result` of the `cpuid` instruction for `leaf` and `sub_leaf` against the
+    /// expected `features` applicable to it.
+    ///
+    /// Returns [`ENOTSUP`] if a required bit is clear or a forbidden bit is set.
+    pub fn validate(
+        features: &[Self],
+        leaf: u32,
+        sub_leaf: u32,
+        result: &CpuidResult,
+    ) -> Result<()> {
+        let valid = |required: u32, forbidden: u32, value: u32| {
+            value & required == required && value & forbidden == 0
+        };
+        for features in features
+            .iter()
+            .filter(|features| features.leaf == leaf && features.sub_leaf == sub_leaf)
+        {
+            let Self {
+                required: r,
+                forbidden: f,
+                ..
+            } = features;
+            if !(valid(r.eax, f.eax, result.eax)
+                && valid(r.ebx, f.ebx, result.ebx)
+                && valid(r.ecx, f.ecx, result.ecx)
+                && valid(r.edx, f.edx, result.edx))
+            {
+                return Err(ENOTSUP);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,24 +1,36 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::alloc::{Alloc, Allocator, Collect, Commit, Committer};
-use super::call::kind;
-use super::syscall::types::{MremapFlags, SockaddrInput, SockaddrOutput, SockoptInput};
-use super::{enarxcall, gdbcall, syscall, Call, Platform, ThreadLocalStorage, SIGRTMAX};
+use super::call::{self, kind};
+use super::syscall::types::{
+    MremapFlags, MsghdrInput, MsghdrOutput, SockaddrInput, SockaddrOutput, SockoptInput,
+};
+use super::{
+    enarxcall, gdbcall, syscall, Call, CpuidFeatures, Notification, Platform, Queued,
+    ThreadLocalStorage, SIGRTMAX,
+};
 use crate::item::enarxcall::sgx;
+use crate::item::hello::Reply;
+use crate::item::ring::Producer;
 use crate::item::syscall::sigaction;
+use crate::item::BlockWriter;
 use crate::libc::{
-    clockid_t, epoll_event, gid_t, mode_t, off_t, pid_t, pollfd, sigset_t, stack_t, stat, timespec,
-    uid_t, utsname, Ioctl, SYS_accept, SYS_accept4, SYS_arch_prctl, SYS_bind, SYS_brk,
-    SYS_clock_getres, SYS_clock_gettime, SYS_close, SYS_connect, SYS_dup, SYS_dup2, SYS_dup3,
-    SYS_epoll_create1, SYS_epoll_ctl, SYS_epoll_pwait, SYS_epoll_wait, SYS_eventfd2, SYS_exit,
-    SYS_exit_group, SYS_fcntl, SYS_fstat, SYS_getegid, SYS_geteuid, SYS_getgid, SYS_getpid,
-    SYS_getrandom, SYS_getsockname, SYS_getuid, SYS_ioctl, SYS_listen, SYS_madvise, SYS_mmap,
-    SYS_mprotect, SYS_mremap, SYS_munmap, SYS_nanosleep, SYS_open, SYS_poll, SYS_read,
-    SYS_readlink, SYS_readv, SYS_recvfrom, SYS_rt_sigaction, SYS_rt_sigprocmask, SYS_sendto,
-    SYS_set_tid_address, SYS_setsockopt, SYS_sigaltstack, SYS_socket, SYS_sync, SYS_uname,
-    SYS_write, SYS_writev, EFAULT, EINVAL, ENOSYS, ENOTSUP, FIONBIO, FIONREAD, MAP_ANONYMOUS,
-    MAP_PRIVATE, MREMAP_DONTUNMAP, MREMAP_FIXED, MREMAP_MAYMOVE, PROT_EXEC, PROT_READ, PROT_WRITE,
+    clockid_t, epoll_event, gid_t, mode_t, msghdr, off_t, pid_t, pollfd, sigset_t, stack_t, stat,
+    statx, timespec, uid_t, utsname, Ioctl, SYS_accept, SYS_accept4, SYS_arch_prctl, SYS_bind,
+    SYS_brk, SYS_clock_getres, SYS_clock_gettime, SYS_close, SYS_connect, SYS_dup, SYS_dup2,
+    SYS_dup3, SYS_epoll_create1, SYS_epoll_ctl, SYS_epoll_pwait, SYS_epoll_wait, SYS_eventfd2,
+    SYS_exit, SYS_exit_group, SYS_fchmod, SYS_fcntl, SYS_fstat, SYS_ftruncate, SYS_getdents64,
+    SYS_getegid, SYS_geteuid, SYS_getgid, SYS_getpid, SYS_getrandom, SYS_getsockname, SYS_getuid,
+    SYS_ioctl, SYS_listen, SYS_lseek, SYS_madvise, SYS_mkdirat, SYS_mmap, SYS_mprotect, SYS_mremap,
+    SYS_munmap, SYS_nanosleep, SYS_newfstatat, SYS_open, SYS_openat, SYS_poll, SYS_pread64,
+    SYS_preadv, SYS_pwrite64, SYS_pwritev, SYS_read, SYS_readlink, SYS_readv, SYS_recvfrom,
+    SYS_recvmsg, SYS_renameat2, SYS_rt_sigaction, SYS_rt_sigprocmask, SYS_sendmsg, SYS_sendto,
+    SYS_set_tid_address, SYS_setsockopt, SYS_sigaltstack, SYS_socket, SYS_statx, SYS_sync,
+    SYS_uname, SYS_unlinkat, SYS_write, SYS_writev, EFAULT, EINTR, EINVAL, ENODATA, ENOSYS,
+    ENOTSUP, EPIPE, EPROTO, FIONBIO, FIONREAD, MAP_ANONYMOUS, MAP_PRIVATE, MREMAP_DONTUNMAP,
+    MREMAP_FIXED, MREMAP_MAYMOVE, PROT_EXEC, PROT_READ, PROT_WRITE,
 };
+use crate::util::version::satisfies;
 use crate::{item, Result};
 
 use core::arch::x86_64::CpuidResult;
@@ -51,10 +63,15 @@ pub trait Handler {
     /// - [`gdbcall::Write`]
     #[inline]
     fn execute<'a, K: kind::Kind, T: Call<'a, K>>(&mut self, call: T) -> Result<T::Collected> {
+        if !K::HELLO {
+            self.handshake()?;
+        }
+
         let mut alloc = Alloc::new(self.block_mut()).stage();
         let ((call, len), mut end_ref) =
             alloc.reserve_input(|alloc| alloc.section(|alloc| call.stage(alloc)))?;
 
+        let end = end_ref.offset() / size_of::<usize>();
         let alloc = alloc.commit();
         let call = call.commit(&alloc);
         let alloc = if len > 0 {
@@ -71,7 +88,16 @@ pub trait Handler {
         } else {
             alloc.collect()
         };
-        Ok(call.collect(&alloc))
+        let collected = call.collect(&alloc);
+        if len > 0 {
+            // The host may have placed notifications in place of the `END` item.
+            let mut pending = core::mem::take(&mut self.thread_local_storage().pending);
+            if let Some(block) = self.block_mut().get_mut(end..) {
+                pending.receive(block);
+            }
+            self.thread_local_storage().pending = pending;
+        }
+        Ok(collected)
     }
 
     /// Loops infinitely trying to exit.
@@ -82,6 +108,132 @@ pub trait Handler {
         }
     }
 
+    /// Removes and returns the next [notification](Notification) passed by the host, if any.
+    ///
+    /// Notifications are received on every exit to the host. Signals, for which no action
+    /// is installed via [`rt_sigaction`](Handler::rt_sigaction), are discarded.
+    #[inline]
+    fn take_notification(&mut self) -> Option<Notification> {
+        let tls = self.thread_local_storage();
+        tls.pending.pop(&tls.actions)
+    }
+
+    /// Performs the protocol version handshake with the host and writes the host [reply](Reply)
+    /// in `reply`.
+    ///
+    /// The handshake is performed automatically by [`execute`](Handler::execute) on the first
+    /// exit to the host, so calling this is only needed to obtain the reply.
+    /// Returns [`EPROTO`] if the host version does not satisfy [`REQUIRES`](crate::REQUIRES)
+    /// and [`ENOSYS`] if the host does not support the handshake.
+    #[inline]
+    fn hello(&mut self, reply: &mut Reply) -> Result<()> {
+        self.execute(call::Hello { reply })??;
+        let requires = core::str::from_utf8(&crate::REQUIRES).map_err(|_| EPROTO)?;
+        match reply.version() {
+            Some(version) if satisfies(version, requires) => Ok(()),
+            _ => Err(EPROTO),
+        }
+    }
+
+    /// Performs the protocol version handshake with the host unless it was already performed
+    /// and returns its result.
+    ///
+    /// Hosts not supporting the handshake and blocks too small to hold it are tolerated.
+    /// Once the host version was found incompatible, [`EPROTO`] is returned for all calls.
+    #[inline]
+    fn handshake(&mut self) -> Result<()> {
+        if let Some(hello) = self.thread_local_storage().hello {
+            return hello;
+        }
+        let hello = match self.hello(&mut Reply::new()) {
+            Err(EPROTO) => Err(EPROTO),
+            _ => Ok(()),
+        };
+        self.thread_local_storage().hello = Some(hello);
+        hello
+    }
+
+    /// Stages `call` in the next free block of `ring` and submits it to the host without
+    /// passing control to it.
+    ///
+    /// The result of the call is obtained by passing the returned [`Queued`] call to
+    /// [`dequeue`](Handler::dequeue). Calls must be dequeued in the order they were queued.
+    /// Returns [`EAGAIN`](crate::libc::EAGAIN) if the ring is full, in which case the oldest
+    /// queued call must be dequeued first.
+    #[inline]
+    fn enqueue<'a, 'r, K: kind::Kind, T: Call<'a, K>>(
+        &mut self,
+        ring: &mut Producer<'r>,
+        call: T,
+    ) -> Result<Queued<'r, T::Committed>> {
+        self.handshake()?;
+        ring.submit_raw(|block, index| {
+            let mut alloc = unsafe { Alloc::from_raw(block) }.stage();
+            let ((call, _), mut end_ref) =
+                alloc.reserve_input(|alloc| alloc.section(|alloc| call.stage(alloc)))?;
+
+            let end = end_ref.offset() / size_of::<usize>();
+            let alloc = alloc.commit();
+            let call = call.commit(&alloc);
+            end_ref.copy_from(
+                &alloc,
+                item::Header {
+                    kind: item::Kind::End,
+                    size: 0,
+                },
+            );
+            Ok(Queued {
+                call,
+                alloc,
+                end,
+                index,
+            })
+        })
+    }
+
+    /// Collects the result of the oldest `queued` call of `ring` queued via
+    /// [`enqueue`](Handler::enqueue).
+    ///
+    /// Control is only passed to the host if it did not execute the call yet, in which case
+    /// the host is expected to execute all blocks submitted to `ring` before returning it.
+    /// Returns [`EINVAL`] if `queued` is not the oldest call queued in `ring`.
+    #[inline]
+    fn dequeue<'r, T: Collect>(
+        &mut self,
+        ring: &mut Producer<'r>,
+        queued: Queued<'r, T>,
+    ) -> Result<T::Item> {
+        if queued.index != ring.collected() {
+            return Err(EINVAL);
+        }
+        if !ring.is_collectable().unwrap_or_else(|_| self.attacked()) {
+            // Pass an empty block, so that only the ring is executed.
+            BlockWriter::new(self.block_mut())?;
+            self.sally()?;
+            let mut pending = core::mem::take(&mut self.thread_local_storage().pending);
+            pending.receive(self.block_mut());
+            self.thread_local_storage().pending = pending;
+
+            if !ring.is_collectable().unwrap_or_else(|_| self.attacked()) {
+                self.attacked()
+            }
+        }
+
+        let Queued {
+            call, alloc, end, ..
+        } = queued;
+        let collected = call.collect(&alloc.collect());
+        let mut pending = core::mem::take(&mut self.thread_local_storage().pending);
+        ring.collect(|block| {
+            // The host may have placed notifications in place of the `END` item.
+            if let Some(block) = block.get_mut(end..) {
+                pending.receive(block);
+            }
+        })?;
+        self.thread_local_storage().pending = pending;
+        Ok(collected)
+    }
+
     // Syscalls, sorted alphabetically.
 
     /// Executes [`accept`](https://man7.org/linux/man-pages/man2/accept.2.html) syscall akin to [`libc::accept`].
@@ -242,6 +394,12 @@ pub trait Handler {
         self.attacked()
     }
 
+    /// Executes [`fchmod`](https://man7.org/linux/man-pages/man2/fchmod.2.html) syscall akin to [`libc::fchmod`].
+    #[inline]
+    fn fchmod(&mut self, fd: c_int, mode: mode_t) -> Result<()> {
+        self.execute(syscall::Fchmod { fd, mode })?
+    }
+
     /// Executes [`fcntl`](https://man7.org/linux/man-pages/man2/fcntl.2.html) syscall akin to [`libc::fcntl`].
     #[inline]
     fn fcntl(&mut self, fd: c_int, cmd: c_int, arg: c_int) -> Result<c_int> {
@@ -252,6 +410,22 @@ pub trait Handler {
     #[inline]
     fn fstat(&mut self, fd: c_int, statbuf: &mut stat) -> Result<()> {
         self.execute(syscall::Fstat { fd, statbuf })?
+            .unwrap_or_else(|| self.attacked())
+    }
+
+    /// Executes [`ftruncate`](https://man7.org/linux/man-pages/man2/ftruncate.2.html) syscall akin to [`libc::ftruncate`].
+    #[inline]
+    fn ftruncate(&mut self, fd: c_int, length: off_t) -> Result<()> {
+        self.execute(syscall::Ftruncate { fd, length })?
+    }
+
+    /// Executes [`getdents64`](https://man7.org/linux/man-pages/man2/getdents64.2.html) syscall.
+    ///
+    /// The `linux_dirent64` records returned by the host are validated to lie within `dirp`.
+    #[inline]
+    fn getdents64(&mut self, fd: c_int, dirp: &mut [u8]) -> Result<c_size_t> {
+        self.execute(syscall::Getdents64 { fd, dirp })?
+            .unwrap_or_else(|| self.attacked())
     }
 
     /// Executes [`getegid`](https://man7.org/linux/man-pages/man2/getegid.2.html) syscall akin to [`libc::getegid`].
@@ -312,6 +486,12 @@ pub trait Handler {
         self.execute(syscall::Listen { sockfd, backlog })?
     }
 
+    /// Executes [`lseek`](https://man7.org/linux/man-pages/man2/lseek.2.html) syscall akin to [`libc::lseek`].
+    #[inline]
+    fn lseek(&mut self, fd: c_int, offset: off_t, whence: c_int) -> Result<off_t> {
+        self.execute(syscall::Lseek { fd, offset, whence })?
+    }
+
     /// Executes [`madvise`](https://man7.org/linux/man-pages/man2/madvise.2.html) syscall akin to [`libc::madvise`].
     fn madvise(
         &mut self,
@@ -321,6 +501,18 @@ pub trait Handler {
         advice: c_int,
     ) -> Result<()>;
 
+    /// Executes [`mkdirat`](https://man7.org/linux/man-pages/man2/mkdirat.2.html) syscall akin to [`libc::mkdirat`].
+    ///
+    /// `pathname` argument must contain the trailing nul terminator byte.
+    #[inline]
+    fn mkdirat(&mut self, dirfd: c_int, pathname: &[u8], mode: mode_t) -> Result<()> {
+        self.execute(syscall::Mkdirat {
+            dirfd,
+            pathname,
+            mode,
+        })?
+    }
+
     /// Executes [`mmap`](https://man7.org/linux/man-pages/man2/mmap.2.html) syscall akin to [`libc::mmap`].
     #[allow(clippy::too_many_arguments)]
     fn mmap(
@@ -423,6 +615,26 @@ pub trait Handler {
         self.execute(syscall::Nanosleep { req, rem })?
     }
 
+    /// Executes [`newfstatat`](https://man7.org/linux/man-pages/man2/newfstatat.2.html) syscall akin to [`libc::fstatat`].
+    ///
+    /// `pathname` argument must contain the trailing nul terminator byte.
+    #[inline]
+    fn newfstatat(
+        &mut self,
+        dirfd: c_int,
+        pathname: &[u8],
+        statbuf: &mut stat,
+        flags: c_int,
+    ) -> Result<()> {
+        self.execute(syscall::Newfstatat {
+            dirfd,
+            pathname,
+            statbuf,
+            flags,
+        })?
+        .unwrap_or_else(|| self.attacked())
+    }
+
     /// Executes [`open`](https://man7.org/linux/man-pages/man2/open.2.html) syscall akin to [`libc::open`].
     ///
     /// `pathname` argument must contain the trailing nul terminator byte.
@@ -434,6 +646,25 @@ pub trait Handler {
         })?
     }
 
+    /// Executes [`openat`](https://man7.org/linux/man-pages/man2/openat.2.html) syscall akin to [`libc::openat`].
+    ///
+    /// `pathname` argument must contain the trailing nul terminator byte.
+    #[inline]
+    fn openat(
+        &mut self,
+        dirfd: c_int,
+        pathname: &[u8],
+        flags: c_int,
+        mode: Option<mode_t>,
+    ) -> Result<c_int> {
+        self.execute(syscall::Openat {
+            dirfd,
+            pathname,
+            flags,
+            mode,
+        })?
+    }
+
     /// Executes [`poll`](https://man7.org/linux/man-pages/man2/poll.2.html) syscall akin to [`libc::poll`].
     #[inline]
     fn poll(&mut self, fds: &mut [pollfd], timeout: c_int) -> Result<c_int> {
@@ -441,6 +672,49 @@ pub trait Handler {
             .unwrap_or_else(|| self.attacked())
     }
 
+    /// Executes [`pread64`](https://man7.org/linux/man-pages/man2/pread64.2.html) syscall akin to [`libc::pread`].
+    #[inline]
+    fn pread64(&mut self, fd: c_int, buf: &mut [u8], offset: off_t) -> Result<c_size_t> {
+        self.execute(syscall::Pread64 { fd, buf, offset })?
+            .unwrap_or_else(|| self.attacked())
+    }
+
+    /// Executes [`preadv`](https://man7.org/linux/man-pages/man2/preadv.2.html) syscall akin to [`libc::preadv`].
+    #[inline]
+    fn preadv<T: ?Sized, U, V>(
+        &mut self,
+        fd: c_int,
+        iovs: &mut T,
+        offset: off_t,
+    ) -> Result<c_size_t>
+    where
+        for<'a> &'a T: IntoIterator<Item = &'a U>,
+        for<'a> &'a mut T: IntoIterator<Item = &'a mut V>,
+        U: AsRef<[u8]>,
+        V: AsMut<[u8]>,
+    {
+        self.execute(syscall::Preadv { fd, iovs, offset })?
+            .unwrap_or_else(|| self.attacked())
+    }
+
+    /// Executes [`pwrite64`](https://man7.org/linux/man-pages/man2/pwrite64.2.html) syscall akin to [`libc::pwrite`].
+    #[inline]
+    fn pwrite64(&mut self, fd: c_int, buf: &[u8], offset: off_t) -> Result<c_size_t> {
+        self.execute(syscall::Pwrite64 { fd, buf, offset })?
+            .unwrap_or_else(|| self.attacked())
+    }
+
+    /// Executes [`pwritev`](https://man7.org/linux/man-pages/man2/pwritev.2.html) syscall akin to [`libc::pwritev`].
+    #[inline]
+    fn pwritev<T: ?Sized, U>(&mut self, fd: c_int, iovs: &T, offset: off_t) -> Result<c_size_t>
+    where
+        for<'a> &'a T: IntoIterator<Item = &'a U>,
+        U: AsRef<[u8]>,
+    {
+        self.execute(syscall::Pwritev { fd, iovs, offset })?
+            .unwrap_or_else(|| self.attacked())
+    }
+
     /// Executes [`read`](https://man7.org/linux/man-pages/man2/read.2.html) syscall akin to [`libc::read`].
     #[inline]
     fn read(&mut self, fd: c_int, buf: &mut [u8]) -> Result<c_size_t> {
@@ -457,8 +731,7 @@ pub trait Handler {
             .unwrap_or_else(|| self.attacked())
     }
 
-    /// Executes [`readv`](https://man7.org/linux/man-pages/man2/readv.2.html) syscall by mapping
-    /// it onto a single [`read`](https://man7.org/linux/man-pages/man2/read.2.html).
+    /// Executes [`readv`](https://man7.org/linux/man-pages/man2/readv.2.html) syscall akin to [`libc::readv`].
     #[inline]
     fn readv<T: ?Sized, U, V>(&mut self, fd: c_int, iovs: &mut T) -> Result<c_size_t>
     where
@@ -496,6 +769,45 @@ pub trait Handler {
         .unwrap_or_else(|| self.attacked())
     }
 
+    /// Executes [`recvmsg`](https://man7.org/linux/man-pages/man2/recvmsg.2.html) syscall akin to [`libc::recvmsg`].
+    #[inline]
+    fn recvmsg<'a, T: ?Sized, U, V>(
+        &mut self,
+        sockfd: c_int,
+        msg: MsghdrOutput<'a, T>,
+        flags: c_int,
+    ) -> Result<c_size_t>
+    where
+        for<'b> &'b T: IntoIterator<Item = &'b U>,
+        for<'b> &'b mut T: IntoIterator<Item = &'b mut V>,
+        U: AsRef<[u8]>,
+        V: AsMut<[u8]>,
+    {
+        self.execute(syscall::Recvmsg { sockfd, msg, flags })?
+            .unwrap_or_else(|| self.attacked())
+    }
+
+    /// Executes [`renameat2`](https://man7.org/linux/man-pages/man2/renameat2.2.html) syscall akin to [`libc::renameat2`].
+    ///
+    /// `oldpath` and `newpath` arguments must contain the trailing nul terminator byte.
+    #[inline]
+    fn renameat2(
+        &mut self,
+        olddirfd: c_int,
+        oldpath: &[u8],
+        newdirfd: c_int,
+        newpath: &[u8],
+        flags: c_uint,
+    ) -> Result<()> {
+        self.execute(syscall::Renameat2 {
+            olddirfd,
+            oldpath,
+            newdirfd,
+            newpath,
+            flags,
+        })?
+    }
+
     /// Executes [`rt_sigaction`](https://man7.org/linux/man-pages/man2/rt_sigaction.2.html).
     #[inline]
     fn rt_sigaction(
@@ -542,6 +854,22 @@ pub trait Handler {
             .unwrap_or_else(|| self.attacked())
     }
 
+    /// Executes [`sendmsg`](https://man7.org/linux/man-pages/man2/sendmsg.2.html) syscall akin to [`libc::sendmsg`].
+    #[inline]
+    fn sendmsg<'a, T: ?Sized, U>(
+        &mut self,
+        sockfd: c_int,
+        msg: MsghdrInput<'a, T>,
+        flags: c_int,
+    ) -> Result<c_size_t>
+    where
+        for<'b> &'b T: IntoIterator<Item = &'b U>,
+        U: AsRef<[u8]>,
+    {
+        self.execute(syscall::Sendmsg { sockfd, msg, flags })?
+            .unwrap_or_else(|| self.attacked())
+    }
+
     /// Executes [`sendto`](https://man7.org/linux/man-pages/man2/sendto.2.html) syscall akin to [`libc::sendto`].
     #[inline]
     fn sendto<'a>(
@@ -599,6 +927,28 @@ pub trait Handler {
         })?
     }
 
+    /// Executes [`statx`](https://man7.org/linux/man-pages/man2/statx.2.html) syscall akin to [`libc::statx`].
+    ///
+    /// `pathname` argument must contain the trailing nul terminator byte.
+    #[inline]
+    fn statx(
+        &mut self,
+        dirfd: c_int,
+        pathname: &[u8],
+        flags: c_int,
+        mask: c_uint,
+        statxbuf: &mut statx,
+    ) -> Result<()> {
+        self.execute(syscall::Statx {
+            dirfd,
+            pathname,
+            flags,
+            mask,
+            statxbuf,
+        })?
+        .unwrap_or_else(|| self.attacked())
+    }
+
     /// Executes [`sync`](https://man7.org/linux/man-pages/man2/sync.2.html) syscall akin to [`libc::sync`].
     #[inline]
     fn sync(&mut self) -> Result<()> {
@@ -611,6 +961,18 @@ pub trait Handler {
         self.execute(syscall::Uname { buf })?
     }
 
+    /// Executes [`unlinkat`](https://man7.org/linux/man-pages/man2/unlinkat.2.html) syscall akin to [`libc::unlinkat`].
+    ///
+    /// `pathname` argument must contain the trailing nul terminator byte.
+    #[inline]
+    fn unlinkat(&mut self, dirfd: c_int, pathname: &[u8], flags: c_int) -> Result<()> {
+        self.execute(syscall::Unlinkat {
+            dirfd,
+            pathname,
+            flags,
+        })?
+    }
+
     /// Executes [`write`](https://man7.org/linux/man-pages/man2/write.2.html) syscall akin to [`libc::write`].
     #[inline]
     fn write(&mut self, fd: c_int, buf: &[u8]) -> Result<c_size_t> {
@@ -618,8 +980,7 @@ pub trait Handler {
             .unwrap_or_else(|| self.attacked())
     }
 
-    /// Executes [`writev`](https://man7.org/linux/man-pages/man2/writev.2.html) syscall by mapping
-    /// it onto a single [`write`](https://man7.org/linux/man-pages/man2/write.2.html).
+    /// Executes [`writev`](https://man7.org/linux/man-pages/man2/writev.2.html) syscall akin to [`libc::writev`].
     #[inline]
     fn writev<T: ?Sized, U>(&mut self, fd: c_int, iovs: &T) -> Result<c_size_t>
     where
@@ -724,6 +1085,7 @@ pub trait Handler {
                 .map(|ret| [ret as _, 0]),
             (SYS_exit, [status, ..]) => self.exit(status as _).map(|_| self.attacked()),
             (SYS_exit_group, [status, ..]) => self.exit_group(status as _).map(|_| self.attacked()),
+            (SYS_fchmod, [fd, mode, ..]) => self.fchmod(fd as _, mode as _).map(|_| [0, 0]),
             (SYS_fcntl, [fd, cmd, arg, ..]) => self
                 .fcntl(fd as _, cmd as _, arg as _)
                 .map(|ret| [ret as _, 0]),
@@ -731,6 +1093,13 @@ pub trait Handler {
                 let statbuf = platform.validate_mut(statbuf)?;
                 self.fstat(fd as _, statbuf).map(|_| [0, 0])
             }
+            (SYS_ftruncate, [fd, length, ..]) => {
+                self.ftruncate(fd as _, length as _).map(|_| [0, 0])
+            }
+            (SYS_getdents64, [fd, dirp, count, ..]) => {
+                let dirp = platform.validate_slice_mut(dirp, count)?;
+                self.getdents64(fd as _, dirp).map(|ret| [ret, 0])
+            }
             (SYS_getegid, ..) => self.getegid().map(|ret| [ret as _, 0]),
             (SYS_geteuid, ..) => self.geteuid().map(|ret| [ret as _, 0]),
             (SYS_getgid, ..) => self.getgid().map(|ret| [ret as _, 0]),
@@ -764,11 +1133,19 @@ pub trait Handler {
             (SYS_listen, [sockfd, backlog, ..]) => {
                 self.listen(sockfd as _, backlog as _).map(|_| [0, 0])
             }
+            (SYS_lseek, [fd, offset, whence, ..]) => self
+                .lseek(fd as _, offset as _, whence as _)
+                .map(|ret| [ret as _, 0]),
             (SYS_madvise, [addr, length, advice, ..]) => {
                 let addr = NonNull::new(addr as _).ok_or(EFAULT)?;
                 self.madvise(platform, addr, length, advice as _)
                     .map(|_| [0, 0])
             }
+            (SYS_mkdirat, [dirfd, pathname, mode, ..]) => {
+                let pathname = platform.validate_str(pathname)?;
+                self.mkdirat(dirfd as _, pathname, mode as _)
+                    .map(|_| [0, 0])
+            }
             (SYS_mmap, [addr, length, prot, flags, fd, offset, ..]) => self
                 .mmap(
                     platform,
@@ -827,16 +1204,44 @@ pub trait Handler {
                 };
                 self.nanosleep(req, rem).map(|_| [0, 0])
             }
+            (SYS_newfstatat, [dirfd, pathname, statbuf, flags, ..]) => {
+                let pathname = platform.validate_str(pathname)?;
+                let statbuf = platform.validate_mut(statbuf)?;
+                self.newfstatat(dirfd as _, pathname, statbuf, flags as _)
+                    .map(|_| [0, 0])
+            }
             (SYS_open, [pathname, flags, mode, ..]) => {
                 let pathname = platform.validate_str(pathname)?;
                 let mode = if mode == 0 { None } else { Some(mode as _) };
                 self.open(pathname, flags as _, mode)
                     .map(|ret| [ret as _, 0])
             }
+            (SYS_openat, [dirfd, pathname, flags, mode, ..]) => {
+                let pathname = platform.validate_str(pathname)?;
+                let mode = if mode == 0 { None } else { Some(mode as _) };
+                self.openat(dirfd as _, pathname, flags as _, mode)
+                    .map(|ret| [ret as _, 0])
+            }
             (SYS_poll, [fds, nfds, timeout, ..]) => {
                 let fds = platform.validate_slice_mut(fds, nfds)?;
                 self.poll(fds, timeout as _).map(|ret| [ret as _, 0])
             }
+            (SYS_pread64, [fd, buf, count, offset, ..]) => {
+                let buf = platform.validate_slice_mut(buf, count)?;
+                self.pread64(fd as _, buf, offset as _).map(|ret| [ret, 0])
+            }
+            (SYS_preadv, [fd, iov, iovcnt, offset, ..]) => {
+                let iovs = platform.validate_iovec_slice_mut(iov, iovcnt)?;
+                self.preadv(fd as _, iovs, offset as _).map(|ret| [ret, 0])
+            }
+            (SYS_pwrite64, [fd, buf, count, offset, ..]) => {
+                let buf = platform.validate_slice(buf, count)?;
+                self.pwrite64(fd as _, buf, offset as _).map(|ret| [ret, 0])
+            }
+            (SYS_pwritev, [fd, iov, iovcnt, offset, ..]) => {
+                let iovs = platform.validate_iovec_slice(iov, iovcnt)?;
+                self.pwritev(fd as _, iovs, offset as _).map(|ret| [ret, 0])
+            }
             (SYS_read, [fd, buf, count, ..]) => {
                 let buf = platform.validate_slice_mut(buf, count)?;
                 self.read(fd as _, buf).map(|ret| [ret, 0])
@@ -860,6 +1265,37 @@ pub trait Handler {
                 }
                 .map(|ret| [ret, 0])
             }
+            (SYS_recvmsg, [sockfd, msg, flags, ..]) => {
+                let msg = platform.validate_mut::<msghdr>(msg)?;
+                let name = if msg.msg_name.is_null() {
+                    None
+                } else {
+                    let name =
+                        platform.validate_slice_mut(msg.msg_name as _, msg.msg_namelen as _)?;
+                    Some(SockaddrOutput::new(name, &mut msg.msg_namelen))
+                };
+                let iov = platform.validate_iovec_slice_mut(msg.msg_iov as _, msg.msg_iovlen)?;
+                let control = if msg.msg_control.is_null() {
+                    &mut []
+                } else {
+                    platform.validate_slice_mut(msg.msg_control as _, msg.msg_controllen)?
+                };
+                let msg = MsghdrOutput {
+                    name,
+                    iov,
+                    control,
+                    controllen: &mut msg.msg_controllen,
+                    flags: &mut msg.msg_flags,
+                };
+                self.recvmsg(sockfd as _, msg, flags as _)
+                    .map(|ret| [ret, 0])
+            }
+            (SYS_renameat2, [olddirfd, oldpath, newdirfd, newpath, flags, ..]) => {
+                let oldpath = platform.validate_str(oldpath)?;
+                let newpath = platform.validate_str(newpath)?;
+                self.renameat2(olddirfd as _, oldpath, newdirfd as _, newpath, flags as _)
+                    .map(|_| [0, 0])
+            }
             (SYS_rt_sigaction, [signum, act, oldact, sigsetsize, ..]) => {
                 let act = if act == 0 {
                     None
@@ -892,6 +1328,24 @@ pub trait Handler {
                 self.rt_sigprocmask(how as _, set, oldset, sigsetsize as _)
                     .map(|_| [0, 0])
             }
+            (SYS_sendmsg, [sockfd, msg, flags, ..]) => {
+                let msg = platform.validate::<msghdr>(msg)?;
+                let name = if msg.msg_name.is_null() {
+                    None
+                } else {
+                    let name = platform.validate_slice(msg.msg_name as _, msg.msg_namelen as _)?;
+                    Some(SockaddrInput(name))
+                };
+                let iov = platform.validate_iovec_slice(msg.msg_iov as _, msg.msg_iovlen)?;
+                let control = if msg.msg_control.is_null() {
+                    &[]
+                } else {
+                    platform.validate_slice(msg.msg_control as _, msg.msg_controllen)?
+                };
+                let msg = MsghdrInput { name, iov, control };
+                self.sendmsg(sockfd as _, msg, flags as _)
+                    .map(|ret| [ret, 0])
+            }
             (SYS_sendto, [sockfd, buf, len, flags, dest_addr, addrlen]) => {
                 let buf = platform.validate_slice(buf, len)?;
                 if dest_addr == 0 {
@@ -931,11 +1385,22 @@ pub trait Handler {
             (SYS_socket, [domain, typ, protocol, ..]) => self
                 .socket(domain as _, typ as _, protocol as _)
                 .map(|ret| [ret as _, 0]),
+            (SYS_statx, [dirfd, pathname, flags, mask, statxbuf, ..]) => {
+                let pathname = platform.validate_str(pathname)?;
+                let statxbuf = platform.validate_mut(statxbuf)?;
+                self.statx(dirfd as _, pathname, flags as _, mask as _, statxbuf)
+                    .map(|_| [0, 0])
+            }
             (SYS_sync, ..) => self.sync().map(|_| [0, 0]),
             (SYS_uname, [buf, ..]) => {
                 let buf = platform.validate_mut(buf)?;
                 self.uname(buf).map(|_| [0, 0])
             }
+            (SYS_unlinkat, [dirfd, pathname, flags, ..]) => {
+                let pathname = platform.validate_str(pathname)?;
+                self.unlinkat(dirfd as _, pathname, flags as _)
+                    .map(|_| [0, 0])
+            }
             (SYS_write, [fd, buf, count, ..]) => {
                 let buf = platform.validate_slice(buf, count)?;
                 self.write(fd as _, buf).map(|ret| [ret, 0])
@@ -948,6 +1413,71 @@ pub trait Handler {
         }
     }
 
+    // Helpers transferring whole buffers over multiple exits, sorted alphabetically.
+    //
+    // A single call transfers at most as many bytes as fit in the block, hence these loop
+    // until the whole buffer is transferred, retrying calls interrupted by [`EINTR`].
+    // Like their `std::io` counterparts, these fail if the whole buffer cannot be transferred,
+    // in which case an unspecified amount of bytes may have been transferred already.
+
+    /// Reads from `fd` until `buf` is full, using as many [`read`](Handler::read) calls
+    /// as necessary.
+    ///
+    /// Returns [`ENODATA`] if end of file is reached before `buf` is full.
+    #[inline]
+    fn read_exact(&mut self, fd: c_int, buf: &mut [u8]) -> Result<()> {
+        transfer(buf.len(), ENODATA, |done| self.read(fd, &mut buf[done..]))
+    }
+
+    /// Receives from `sockfd` until `buf` is full, using as many [`recv`](Handler::recv) calls
+    /// as necessary.
+    ///
+    /// Returns [`ENODATA`] if the peer performs an orderly shutdown before `buf` is full.
+    #[inline]
+    fn recv_exact(&mut self, sockfd: c_int, buf: &mut [u8], flags: c_int) -> Result<()> {
+        transfer(buf.len(), ENODATA, |done| {
+            self.recv(sockfd, &mut buf[done..], flags)
+        })
+    }
+
+    /// Sends the whole `buf` to `sockfd`, using as many [`send`](Handler::send) calls
+    /// as necessary.
+    ///
+    /// Returns [`EPIPE`] if the host sends no bytes.
+    #[inline]
+    fn send_all(&mut self, sockfd: c_int, buf: &[u8], flags: c_int) -> Result<()> {
+        transfer(buf.len(), EPIPE, |done| {
+            self.send(sockfd, &buf[done..], flags)
+        })
+    }
+
+    /// Sends the whole `buf` to `dest_addr` via `sockfd`, using as many
+    /// [`sendto`](Handler::sendto) calls as necessary.
+    ///
+    /// Returns [`EPIPE`] if the host sends no bytes.
+    #[inline]
+    fn sendto_all<'a>(
+        &mut self,
+        sockfd: c_int,
+        buf: &'a [u8],
+        flags: c_int,
+        dest_addr: impl Into<SockaddrInput<'a>>,
+    ) -> Result<()> {
+        let SockaddrInput(dest_addr) = dest_addr.into();
+        transfer(buf.len(), EPIPE, |done| {
+            self.sendto(sockfd, &buf[done..], flags, dest_addr)
+        })
+    }
+
+    /// Writes the whole `buf` to `fd`, using as many [`write`](Handler::write) calls
+    /// as necessary.
+    ///
+    /// Returns [`EPIPE`] if the host writes no bytes.
+    #[inline]
+    fn write_all(&mut self, fd: c_int, buf: &[u8]) -> Result<()> {
+        transfer(buf.len(), EPIPE, |done| self.write(fd, &buf[done..]))
+    }
+
     // GDB calls, sorted alphabetically.
 
     #[cfg_attr(feature = "doc", doc = "Executes [gdbstub::conn::Connection::flush]")]
@@ -1026,6 +1556,20 @@ pub trait Handler {
         })?
     }
 
+    /// Execute `cpuid` instruction storing the result in `result` and validate it against
+    /// the expected `features` as described for [`CpuidFeatures::validate`].
+    #[inline]
+    fn cpuid_validated(
+        &mut self,
+        leaf: u32,
+        sub_leaf: u32,
+        result: &mut CpuidResult,
+        features: &[CpuidFeatures],
+    ) -> Result<()> {
+        self.cpuid(leaf, sub_leaf, result)?;
+        CpuidFeatures::validate(features, leaf, sub_leaf, result)
+    }
+
     /// Requests SGX quote from the host given a report and returns the length of the quote on success.
     #[inline]
     fn get_sgx_quote(&mut self, report: &sgx::Report, quote: &mut [u8]) -> Result<usize> {
@@ -1087,3 +1631,22 @@ pub trait Handler {
         self.execute(enarxcall::TrimSgxPages { addr, length })?
     }
 }
+
+/// Calls `step` with the count of bytes transferred so far until `len` bytes are transferred.
+///
+/// Calls failing with [`EINTR`] are retried. Returns `short` if `step` transfers no bytes
+/// and [`EINVAL`] if it reports more bytes than requested.
+#[inline]
+fn transfer(len: usize, short: c_int, mut step: impl FnMut(usize) -> Result<usize>) -> Result<()> {
+    let mut done = 0;
+    while done < len {
+        match step(done) {
+            Ok(0) => return Err(short),
+            Ok(n) if n > len - done => return Err(EINVAL),
+            Ok(n) => done += n,
+            Err(EINTR) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
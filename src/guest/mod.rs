@@ -14,6 +14,14 @@
 //! - API for execution of an arbitrary [`Call`]:
 //!     - [`execute`](Handler::execute)
 //!
+//! - API for queueing of calls in a [ring](crate::item::ring) of blocks, which only passes control
+//!   to the host once a result is needed, which the host did not produce yet:
+//!     - [`enqueue`](Handler::enqueue)
+//!     - [`dequeue`](Handler::dequeue)
+//!
+//! - API for reception of [notifications](Notification) passed by the host:
+//!     - [`take_notification`](Handler::take_notification)
+//!
 //! - [`libc`]-like API for syscall execution using safe Rust abstractions where possible, for example:
 //!     - [`syscall`](Handler::syscall) corresponding to [`libc::syscall`].
 //!     - [`read`](Handler::read) corresponding to [`libc::read`].
@@ -55,11 +63,19 @@
 pub mod alloc;
 pub mod call;
 
+mod cpuid;
 mod handler;
+mod notification;
 mod platform;
+mod queue;
+mod replay;
 mod tls;
 
 pub use call::{enarxcall, gdbcall, syscall, Call};
+pub use cpuid::*;
 pub use handler::*;
+pub use notification::{Notification, READABLE_MAX};
 pub use platform::*;
+pub use queue::*;
+pub use replay::*;
 pub use tls::*;
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Notifications passed by the host.
+
+use super::SIGRTMAX;
+use crate::item::notification::Number;
+use crate::item::syscall::sigaction;
+use crate::item::{Block, Item};
+
+use core::ffi::c_int;
+
+/// Maximum count of distinct readable file descriptors pending delivery.
+pub const READABLE_MAX: usize = 16;
+
+/// Notification passed by the host.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Notification {
+    /// Signal `signum` is pending and `action` is installed for it.
+    Signal { signum: c_int, action: sigaction },
+
+    /// File descriptor `fd` became readable.
+    Readable { fd: c_int },
+
+    /// The host requests `size` bytes of memory to be returned.
+    ReclaimMemory { size: usize },
+}
+
+/// Notifications pending delivery.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(super) struct Pending {
+    signals: u64,
+    readable: [c_int; READABLE_MAX],
+    readable_len: usize,
+    reclaim: usize,
+}
+
+impl Pending {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            signals: 0,
+            readable: [0; READABLE_MAX],
+            readable_len: 0,
+            reclaim: 0,
+        }
+    }
+
+    /// Records the notification items contained at the beginning of `block`.
+    ///
+    /// Reception stops at the first item, which is not a notification or is malformed.
+    /// Notifications with invalid arguments are ignored.
+    pub fn receive(&mut self, block: &mut [usize]) {
+        for item in Block::from(block).try_iter() {
+            let (num, arg) = match item {
+                Ok(Item::Notification(notification, _)) => (notification.num, notification.argv[0]),
+                _ => return,
+            };
+            match num {
+                Number::Signal => {
+                    if (1..SIGRTMAX as usize).contains(&arg) {
+                        self.signals |= 1 << arg;
+                    }
+                }
+                Number::Readable => match c_int::try_from(arg) {
+                    Ok(fd) if fd >= 0 => self.push_readable(fd),
+                    _ => {}
+                },
+                Number::ReclaimMemory => self.reclaim = self.reclaim.saturating_add(arg),
+            }
+        }
+    }
+
+    /// Records `fd` as readable, unless it is already pending.
+    ///
+    /// The notification is dropped if [`READABLE_MAX`] file descriptors are already pending.
+    fn push_readable(&mut self, fd: c_int) {
+        let pending = &self.readable[..self.readable_len];
+        if !pending.contains(&fd) && self.readable_len < READABLE_MAX {
+            self.readable[self.readable_len] = fd;
+            self.readable_len += 1;
+        }
+    }
+
+    /// Removes and returns the next pending notification.
+    ///
+    /// Signals, for which no action is installed in `actions`, are discarded.
+    pub fn pop(&mut self, actions: &[Option<sigaction>]) -> Option<Notification> {
+        while self.signals != 0 {
+            let signum = self.signals.trailing_zeros();
+            self.signals &= !(1 << signum);
+            if let Some(Some(action)) = actions.get(signum as usize) {
+                return Some(Notification::Signal {
+                    signum: signum as _,
+                    action: *action,
+                });
+            }
+        }
+        if self.readable_len > 0 {
+            let fd = self.readable[0];
+            self.readable.copy_within(1..self.readable_len, 0);
+            self.readable_len -= 1;
+            return Some(Notification::Readable { fd });
+        }
+        if self.reclaim > 0 {
+            let size = self.reclaim;
+            self.reclaim = 0;
+            return Some(Notification::ReclaimMemory { size });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host;
+    use crate::item::{notification, BlockWriter, Notification as Item};
+
+    #[test]
+    fn receive() {
+        let mut block = [0; 64];
+        BlockWriter::new(&mut block).unwrap();
+        assert_eq!(
+            host::notify(
+                &mut block,
+                [
+                    (notification::Number::Readable, 5),
+                    (notification::Number::Signal, 10),
+                    (notification::Number::ReclaimMemory, 4096),
+                    (notification::Number::Signal, SIGRTMAX as _),
+                    (notification::Number::Signal, 2),
+                    (notification::Number::Readable, 5),
+                    (notification::Number::Readable, usize::MAX),
+                    (notification::Number::ReclaimMemory, 4096),
+                    (notification::Number::Readable, 3),
+                ]
+                .map(|(num, arg)| Item {
+                    num,
+                    argv: [arg, 0],
+                }),
+            ),
+            Ok(9)
+        );
+
+        let mut pending = Pending::new();
+        pending.receive(&mut block);
+
+        let mut actions = [None; SIGRTMAX as _];
+        actions[10] = Some([1, 2, 3, 4]);
+        assert_eq!(
+            pending.pop(&actions),
+            Some(Notification::Signal {
+                signum: 10,
+                action: [1, 2, 3, 4]
+            })
+        );
+        assert_eq!(
+            pending.pop(&actions),
+            Some(Notification::Readable { fd: 5 })
+        );
+        assert_eq!(
+            pending.pop(&actions),
+            Some(Notification::Readable { fd: 3 })
+        );
+        assert_eq!(
+            pending.pop(&actions),
+            Some(Notification::ReclaimMemory { size: 8192 })
+        );
+        assert_eq!(pending.pop(&actions), None);
+    }
+
+    #[test]
+    fn receive_full() {
+        let mut block = [0; 8 * READABLE_MAX];
+        BlockWriter::new(&mut block).unwrap();
+        let notifications = (0..READABLE_MAX + 1).map(|fd| Item {
+            num: notification::Number::Readable,
+            argv: [fd, 0],
+        });
+        assert_eq!(
+            host::notify(&mut block, notifications),
+            Ok(READABLE_MAX + 1)
+        );
+
+        let mut pending = Pending::new();
+        pending.receive(&mut block);
+        for fd in 0..READABLE_MAX {
+            assert_eq!(
+                pending.pop(&[]),
+                Some(Notification::Readable { fd: fd as _ })
+            );
+        }
+        assert_eq!(pending.pop(&[]), None);
+    }
+}
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::alloc::{phase, Alloc};
+
+/// Call [queued](super::Handler::enqueue) in a block of a [ring](crate::item::ring), whose
+/// result is obtained by [dequeuing](super::Handler::dequeue) it.
+#[must_use = "the call must be dequeued to collect its result"]
+pub struct Queued<'a, T> {
+    pub(super) call: T,
+    pub(super) alloc: Alloc<'a, phase::Commit>,
+
+    /// Index of the `END` item within the block.
+    pub(super) end: usize,
+
+    /// Ring index of the block.
+    pub(super) index: usize,
+}
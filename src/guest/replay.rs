@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{Handler, Platform, ThreadLocalStorage};
+use crate::item::record::Reader;
+use crate::libc::{off_t, EIO, ENODATA};
+use crate::Result;
+
+use core::ffi::{c_int, c_size_t, c_ulong, c_void};
+use core::ptr::NonNull;
+
+/// [`Handler`], which replays a recording produced by [`Recorder`](crate::host::Recorder)
+/// instead of passing control to the host.
+///
+/// All required methods except [`sally`](Handler::sally) are delegated to the wrapped handler.
+/// Provided methods overridden by the wrapped handler are not delegated.
+#[derive(Debug)]
+pub struct ReplayHandler<'a, H> {
+    handler: H,
+    recording: Reader<'a>,
+}
+
+impl<'a, H: Handler> ReplayHandler<'a, H> {
+    /// Creates a new [`ReplayHandler`], which replays `recording` using the block and
+    /// thread-local storage of `handler`.
+    ///
+    /// For the replay to succeed, the state of `handler` must match the state of the handler
+    /// at the time the recording was started.
+    #[inline]
+    pub fn new(handler: H, recording: &'a [u8]) -> Result<Self> {
+        Ok(Self {
+            handler,
+            recording: Reader::new(recording)?,
+        })
+    }
+
+    /// Returns the wrapped handler.
+    #[inline]
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+}
+
+impl<'a, H: Handler> Handler for ReplayHandler<'a, H> {
+    /// Verifies that the block matches the next recorded block passed by the guest and
+    /// replaces its contents by the recorded block returned by the host.
+    ///
+    /// Returns [`ENODATA`] if the recording is exhausted and [`EIO`] if the guest diverged
+    /// from the recording.
+    fn sally(&mut self) -> Result<()> {
+        let exchange = self.recording.next().ok_or(ENODATA)??;
+        let block = self.handler.block_mut();
+
+        let before = exchange.before();
+        match block.get(..before.len()) {
+            Some(prefix) if before.eq(prefix.iter().copied()) => {}
+            _ => return Err(EIO),
+        }
+
+        let after = exchange.after();
+        if after.len() > block.len() {
+            return Err(EIO);
+        }
+        block
+            .iter_mut()
+            .zip(after)
+            .for_each(|(dst, src)| *dst = src);
+        Ok(())
+    }
+
+    #[inline]
+    fn block(&self) -> &[usize] {
+        self.handler.block()
+    }
+
+    #[inline]
+    fn block_mut(&mut self) -> &mut [usize] {
+        self.handler.block_mut()
+    }
+
+    #[inline]
+    fn thread_local_storage(&mut self) -> &mut ThreadLocalStorage {
+        self.handler.thread_local_storage()
+    }
+
+    #[inline]
+    fn arch_prctl(&mut self, platform: &impl Platform, code: c_int, addr: c_ulong) -> Result<()> {
+        self.handler.arch_prctl(platform, code, addr)
+    }
+
+    #[inline]
+    fn brk(
+        &mut self,
+        platform: &impl Platform,
+        addr: Option<NonNull<c_void>>,
+    ) -> Result<NonNull<c_void>> {
+        self.handler.brk(platform, addr)
+    }
+
+    #[inline]
+    fn madvise(
+        &mut self,
+        platform: &impl Platform,
+        addr: NonNull<c_void>,
+        length: c_size_t,
+        advice: c_int,
+    ) -> Result<()> {
+        self.handler.madvise(platform, addr, length, advice)
+    }
+
+    #[inline]
+    fn mmap(
+        &mut self,
+        platform: &impl Platform,
+        addr: Option<NonNull<c_void>>,
+        length: c_size_t,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: off_t,
+    ) -> Result<NonNull<c_void>> {
+        self.handler
+            .mmap(platform, addr, length, prot, flags, fd, offset)
+    }
+
+    #[inline]
+    fn mprotect(
+        &mut self,
+        platform: &impl Platform,
+        addr: NonNull<c_void>,
+        len: c_size_t,
+        prot: c_int,
+    ) -> Result<()> {
+        self.handler.mprotect(platform, addr, len, prot)
+    }
+
+    #[inline]
+    fn munmap(
+        &mut self,
+        platform: &impl Platform,
+        addr: NonNull<c_void>,
+        length: c_size_t,
+    ) -> Result<()> {
+        self.handler.munmap(platform, addr, length)
+    }
+}
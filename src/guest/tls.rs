@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use super::notification::Pending;
 use crate::item::syscall::sigaction;
+use crate::Result;
 
 use core::ffi::c_int;
 
@@ -9,6 +11,10 @@ pub(super) const SIGRTMAX: c_int = 64;
 /// Thread-local storage shared between [`Handler`](super::Handler) instances.
 pub struct ThreadLocalStorage {
     pub(super) actions: [Option<sigaction>; SIGRTMAX as _],
+    pub(super) pending: Pending,
+
+    /// Result of the protocol version handshake with the host, once performed.
+    pub(super) hello: Option<Result<()>>,
 }
 
 impl ThreadLocalStorage {
@@ -16,6 +22,8 @@ impl ThreadLocalStorage {
     pub const fn new() -> Self {
         Self {
             actions: [None; SIGRTMAX as _],
+            pending: Pending::new(),
+            hello: None,
         }
     }
 }
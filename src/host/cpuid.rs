@@ -0,0 +1,254 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{deref_aligned, Executor};
+use crate::item::enarxcall::Number;
+use crate::item::hello::Reply;
+use crate::item::{Enarxcall, Gdbcall, Hello, Syscall};
+use crate::Result;
+
+use core::arch::x86_64::CpuidResult;
+use core::ops::RangeInclusive;
+
+/// Leaves reserved for hypervisor use.
+const HYPERVISOR_LEAVES: RangeInclusive<u32> = 0x4000_0000..=0x4fff_ffff;
+
+/// Hypervisor present bit in ECX of leaf `1`.
+const HYPERVISOR_BIT: u32 = 1 << 31;
+
+/// Rule of a [`CpuidPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuidRule {
+    /// Clears the bits set in `mask` in the result of `leaf` and `sub_leaf`. A `sub_leaf` of
+    /// `None` matches all sub-leaves.
+    Mask {
+        leaf: u32,
+        sub_leaf: Option<u32>,
+        mask: CpuidResult,
+    },
+
+    /// Replaces the result of `leaf` and `sub_leaf` by `value`. A `sub_leaf` of `None` matches
+    /// all sub-leaves.
+    Force {
+        leaf: u32,
+        sub_leaf: Option<u32>,
+        value: CpuidResult,
+    },
+
+    /// Hides the presence of a hypervisor by clearing the hypervisor present bit of leaf `1`
+    /// and zeroing the hypervisor leaves `0x4000_0000` to `0x4fff_ffff`.
+    HideHypervisor,
+}
+
+impl CpuidRule {
+    /// Applies the rule to `result` of `leaf` and `sub_leaf`.
+    fn apply(&self, leaf: u32, sub_leaf: u32, result: &mut CpuidResult) {
+        let matches = |l: u32, s: Option<u32>| l == leaf && (s.is_none() || s == Some(sub_leaf));
+        match *self {
+            Self::Mask {
+                leaf: l,
+                sub_leaf: s,
+                mask,
+            } if matches(l, s) => {
+                result.eax &= !mask.eax;
+                result.ebx &= !mask.ebx;
+                result.ecx &= !mask.ecx;
+                result.edx &= !mask.edx;
+            }
+            Self::Force {
+                leaf: l,
+                sub_leaf: s,
+                value,
+            } if matches(l, s) => *result = value,
+            Self::HideHypervisor if leaf == 1 => result.ecx &= !HYPERVISOR_BIT,
+            Self::HideHypervisor if HYPERVISOR_LEAVES.contains(&leaf) => {
+                *result = CpuidResult {
+                    eax: 0,
+                    ebx: 0,
+                    ecx: 0,
+                    edx: 0,
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Declarative policy adjusting the results of the `cpuid` instruction exposed to the guest.
+///
+/// The rules are applied in order, so that later rules take precedence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuidPolicy<'a> {
+    rules: &'a [CpuidRule],
+}
+
+impl<'a> CpuidPolicy<'a> {
+    /// Creates a new [`CpuidPolicy`] consisting of `rules`.
+    #[inline]
+    pub const fn new(rules: &'a [CpuidRule]) -> Self {
+        Self { rules }
+    }
+
+    /// Applies the policy to `result` of `leaf` and `sub_leaf`.
+    #[inline]
+    pub fn apply(&self, leaf: u32, sub_leaf: u32, result: &mut CpuidResult) {
+        self.rules
+            .iter()
+            .for_each(|rule| rule.apply(leaf, sub_leaf, result))
+    }
+}
+
+/// [`Executor`] applying a [`CpuidPolicy`] to the results of [`Cpuid`](Number::Cpuid) Enarx
+/// calls executed by another executor.
+#[derive(Debug)]
+pub struct CpuidFilter<'a, E> {
+    policy: CpuidPolicy<'a>,
+    executor: E,
+}
+
+impl<'a, E: Executor> CpuidFilter<'a, E> {
+    /// Creates a new [`CpuidFilter`] of `policy`, which passes all items to `executor`.
+    #[inline]
+    pub fn new(policy: CpuidPolicy<'a>, executor: E) -> Self {
+        Self { policy, executor }
+    }
+
+    /// Returns the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.executor
+    }
+}
+
+impl<E: Executor> Executor for CpuidFilter<'_, E> {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        self.executor.syscall(call, data)
+    }
+
+    #[inline]
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        self.executor.offload(call, data)
+    }
+
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        self.executor.gdbcall(call, data)
+    }
+
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        self.executor.enarxcall(call, data)?;
+        if let Enarxcall {
+            num: Number::Cpuid,
+            argv: [leaf, sub_leaf, result_offset, _],
+            ret: 0,
+        } = *call
+        {
+            let result = deref_aligned::<CpuidResult>(data, result_offset, 1)?;
+            self.policy
+                .apply(leaf as _, sub_leaf as _, unsafe { &mut *result });
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn hello(&mut self, call: &mut Hello, data: &mut [u8]) -> Result<()> {
+        self.executor.hello(call, data)
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        self.executor.reply()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{execute_with, Native};
+    use crate::item::Item;
+    use crate::libc::ENOSYS;
+
+    use core::mem::size_of;
+
+    const ZERO: CpuidResult = CpuidResult {
+        eax: 0,
+        ebx: 0,
+        ecx: 0,
+        edx: 0,
+    };
+
+    const ONES: CpuidResult = CpuidResult {
+        eax: u32::MAX,
+        ebx: u32::MAX,
+        ecx: u32::MAX,
+        edx: u32::MAX,
+    };
+
+    const RULES: &[CpuidRule] = &[
+        CpuidRule::Mask {
+            leaf: 7,
+            sub_leaf: Some(0),
+            mask: CpuidResult { ebx: 0xf0, ..ZERO },
+        },
+        CpuidRule::Force {
+            leaf: 0xd,
+            sub_leaf: None,
+            value: CpuidResult { eax: 42, ..ZERO },
+        },
+        CpuidRule::HideHypervisor,
+    ];
+
+    #[test]
+    fn apply() {
+        let policy = CpuidPolicy::new(RULES);
+        for (leaf, sub_leaf, expected) in [
+            (
+                1,
+                0,
+                CpuidResult {
+                    ecx: !(1 << 31),
+                    ..ONES
+                },
+            ),
+            (7, 0, CpuidResult { ebx: !0xf0, ..ONES }),
+            (7, 1, ONES),
+            (0xd, 0, CpuidResult { eax: 42, ..ZERO }),
+            (0xd, 5, CpuidResult { eax: 42, ..ZERO }),
+            (0x4000_0000, 0, ZERO),
+            (0x8000_0000, 0, ONES),
+        ] {
+            let mut result = ONES;
+            policy.apply(leaf, sub_leaf, &mut result);
+            assert_eq!(result, expected, "leaf {leaf:#x} sub-leaf {sub_leaf}");
+        }
+    }
+
+    #[test]
+    fn filter() {
+        let mut filter = CpuidFilter::new(CpuidPolicy::new(RULES), Native);
+        for leaf in [1, 0xd] {
+            let mut result = ZERO;
+            let data = unsafe {
+                core::slice::from_raw_parts_mut(
+                    &mut result as *mut _ as *mut u8,
+                    size_of::<CpuidResult>(),
+                )
+            };
+            let mut call = Enarxcall {
+                num: Number::Cpuid,
+                argv: [leaf, 0, 0, 0],
+                ret: -ENOSYS as _,
+            };
+            execute_with(&mut filter, [Item::Enarxcall(&mut call, data)]).unwrap();
+            if cfg!(miri) {
+                assert_eq!(call.ret, -ENOSYS as _);
+                continue;
+            }
+            assert_eq!(call.ret, 0);
+            match leaf {
+                1 => assert_eq!(result.ecx & 1 << 31, 0),
+                _ => assert_eq!(result, CpuidResult { eax: 42, ..ZERO }),
+            }
+        }
+    }
+}
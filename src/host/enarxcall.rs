@@ -7,6 +7,9 @@ use crate::{item, Result};
 use core::arch::asm;
 use core::arch::x86_64::CpuidResult;
 
+/// Enarx calls supported by [`execute`].
+pub(super) const NUMBERS: &[Number] = &[Number::Cpuid];
+
 pub(super) unsafe fn execute(call: &mut item::Enarxcall, data: &mut [u8]) -> Result<()> {
     #[allow(clippy::single_match)]
     match call {
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::hello;
+use crate::item::hello::Reply;
+use crate::item::{Enarxcall, Gdbcall, Hello, Syscall};
+use crate::Result;
+
+/// Executor of the items passed by the guest.
+///
+/// Each method executes a single item of the respective kind and either writes the result
+/// into the return value of the item or returns an error, in which case the negated error
+/// number is written into the first return value of the item by the caller.
+///
+/// The provided methods implement the default behavior of the host, which executes syscalls
+/// and Enarx calls directly and silently skips unsupported items. Implementors may override
+/// individual methods to virtualize, deny or redirect calls and delegate the rest to [`Native`].
+pub trait Executor {
+    /// Executes a [`Syscall`] item referencing memory in `data`.
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        #[cfg(not(miri))]
+        unsafe { super::syscall::execute(call, data) }?;
+        #[cfg(miri)]
+        let _ = (call, data);
+        Ok(())
+    }
+
+    /// Checks and translates a host-private copy of a [`Syscall`] item referencing memory in
+    /// `data`, which is about to be executed by the kernel directly, bypassing
+    /// [`syscall`](Executor::syscall), for example, by a [`Uring`](super::Uring).
+    ///
+    /// Returns `Ok(true)` if `call` may be executed this way, `Ok(false)` if it must be passed
+    /// to [`syscall`](Executor::syscall) instead and an error if it must fail without being
+    /// executed. As executors are not passed the result of such calls, no call is allowed by
+    /// default.
+    #[inline]
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        let _ = (call, data);
+        Ok(false)
+    }
+
+    /// Executes a [`Gdbcall`] item referencing memory in `data`.
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        let _ = (call, data);
+        Ok(())
+    }
+
+    /// Executes an [`Enarxcall`] item referencing memory in `data`.
+    #[inline]
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        #[cfg(not(miri))]
+        unsafe { super::enarxcall::execute(call, data) }?;
+        #[cfg(miri)]
+        let _ = (call, data);
+        Ok(())
+    }
+
+    /// Executes a [`Hello`] item referencing memory in `data` by writing the
+    /// [`reply`](Executor::reply) into it.
+    #[inline]
+    fn hello(&mut self, call: &mut Hello, data: &mut [u8]) -> Result<()> {
+        let reply = self.reply();
+        unsafe { hello::execute(call, data, reply) }
+    }
+
+    /// Returns the [`Reply`] describing the item kinds, syscalls and Enarx calls supported
+    /// by the executor.
+    #[inline]
+    fn reply(&self) -> Reply {
+        hello::reply()
+    }
+}
+
+/// [`Executor`] executing items directly on the host using the provided methods.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Native;
+
+impl Executor for Native {
+    #[inline]
+    fn offload(&mut self, _: &mut Syscall, _: &mut [u8]) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+impl<E: Executor + ?Sized> Executor for &mut E {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        (**self).syscall(call, data)
+    }
+
+    #[inline]
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        (**self).offload(call, data)
+    }
+
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        (**self).gdbcall(call, data)
+    }
+
+    #[inline]
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        (**self).enarxcall(call, data)
+    }
+
+    #[inline]
+    fn hello(&mut self, call: &mut Hello, data: &mut [u8]) -> Result<()> {
+        (**self).hello(call, data)
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        (**self).reply()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::execute_with;
+    use crate::item::Item;
+    use crate::NULL;
+
+    use libc::{SYS_getpid, SYS_sync, ENOSYS, EPERM};
+
+    /// Denies `sync` and virtualizes `getpid`.
+    struct Deny;
+
+    impl Executor for Deny {
+        fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+            #[allow(non_upper_case_globals)]
+            match call.num as _ {
+                SYS_sync => Err(EPERM),
+                SYS_getpid => {
+                    call.ret = [42, 0];
+                    Ok(())
+                }
+                _ => Native.syscall(call, data),
+            }
+        }
+    }
+
+    #[test]
+    fn override_syscall() {
+        let mut sync = Syscall {
+            num: SYS_sync as _,
+            argv: [NULL; 6],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut getpid = Syscall {
+            num: SYS_getpid as _,
+            argv: [NULL; 6],
+            ret: [-ENOSYS as _, 0],
+        };
+        assert_eq!(
+            execute_with(
+                &mut Deny,
+                [
+                    Item::Syscall(&mut sync, &mut []),
+                    Item::Syscall(&mut getpid, &mut []),
+                ]
+            ),
+            Ok(())
+        );
+        assert_eq!(sync.ret, [-EPERM as _, 0]);
+        assert_eq!(getpid.ret, [42, 0]);
+    }
+}
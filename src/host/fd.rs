@@ -0,0 +1,764 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{deref_aligned, deref_aligned_slice, private, Executor};
+use crate::item::hello::Reply;
+use crate::item::syscall::Cmsgs;
+use crate::item::{Enarxcall, Gdbcall, Hello, Syscall};
+use crate::libc::{
+    msghdr, pollfd, SYS_accept, SYS_accept4, SYS_bind, SYS_clock_getres, SYS_clock_gettime,
+    SYS_close, SYS_connect, SYS_dup, SYS_dup2, SYS_dup3, SYS_epoll_create1, SYS_epoll_ctl,
+    SYS_epoll_pwait, SYS_epoll_wait, SYS_eventfd2, SYS_exit, SYS_exit_group, SYS_fchmod, SYS_fcntl,
+    SYS_fstat, SYS_ftruncate, SYS_getdents64, SYS_getsockname, SYS_ioctl, SYS_listen, SYS_lseek,
+    SYS_mkdirat, SYS_nanosleep, SYS_newfstatat, SYS_open, SYS_openat, SYS_poll, SYS_pread64,
+    SYS_preadv, SYS_pwrite64, SYS_pwritev, SYS_read, SYS_readv, SYS_recvfrom, SYS_recvmsg,
+    SYS_renameat2, SYS_sendmsg, SYS_sendto, SYS_setsockopt, SYS_socket, SYS_statx, SYS_sync,
+    SYS_unlinkat, SYS_write, SYS_writev, AT_FDCWD, EBADF, EFAULT, EINVAL, EMFILE, ENOSYS, F_DUPFD,
+    F_DUPFD_CLOEXEC, MSG_CTRUNC, O_CLOEXEC, POLLNVAL, SCM_RIGHTS, SOL_SOCKET,
+};
+use crate::{Result, NULL};
+
+use core::ffi::{c_int, c_long};
+use core::mem::size_of;
+
+/// Syscalls supported by [`FdTable`], sorted alphabetically, along with the indices of their
+/// file descriptor arguments and whether they return a new file descriptor.
+///
+/// `close`, `dup2`, `dup3`, `fcntl` and `poll` are translated separately, as are the file
+/// descriptors passed in `SCM_RIGHTS` control messages of `recvmsg` and `sendmsg`.
+const SYSCALLS: &[(c_long, &[usize], bool)] = &[
+    (SYS_accept, &[0], true),
+    (SYS_accept4, &[0], true),
+    (SYS_bind, &[0], false),
+    (SYS_clock_getres, &[], false),
+    (SYS_clock_gettime, &[], false),
+    (SYS_connect, &[0], false),
+    (SYS_dup, &[0], true),
+    (SYS_epoll_create1, &[], true),
+    (SYS_epoll_ctl, &[0, 2], false),
+    (SYS_epoll_pwait, &[0], false),
+    (SYS_epoll_wait, &[0], false),
+    (SYS_eventfd2, &[], true),
+    (SYS_exit, &[], false),
+    (SYS_exit_group, &[], false),
+    (SYS_fchmod, &[0], false),
+    (SYS_fstat, &[0], false),
+    (SYS_ftruncate, &[0], false),
+    (SYS_getdents64, &[0], false),
+    (SYS_getsockname, &[0], false),
+    (SYS_ioctl, &[0], false),
+    (SYS_listen, &[0], false),
+    (SYS_lseek, &[0], false),
+    (SYS_mkdirat, &[0], false),
+    (SYS_nanosleep, &[], false),
+    (SYS_newfstatat, &[0], false),
+    (SYS_open, &[], true),
+    (SYS_openat, &[0], true),
+    (SYS_pread64, &[0], false),
+    (SYS_preadv, &[0], false),
+    (SYS_pwrite64, &[0], false),
+    (SYS_pwritev, &[0], false),
+    (SYS_read, &[0], false),
+    (SYS_readv, &[0], false),
+    (SYS_recvfrom, &[0], false),
+    (SYS_recvmsg, &[0], false),
+    (SYS_renameat2, &[0, 2], false),
+    (SYS_sendmsg, &[0], false),
+    (SYS_sendto, &[0], false),
+    (SYS_setsockopt, &[0], false),
+    (SYS_socket, &[], true),
+    (SYS_statx, &[0], false),
+    (SYS_sync, &[], false),
+    (SYS_unlinkat, &[0], false),
+    (SYS_write, &[0], false),
+    (SYS_writev, &[0], false),
+];
+
+/// Syscalls of [`SYSCALLS`], whose directory file descriptor arguments are passed through
+/// untranslated if they are [`AT_FDCWD`].
+const AT_SYSCALLS: &[c_long] = &[
+    SYS_mkdirat,
+    SYS_newfstatat,
+    SYS_openat,
+    SYS_renameat2,
+    SYS_statx,
+    SYS_unlinkat,
+];
+
+/// Returns the value of a syscall return value `ret` on success.
+#[inline]
+fn success(ret: usize) -> Option<usize> {
+    match ret as isize {
+        -4095..=-1 => None,
+        _ => Some(ret),
+    }
+}
+
+/// Returns the control messages of the [`msghdr`] at `offset` within `data`.
+#[inline]
+fn control(data: &mut [u8], offset: usize) -> Result<Cmsgs<&mut [u8]>> {
+    let msg = unsafe { deref_aligned::<msghdr>(data, offset, 1)?.read() };
+    let control = match msg.msg_control as usize {
+        NULL => &mut [],
+        control => control
+            .checked_add(msg.msg_controllen)
+            .and_then(|end| data.get_mut(control..end))
+            .ok_or(EFAULT)?,
+    };
+    Ok(Cmsgs::new(control))
+}
+
+/// Calls `f` with each file descriptor passed in the `SCM_RIGHTS` control messages of
+/// `cmsgs` and replaces it by the file descriptor returned.
+fn for_each_right(
+    mut cmsgs: Cmsgs<&mut [u8]>,
+    mut f: impl FnMut(c_int) -> Result<c_int>,
+) -> Result<()> {
+    while let Some(cmsg) = cmsgs.next() {
+        match cmsg? {
+            (cmsg, data) if cmsg.cmsg_level == SOL_SOCKET && cmsg.cmsg_type == SCM_RIGHTS => {
+                for fd in cmsgs.control_mut()[data].chunks_exact_mut(size_of::<c_int>()) {
+                    let new = f(c_int::from_ne_bytes(fd.try_into().unwrap()))?;
+                    fd.copy_from_slice(&new.to_ne_bytes());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// [`Executor`] translating guest file descriptors into host file descriptors before passing
+/// syscalls to another executor.
+///
+/// The guest can only access host file descriptors [granted](FdTable::grant) to it or created
+/// on its behalf, which are mapped to guest file descriptors below `N`. All file descriptor
+/// arguments and return values of syscalls are rewritten accordingly and syscalls, which cannot
+/// be translated, fail with [`ENOSYS`].
+///
+/// Translated arguments and memory are only passed to the wrapped executor in host-private
+/// copies, so that the guest can neither observe nor modify host file descriptors. Hence
+/// `poll`, `recvmsg` and `sendmsg` referencing data sections larger than the host-private
+/// memory fail with [`ENOMEM`](crate::libc::ENOMEM).
+///
+/// Syscalls [offloaded](Executor::offload) to the kernel are translated likewise, apart from
+/// syscalls creating, releasing or passing file descriptors in memory, which are always
+/// executed by the [`FdTable`].
+#[derive(Debug)]
+pub struct FdTable<E, const N: usize> {
+    executor: E,
+    fds: [Option<c_int>; N],
+}
+
+impl<E: Executor, const N: usize> FdTable<E, N> {
+    /// Creates a new empty [`FdTable`], which passes translated items to `executor`.
+    #[inline]
+    pub fn new(executor: E) -> Self {
+        Self {
+            executor,
+            fds: [None; N],
+        }
+    }
+
+    /// Returns the host file descriptor mapped to guest file descriptor `fd`, if any.
+    #[inline]
+    pub fn get(&self, fd: c_int) -> Option<c_int> {
+        *usize::try_from(fd).ok().and_then(|fd| self.fds.get(fd))?
+    }
+
+    /// Grants the guest access to `host_fd` and returns the lowest free guest file descriptor
+    /// it is mapped to.
+    ///
+    /// Returns [`EMFILE`] if no guest file descriptor is free.
+    #[inline]
+    pub fn grant(&mut self, host_fd: c_int) -> Result<c_int> {
+        self.insert(0, host_fd).map(|fd| fd as _)
+    }
+
+    /// Grants the guest access to `host_fd` as guest file descriptor `fd` and returns the host
+    /// file descriptor `fd` was mapped to before, if any.
+    ///
+    /// Returns [`EBADF`] if `fd` is out of range.
+    #[inline]
+    pub fn grant_at(&mut self, fd: c_int, host_fd: c_int) -> Result<Option<c_int>> {
+        let slot = usize::try_from(fd)
+            .ok()
+            .and_then(|fd| self.fds.get_mut(fd))
+            .ok_or(EBADF)?;
+        Ok(slot.replace(host_fd))
+    }
+
+    /// Revokes guest access to guest file descriptor `fd` and returns the host file descriptor
+    /// it was mapped to, if any.
+    ///
+    /// The host file descriptor is not closed.
+    #[inline]
+    pub fn revoke(&mut self, fd: c_int) -> Option<c_int> {
+        usize::try_from(fd)
+            .ok()
+            .and_then(|fd| self.fds.get_mut(fd))?
+            .take()
+    }
+
+    /// Returns the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.executor
+    }
+
+    /// Maps `host_fd` to the lowest free guest file descriptor not less than `min`.
+    fn insert(&mut self, min: usize, host_fd: c_int) -> Result<usize> {
+        let (fd, slot) = self
+            .fds
+            .iter_mut()
+            .enumerate()
+            .skip(min)
+            .find(|(_, slot)| slot.is_none())
+            .ok_or(EMFILE)?;
+        *slot = Some(host_fd);
+        Ok(fd)
+    }
+
+    /// Returns the index of the slot of guest file descriptor `arg` passed as a syscall argument.
+    fn slot(arg: usize) -> Result<usize> {
+        usize::try_from(arg as isize)
+            .ok()
+            .filter(|fd| *fd < N)
+            .ok_or(EBADF)
+    }
+
+    /// Translates guest file descriptor `arg` passed as a syscall argument.
+    fn translate(&self, arg: usize) -> Result<usize> {
+        self.fds[Self::slot(arg)?].map(|fd| fd as _).ok_or(EBADF)
+    }
+
+    /// Executes syscall `num` with `argv` not referencing memory using the wrapped executor.
+    fn call(&mut self, num: c_long, argv: [usize; 6]) -> Result<usize> {
+        let mut call = Syscall {
+            num: num as _,
+            argv,
+            ret: [-ENOSYS as _, 0],
+        };
+        self.executor.syscall(&mut call, &mut [])?;
+        success(call.ret[0]).ok_or(-(call.ret[0] as isize) as _)
+    }
+
+    /// Maps `host_fd` returned by the host to the lowest free guest file descriptor not less
+    /// than `min` and returns it.
+    ///
+    /// `host_fd` is closed if no guest file descriptor is free.
+    fn map(&mut self, min: usize, host_fd: usize) -> Result<usize> {
+        self.insert(min, host_fd as _).inspect_err(|_| {
+            let _ = self.call(SYS_close, [host_fd, 0, 0, 0, 0, 0]);
+        })
+    }
+
+    /// Executes a copy of `call` with the file descriptor arguments at `fd_args` translated
+    /// and copies the return values back into `call`.
+    fn execute(&mut self, call: &mut Syscall, data: &mut [u8], fd_args: &[usize]) -> Result<()> {
+        let mut copy = *call;
+        self.translate_args(&mut copy, fd_args)?;
+        let ret = self.executor.syscall(&mut copy, data);
+        call.ret = copy.ret;
+        ret
+    }
+
+    /// Translates the file descriptor arguments of `call` at `fd_args`.
+    fn translate_args(&self, call: &mut Syscall, fd_args: &[usize]) -> Result<()> {
+        let at = AT_SYSCALLS.contains(&(call.num as _));
+        for i in fd_args {
+            if at && call.argv[*i] as c_int == AT_FDCWD {
+                continue;
+            }
+            call.argv[*i] = self.translate(call.argv[*i])?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        self.execute(call, data, &[0])?;
+        // The file descriptor is released even if `close` fails.
+        self.fds[Self::slot(call.argv[0])?] = None;
+        Ok(())
+    }
+
+    /// Executes `dup2` or `dup3` by duplicating the host file descriptor using `fcntl` command
+    /// `cmd` and mapping the duplicate to the requested guest file descriptor.
+    fn dup(&mut self, call: &mut Syscall, cmd: c_int) -> Result<()> {
+        let [oldfd, newfd, ..] = call.argv;
+        let old = self.translate(oldfd)?;
+        let new = Self::slot(newfd)?;
+        if oldfd != newfd {
+            let dup = self.call(SYS_fcntl, [old, cmd as _, 0, 0, 0, 0])?;
+            if let Some(prev) = self.fds[new].replace(dup as _) {
+                let _ = self.call(SYS_close, [prev as _, 0, 0, 0, 0, 0]);
+            }
+        }
+        call.ret = [newfd, 0];
+        Ok(())
+    }
+
+    fn fcntl(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        let [_, cmd, arg, ..] = call.argv;
+        let dup = matches!(cmd as c_int, F_DUPFD | F_DUPFD_CLOEXEC);
+        if dup && arg >= N {
+            return Err(EINVAL);
+        }
+        self.execute(call, data, &[0])?;
+        match success(call.ret[0]) {
+            Some(host_fd) if dup => call.ret[0] = self.map(arg, host_fd)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Executes `poll` with the file descriptors in a host-private copy of the data section
+    /// translated.
+    ///
+    /// Like the `RLIMIT_NOFILE` limit of Linux, `N` limits the count of file descriptors.
+    /// Entries with guest file descriptors not mapped to host file descriptors are reported
+    /// as [`POLLNVAL`].
+    fn poll(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        let [fds_offset, nfds, ..] = call.argv;
+        if nfds > N {
+            return Err(EINVAL);
+        }
+        private(data, |data| {
+            let mut guest = [0; N];
+            let mut invalid = 0;
+            {
+                let fds = unsafe { &mut *deref_aligned_slice::<pollfd>(data, fds_offset, nfds)? };
+                for (fd, guest) in fds.iter_mut().zip(&mut guest) {
+                    *guest = fd.fd;
+                    if fd.fd >= 0 {
+                        fd.fd = self.get(fd.fd).unwrap_or_else(|| {
+                            invalid += 1;
+                            -1
+                        });
+                    }
+                }
+            }
+            let mut copy = *call;
+            if invalid > 0 {
+                // Linux does not block if any entry is invalid.
+                copy.argv[2] = 0;
+            }
+            let ret = self.executor.syscall(&mut copy, data);
+            call.ret = copy.ret;
+
+            // The host file descriptors are replaced before the copy is passed back to the guest.
+            let fds = unsafe { &mut *deref_aligned_slice::<pollfd>(data, fds_offset, nfds)? };
+            for (fd, guest) in fds.iter_mut().zip(guest) {
+                if guest >= 0 && fd.fd < 0 {
+                    fd.revents = POLLNVAL;
+                }
+                fd.fd = guest;
+            }
+            ret?;
+            if let Some(ret) = success(call.ret[0]) {
+                call.ret[0] = ret + invalid;
+            }
+            Ok(())
+        })?
+    }
+
+    /// Executes `recvmsg` and maps the host file descriptors received in `SCM_RIGHTS` control
+    /// messages to the lowest free guest file descriptors.
+    ///
+    /// The host file descriptors are only received into a host-private copy of the data section.
+    /// Host file descriptors, which cannot be mapped, are closed and replaced by `-1` and the
+    /// message is marked as truncated with [`MSG_CTRUNC`].
+    fn recvmsg(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        private(data, |data| {
+            self.execute(call, data, &[0])?;
+            if success(call.ret[0]).is_none() {
+                return Ok(());
+            }
+
+            let offset = call.argv[1];
+            let mut truncated = false;
+            for_each_right(control(data, offset)?, |fd| {
+                Ok(self.map(0, fd as _).map_or_else(
+                    |_| {
+                        truncated = true;
+                        -1
+                    },
+                    |fd| fd as _,
+                ))
+            })?;
+            if truncated {
+                unsafe { (*deref_aligned::<msghdr>(data, offset, 1)?).msg_flags |= MSG_CTRUNC };
+            }
+            Ok(())
+        })?
+    }
+
+    /// Executes `sendmsg` with the guest file descriptors passed in `SCM_RIGHTS` control
+    /// messages translated in a host-private copy of the data section.
+    ///
+    /// Like the `SCM_MAX_FD` limit of Linux, `N` limits the count of file descriptors passed.
+    fn sendmsg(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        let offset = call.argv[1];
+        private(data, |data| {
+            let mut guest = [0; N];
+            let mut count = 0;
+            let translated = for_each_right(control(data, offset)?, |fd| {
+                let slot = guest.get_mut(count).ok_or(EINVAL)?;
+                let host = self.translate(fd as _)?;
+                *slot = fd;
+                count += 1;
+                Ok(host as _)
+            });
+            let ret = translated.and_then(|_| self.execute(call, data, &[0]));
+
+            // The host file descriptors are replaced before the copy is passed back to the guest.
+            let mut guest = guest[..count].iter();
+            let _ = for_each_right(control(data, offset)?, |fd| {
+                Ok(guest.next().copied().unwrap_or(fd))
+            });
+            ret
+        })?
+    }
+
+    /// Executes host-private `call` with its file descriptors translated.
+    fn translated(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        #[allow(non_upper_case_globals)]
+        match call.num as c_long {
+            SYS_close => return self.close(call, data),
+            SYS_dup2 => return self.dup(call, F_DUPFD),
+            SYS_dup3 => {
+                let flags = call.argv[2] as c_int;
+                if flags & !O_CLOEXEC != 0 || call.argv[0] == call.argv[1] {
+                    return Err(EINVAL);
+                }
+                let cmd = if flags != 0 { F_DUPFD_CLOEXEC } else { F_DUPFD };
+                return self.dup(call, cmd);
+            }
+            SYS_fcntl => return self.fcntl(call, data),
+            SYS_poll => return self.poll(call, data),
+            SYS_recvmsg => return self.recvmsg(call, data),
+            SYS_sendmsg => return self.sendmsg(call, data),
+            _ => {}
+        }
+
+        let (_, fd_args, new_fd) = SYSCALLS
+            .iter()
+            .find(|(num, ..)| *num as usize == call.num)
+            .ok_or(ENOSYS)?;
+        self.execute(call, data, fd_args)?;
+        match success(call.ret[0]) {
+            Some(host_fd) if *new_fd => call.ret[0] = self.map(0, host_fd)?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<E: Executor, const N: usize> Executor for FdTable<E, N> {
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        // The call is copied, so that the guest cannot modify its arguments during translation.
+        let mut copy = *call;
+        let ret = self.translated(&mut copy, data);
+        call.ret = copy.ret;
+        ret
+    }
+
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        // Syscalls creating or releasing file descriptors or passing them in memory need their
+        // results to be translated, so they are only executed by the table.
+        #[allow(non_upper_case_globals)]
+        match call.num as c_long {
+            SYS_close | SYS_dup2 | SYS_dup3 | SYS_fcntl | SYS_poll | SYS_recvmsg | SYS_sendmsg => {
+                return Ok(false)
+            }
+            _ => {}
+        }
+        match SYSCALLS.iter().find(|(num, ..)| *num as usize == call.num) {
+            Some((_, fd_args, false)) => {
+                self.translate_args(call, fd_args)?;
+                self.executor.offload(call, data)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        self.executor.gdbcall(call, data)
+    }
+
+    #[inline]
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        self.executor.enarxcall(call, data)
+    }
+
+    #[inline]
+    fn hello(&mut self, call: &mut Hello, data: &mut [u8]) -> Result<()> {
+        self.executor.hello(call, data)
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        self.executor.reply()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{execute_with, Native};
+    use crate::item::Item;
+    use crate::libc::{cmsghdr, iovec, SYS_getpid, CMSG_LEN, CMSG_SPACE, POLLIN};
+
+    use core::mem::{offset_of, size_of};
+    use std::os::unix::io::IntoRawFd;
+    use std::os::unix::net::UnixStream;
+
+    type Table = FdTable<Native, 8>;
+
+    fn syscall(table: &mut Table, num: c_long, argv: [usize; 6], data: &mut [u8]) -> [usize; 2] {
+        let mut call = Syscall {
+            num: num as _,
+            argv,
+            ret: [-ENOSYS as _, 0],
+        };
+        execute_with(table, [Item::Syscall(&mut call, data)]).unwrap();
+        assert_eq!(call.argv, argv);
+        call.ret
+    }
+
+    #[test]
+    fn grant() {
+        let mut table = Table::new(Native);
+        assert_eq!(table.grant(42), Ok(0));
+        assert_eq!(table.grant_at(2, 43), Ok(None));
+        assert_eq!(table.grant(44), Ok(1));
+        assert_eq!(table.grant_at(2, 45), Ok(Some(43)));
+        assert_eq!(table.grant_at(8, 46), Err(EBADF));
+        assert_eq!(table.get(2), Some(45));
+        assert_eq!(table.revoke(0), Some(42));
+        assert_eq!(table.get(0), None);
+        assert_eq!(table.get(-1), None);
+        assert_eq!(table.grant(47), Ok(0));
+        for fd in 3..8 {
+            assert_eq!(table.grant(fd), Ok(fd));
+        }
+        assert_eq!(table.grant(48), Err(EMFILE));
+    }
+
+    #[test]
+    fn private() {
+        /// Executor checking that it is passed host-private copies of calls and data sections.
+        struct Private(*const Syscall, *const u8);
+
+        impl Executor for Private {
+            fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+                assert_ne!(call as *const _, self.0);
+                assert_ne!(data.as_ptr(), self.1);
+                assert_eq!(call.argv[..2], [0, 1]);
+                let fds = unsafe { &*deref_aligned::<pollfd>(data, 0, 1).unwrap() };
+                assert_eq!(fds.fd, 42);
+                call.ret = [1, 0];
+                Ok(())
+            }
+        }
+
+        let mut fds = [pollfd {
+            fd: 0,
+            events: POLLIN,
+            revents: 0,
+        }];
+        let data = unsafe {
+            core::slice::from_raw_parts_mut(fds.as_mut_ptr().cast::<u8>(), size_of::<pollfd>())
+        };
+        let mut call = Syscall {
+            num: SYS_poll as _,
+            argv: [0, 1, 0, 0, 0, 0],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut table = FdTable::<_, 8>::new(Private(&call, data.as_ptr()));
+        assert_eq!(table.grant(42), Ok(0));
+        assert_eq!(table.syscall(&mut call, data), Ok(()));
+        assert_eq!(call.ret, [1, 0]);
+        assert_eq!(fds[0].fd, 0);
+    }
+
+    #[test]
+    fn translate() {
+        let mut table = Table::new(Native);
+        let ebadf = [-EBADF as _, 0];
+        assert_eq!(
+            syscall(&mut table, SYS_getpid, [0; 6], &mut []),
+            [-ENOSYS as _, 0]
+        );
+        assert_eq!(syscall(&mut table, SYS_close, [0; 6], &mut []), ebadf);
+        assert_eq!(
+            syscall(&mut table, SYS_dup2, [0, 1, 0, 0, 0, 0], &mut []),
+            ebadf
+        );
+        assert_eq!(
+            syscall(
+                &mut table,
+                SYS_renameat2,
+                [AT_FDCWD as _, 0, 7, 0, 0, 0],
+                &mut []
+            ),
+            ebadf
+        );
+
+        let [fd, _] = syscall(&mut table, SYS_eventfd2, [1, 0, 0, 0, 0, 0], &mut []);
+        if cfg!(miri) {
+            return;
+        }
+        assert_eq!(fd, 0);
+        let host_fd = table.get(0).unwrap();
+        assert!(host_fd > 2);
+
+        assert_eq!(
+            syscall(&mut table, SYS_dup2, [0, 5, 0, 0, 0, 0], &mut []),
+            [5, 0]
+        );
+        assert_ne!(table.get(5), Some(host_fd));
+        assert_eq!(
+            syscall(&mut table, SYS_dup3, [0, 5, 1, 0, 0, 0], &mut []),
+            [-EINVAL as _, 0]
+        );
+        assert_eq!(
+            syscall(
+                &mut table,
+                SYS_fcntl,
+                [5, F_DUPFD as _, 3, 0, 0, 0],
+                &mut []
+            ),
+            [3, 0]
+        );
+        assert_eq!(
+            syscall(&mut table, SYS_close, [3, 0, 0, 0, 0, 0], &mut []),
+            [0, 0]
+        );
+        assert_eq!(table.get(3), None);
+
+        let mut fds = [
+            pollfd {
+                fd: 5,
+                events: POLLIN,
+                revents: 0,
+            },
+            pollfd {
+                fd: 7,
+                events: POLLIN,
+                revents: 0,
+            },
+            pollfd {
+                fd: -1,
+                events: POLLIN,
+                revents: 0,
+            },
+        ];
+        let data = unsafe {
+            core::slice::from_raw_parts_mut(fds.as_mut_ptr() as *mut u8, size_of::<[pollfd; 3]>())
+        };
+        assert_eq!(
+            syscall(&mut table, SYS_poll, [0, 3, 1000, 0, 0, 0], data),
+            [2, 0]
+        );
+        assert_eq!(
+            fds.map(|fd| (fd.fd, fd.revents)),
+            [(5, POLLIN), (7, POLLNVAL), (-1, 0)]
+        );
+
+        for fd in [0, 5] {
+            assert_eq!(
+                syscall(&mut table, SYS_close, [fd, 0, 0, 0, 0, 0], &mut []),
+                [0, 0]
+            );
+        }
+        assert_eq!(syscall(&mut table, SYS_close, [0; 6], &mut []), ebadf);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn rights() {
+        #[repr(C)]
+        struct Msg {
+            hdr: msghdr,
+            iov: iovec,
+            buf: [u8; 1],
+            control: [u64; CMSG_SPACE(size_of::<c_int>()) / size_of::<u64>()],
+        }
+
+        fn rights(fd: c_int) -> Msg {
+            let mut msg = Msg {
+                hdr: msghdr {
+                    msg_name: NULL as _,
+                    msg_namelen: 0,
+                    msg_iov: offset_of!(Msg, iov) as _,
+                    msg_iovlen: 1,
+                    msg_control: offset_of!(Msg, control) as _,
+                    msg_controllen: CMSG_LEN(size_of::<c_int>()),
+                    msg_flags: 0,
+                },
+                iov: iovec {
+                    iov_base: offset_of!(Msg, buf) as _,
+                    iov_len: 1,
+                },
+                buf: [0],
+                control: [0; 3],
+            };
+            let control = msg.control.as_mut_ptr() as *mut u8;
+            unsafe {
+                control.cast::<cmsghdr>().write(cmsghdr {
+                    cmsg_len: CMSG_LEN(size_of::<c_int>()),
+                    cmsg_level: SOL_SOCKET,
+                    cmsg_type: SCM_RIGHTS,
+                });
+                control.add(CMSG_LEN(0)).cast::<c_int>().write_unaligned(fd);
+            }
+            msg
+        }
+
+        fn data(msg: &mut Msg) -> &mut [u8] {
+            unsafe { core::slice::from_raw_parts_mut(msg as *mut _ as _, size_of::<Msg>()) }
+        }
+
+        fn right(msg: &Msg) -> c_int {
+            msg.control[2] as c_int
+        }
+
+        let mut table = Table::new(Native);
+        let (a, b) = UnixStream::pair().unwrap();
+        assert_eq!(table.grant(a.into_raw_fd()), Ok(0));
+        assert_eq!(table.grant(b.into_raw_fd()), Ok(1));
+
+        let mut msg = rights(7);
+        assert_eq!(
+            syscall(&mut table, SYS_sendmsg, [0; 6], data(&mut msg)),
+            [-EBADF as _, 0]
+        );
+        assert_eq!(right(&msg), 7);
+
+        let mut msg = rights(1);
+        assert_eq!(
+            syscall(&mut table, SYS_sendmsg, [0; 6], data(&mut msg)),
+            [1, 0]
+        );
+        assert_eq!(right(&msg), 1);
+
+        let mut msg = rights(-1);
+        msg.hdr.msg_controllen = size_of_val(&msg.control);
+        assert_eq!(
+            syscall(&mut table, SYS_recvmsg, [1, 0, 0, 0, 0, 0], data(&mut msg)),
+            [1, 0]
+        );
+        assert_eq!(msg.hdr.msg_flags, 0);
+        assert_eq!(right(&msg), 2);
+        assert!(table.get(2).is_some());
+        assert_ne!(table.get(2), table.get(1));
+
+        for fd in 0..3 {
+            assert_eq!(
+                syscall(&mut table, SYS_close, [fd, 0, 0, 0, 0, 0], &mut []),
+                [0, 0]
+            );
+        }
+    }
+}
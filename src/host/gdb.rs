@@ -0,0 +1,345 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::syscall::{raw, Execute, Syscall as RawSyscall};
+use super::{deref_slice, Executor, Sockaddr};
+use crate::item::gdbcall::Number;
+use crate::item::hello::Reply;
+use crate::item::{Enarxcall, Gdbcall, Hello, Syscall};
+use crate::libc::{
+    pollfd, sockaddr_storage, SYS_accept4, SYS_bind, SYS_close, SYS_listen, SYS_poll, SYS_recvfrom,
+    SYS_sendto, SYS_setsockopt, SYS_socket, AF_INET, AF_INET6, AF_UNIX, EAGAIN, ECONNRESET, EINTR,
+    EINVAL, ENOTCONN, EPIPE, IPPROTO_TCP, MSG_DONTWAIT, MSG_NOSIGNAL, MSG_PEEK, POLLIN,
+    SOCK_CLOEXEC, SOCK_STREAM, SOL_SOCKET, SO_REUSEADDR, TCP_NODELAY,
+};
+use crate::{Result, NULL};
+
+use core::ffi::{c_int, c_long};
+use core::mem::{size_of, ManuallyDrop};
+use core::ptr;
+
+/// Executes syscall `num` with `argv` directly, retrying on [`EINTR`].
+fn retry<const ARGS: usize>(num: c_long, argv: [usize; ARGS]) -> Result<usize>
+where
+    for<'a> RawSyscall<'a, ARGS, 1>: Execute,
+{
+    loop {
+        match unsafe { raw(num, argv) } {
+            Err(EINTR) => continue,
+            res => return res,
+        }
+    }
+}
+
+/// Sets socket option `name` at `level` of socket `fd` to `1`.
+fn enable(fd: c_int, level: c_int, name: c_int) -> Result<()> {
+    let one: c_int = 1;
+    let argv = [
+        fd as _,
+        level as _,
+        name as _,
+        &one as *const _ as _,
+        size_of::<c_int>(),
+    ];
+    retry(SYS_setsockopt, argv).map(|_| ())
+}
+
+/// [`Executor`] serving [`Gdbcall`] items over a connection accepted on a TCP or Unix socket
+/// and passing all other items to another executor.
+///
+/// A single GDB client is served at a time. The connection is accepted on
+/// [`OnSessionStart`](Number::OnSessionStart) or any other GDB call requiring it and closed
+/// once the client disconnects, after which the next GDB call waits for a new client.
+/// [`Peek`](Number::Peek) never blocks and returns no byte if no client is connected or no
+/// data is available.
+#[derive(Debug)]
+pub struct GdbBridge<E> {
+    executor: E,
+    listener: c_int,
+    conn: Option<c_int>,
+
+    /// Whether the listener is a TCP socket.
+    tcp: bool,
+}
+
+impl<E> GdbBridge<E> {
+    /// Creates a new [`GdbBridge`] listening on `addr`, which passes all items other than
+    /// [`Gdbcall`] items to `executor`.
+    ///
+    /// Returns [`EINVAL`] if `addr` cannot be encoded.
+    pub fn bind(addr: &Sockaddr<'_>, executor: E) -> Result<Self> {
+        let mut buf = [0u8; size_of::<sockaddr_storage>()];
+        let addrlen = addr.encode(&mut buf).ok_or(EINVAL)?;
+        let domain = match addr {
+            Sockaddr::Inet { .. } => AF_INET,
+            Sockaddr::Inet6 { .. } => AF_INET6,
+            Sockaddr::Unix(..) => AF_UNIX,
+        };
+        let listener = retry(
+            SYS_socket,
+            [domain as _, (SOCK_STREAM | SOCK_CLOEXEC) as _, 0],
+        )?;
+
+        // The listener is closed on drop of `bridge` on error.
+        let bridge = Self {
+            executor,
+            listener: listener as _,
+            conn: None,
+            tcp: domain != AF_UNIX,
+        };
+        if bridge.tcp {
+            enable(bridge.listener, SOL_SOCKET, SO_REUSEADDR)?;
+        }
+        retry(SYS_bind, [listener, buf.as_ptr() as _, addrlen])?;
+        retry(SYS_listen, [listener, 1])?;
+        Ok(bridge)
+    }
+
+    /// Returns the file descriptor of the listening socket.
+    #[inline]
+    pub fn listener(&self) -> c_int {
+        self.listener
+    }
+
+    /// Returns `true` if a GDB client is connected.
+    #[inline]
+    pub fn is_connected(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    /// Closes the connection to the GDB client, if any.
+    #[inline]
+    pub fn disconnect(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            let _ = unsafe { raw(SYS_close, [conn as _]) };
+        }
+    }
+
+    /// Closes the sockets and returns the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        let mut bridge = ManuallyDrop::new(self);
+        bridge.close();
+        unsafe { ptr::read(&bridge.executor) }
+    }
+
+    fn close(&mut self) {
+        self.disconnect();
+        let _ = unsafe { raw(SYS_close, [self.listener as _]) };
+    }
+
+    /// Returns the connection to the GDB client. If no client is connected, waits for a client
+    /// to connect if `wait` is `true` and returns `None` otherwise.
+    fn connection(&mut self, wait: bool) -> Result<Option<c_int>> {
+        if let Some(conn) = self.conn {
+            return Ok(Some(conn));
+        }
+        if !wait {
+            let mut fds = pollfd {
+                fd: self.listener,
+                events: POLLIN,
+                revents: 0,
+            };
+            if retry(SYS_poll, [&mut fds as *mut _ as _, 1, 0])? == 0 {
+                return Ok(None);
+            }
+        }
+        let conn = retry(SYS_accept4, [self.listener as _, 0, 0, SOCK_CLOEXEC as _])? as c_int;
+        self.conn = Some(conn);
+        if self.tcp {
+            // GDB packets are small and latency sensitive.
+            enable(conn, IPPROTO_TCP, TCP_NODELAY)?;
+        }
+        Ok(Some(conn))
+    }
+
+    /// Sends all of `buf` to the GDB client and returns the count of bytes sent.
+    fn send(&mut self, buf: &[u8]) -> Result<usize> {
+        let conn = self.connection(true)?.ok_or(ENOTCONN)?;
+        let mut sent = 0;
+        while sent < buf.len() {
+            let tail = &buf[sent..];
+            let argv = [
+                conn as _,
+                tail.as_ptr() as _,
+                tail.len(),
+                MSG_NOSIGNAL as _,
+                0,
+                0,
+            ];
+            sent += retry(SYS_sendto, argv).inspect_err(|e| self.lost(*e))?;
+        }
+        Ok(sent)
+    }
+
+    /// Receives a byte from the GDB client passing `flags` to `recvfrom`.
+    ///
+    /// Returns `None` and closes the connection if the client disconnected.
+    fn recv(&mut self, conn: c_int, flags: c_int) -> Result<Option<u8>> {
+        let mut byte = 0u8;
+        let argv = [conn as _, &mut byte as *mut _ as _, 1, flags as _, 0, 0];
+        match retry(SYS_recvfrom, argv).inspect_err(|e| self.lost(*e))? {
+            0 => {
+                self.disconnect();
+                Ok(None)
+            }
+            _ => Ok(Some(byte)),
+        }
+    }
+
+    /// Closes the connection if `errno` indicates the client disconnected.
+    fn lost(&mut self, errno: c_int) {
+        if matches!(errno, ECONNRESET | EPIPE) {
+            self.disconnect()
+        }
+    }
+}
+
+impl<E> Drop for GdbBridge<E> {
+    fn drop(&mut self) {
+        self.close()
+    }
+}
+
+impl<E: Executor> Executor for GdbBridge<E> {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        self.executor.syscall(call, data)
+    }
+
+    #[inline]
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        self.executor.offload(call, data)
+    }
+
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        call.ret = match call.num {
+            Number::Write => self.send(&[call.argv[0] as u8]).map(|_| 0)?,
+            Number::WriteAll => {
+                let buf = unsafe { &*deref_slice::<u8>(data, call.argv[0], call.argv[1])? };
+                self.send(buf)?
+            }
+            // Data is sent without buffering.
+            Number::Flush => 0,
+            Number::OnSessionStart => self.connection(true).map(|_| 0)?,
+            Number::Read => {
+                let conn = self.connection(true)?.ok_or(ENOTCONN)?;
+                self.recv(conn, 0)?.ok_or(ENOTCONN)? as _
+            }
+            Number::Peek => match self.connection(false)? {
+                Some(conn) => match self.recv(conn, MSG_PEEK | MSG_DONTWAIT) {
+                    Ok(Some(byte)) => byte as _,
+                    Ok(None) | Err(EAGAIN) => NULL,
+                    Err(e) => return Err(e),
+                },
+                None => NULL,
+            },
+        };
+        Ok(())
+    }
+
+    #[inline]
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        self.executor.enarxcall(call, data)
+    }
+
+    #[inline]
+    fn hello(&mut self, call: &mut Hello, data: &mut [u8]) -> Result<()> {
+        self.executor.hello(call, data)
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        self.executor.reply()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{execute_with, Native};
+    use crate::item::Item;
+    use crate::libc::{EFAULT, ENOSYS};
+
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    fn gdbcall(
+        bridge: &mut GdbBridge<Native>,
+        num: Number,
+        argv: [usize; 4],
+        data: &mut [u8],
+    ) -> usize {
+        let mut call = Gdbcall {
+            num,
+            argv,
+            ret: -ENOSYS as _,
+        };
+        execute_with(bridge, [Item::Gdbcall(&mut call, data)]).unwrap();
+        call.ret
+    }
+
+    #[test]
+    fn bridge() {
+        let tcp = Sockaddr::Inet {
+            addr: [127, 0, 0, 1],
+            port: 0,
+        };
+        assert!(GdbBridge::bind(&tcp, Native).is_ok());
+
+        let path = std::env::temp_dir().join(format!("sallyport-gdb-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let unix = Sockaddr::Unix(path.as_os_str().as_encoded_bytes());
+        let mut bridge = GdbBridge::bind(&unix, Native).unwrap();
+        assert_eq!(gdbcall(&mut bridge, Number::Peek, [0; 4], &mut []), NULL);
+        assert!(!bridge.is_connected());
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            gdbcall(&mut bridge, Number::OnSessionStart, [0; 4], &mut []),
+            0
+        );
+        assert!(bridge.is_connected());
+        assert_eq!(gdbcall(&mut bridge, Number::Peek, [0; 4], &mut []), NULL);
+
+        client.write_all(b"$g").unwrap();
+        assert_eq!(
+            gdbcall(&mut bridge, Number::Read, [0; 4], &mut []),
+            b'$' as _
+        );
+        assert_eq!(
+            gdbcall(&mut bridge, Number::Peek, [0; 4], &mut []),
+            b'g' as _
+        );
+        assert_eq!(
+            gdbcall(&mut bridge, Number::Read, [0; 4], &mut []),
+            b'g' as _
+        );
+
+        assert_eq!(
+            gdbcall(&mut bridge, Number::Write, [b'+' as _, 0, 0, 0], &mut []),
+            0
+        );
+        let mut data = *b"..OK";
+        assert_eq!(
+            gdbcall(&mut bridge, Number::WriteAll, [2, 2, 0, 0], &mut data),
+            2
+        );
+        assert_eq!(
+            gdbcall(&mut bridge, Number::WriteAll, [2, 3, 0, 0], &mut data),
+            -EFAULT as _
+        );
+        assert_eq!(gdbcall(&mut bridge, Number::Flush, [0; 4], &mut []), 0);
+        let mut buf = [0; 3];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"+OK");
+
+        drop(client);
+        assert_eq!(
+            gdbcall(&mut bridge, Number::Read, [0; 4], &mut []),
+            -ENOTCONN as _
+        );
+        assert!(!bridge.is_connected());
+        assert_eq!(bridge.into_inner(), Native);
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{deref_aligned, deref_slice};
+use crate::item::hello::Reply;
+use crate::item::{self, Kind};
+use crate::libc::{EINVAL, EPROTO};
+use crate::util::version::satisfies;
+use crate::{Result, VERSION};
+
+/// Returns the [`Reply`] describing the capabilities of the host.
+pub(super) fn reply() -> Reply {
+    let mut reply = Reply::new();
+    reply.set_version(VERSION);
+    reply.add_kind(Kind::End);
+    reply.add_kind(Kind::Hello);
+    reply.add_kind(Kind::Notification);
+    reply.add_kind(Kind::Link);
+
+    #[cfg(not(miri))]
+    {
+        reply.add_kind(Kind::Syscall);
+        reply.add_kind(Kind::Enarxcall);
+        super::syscall::SYSCALLS.iter().for_each(|num| {
+            reply.add_syscall(*num);
+        });
+        super::enarxcall::NUMBERS
+            .iter()
+            .for_each(|num| reply.add_enarxcall(*num));
+    }
+    reply
+}
+
+pub(super) unsafe fn execute(call: &mut item::Hello, data: &mut [u8], reply: Reply) -> Result<()> {
+    deref_slice::<u8>(data, call.version, call.version_len)?;
+    let requires = deref_slice::<u8>(data, call.requires, call.requires_len)?;
+    let requires = core::str::from_utf8(&*requires).map_err(|_| EINVAL)?;
+    let compatible = satisfies(VERSION, requires);
+
+    let reply_ptr = deref_aligned::<Reply>(data, call.reply, 1)?;
+    reply_ptr.write(reply);
+
+    call.ret = if compatible { 0 } else { -EPROTO as _ };
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{Execute, Executor};
+use crate::item::link::BACK_MAX;
+use crate::item::{Item, Link};
+use crate::libc::{ECANCELED, EINVAL};
+use crate::Result;
+
+/// Maximum count of links applied to a single item.
+const LINKS_MAX: usize = 6;
+
+/// Resolves [`Link`] items during execution of a block.
+#[derive(Debug)]
+pub(super) struct Links {
+    /// First return values of the most recently executed items.
+    rets: [usize; BACK_MAX],
+
+    /// Count of executed items, which have a return value.
+    count: usize,
+
+    /// Links to apply to the next item.
+    pending: [(usize, Result<usize>); LINKS_MAX],
+    pending_len: usize,
+}
+
+impl Links {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            rets: [0; BACK_MAX],
+            count: 0,
+            pending: [(0, Ok(0)); LINKS_MAX],
+            pending_len: 0,
+        }
+    }
+
+    /// Resolves `link` and records it to be applied to the next item.
+    fn push(&mut self, link: &Link) {
+        let ret = match link.back {
+            back if back == 0 || back > BACK_MAX || back > self.count => Err(EINVAL),
+            back => match self.rets[(self.count - back) % BACK_MAX] as isize {
+                // The referenced item failed.
+                -4095..=-1 => Err(ECANCELED),
+                ret => Ok(ret as _),
+            },
+        };
+        match self.pending.get_mut(self.pending_len) {
+            Some(pending) => *pending = (link.arg, ret),
+            None => self.pending[LINKS_MAX - 1] = (0, Err(EINVAL)),
+        }
+        self.pending_len = (self.pending_len + 1).min(LINKS_MAX);
+    }
+
+    /// Applies the pending links to the arguments of `item`.
+    fn apply(&mut self, item: &mut Item<'_>) -> Result<()> {
+        let pending = &self.pending[..self.pending_len];
+        self.pending_len = 0;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let argv = item.argv_mut().ok_or(EINVAL)?;
+        for (arg, ret) in pending {
+            *argv.get_mut(*arg).ok_or(EINVAL)? = (*ret)?;
+        }
+        Ok(())
+    }
+
+    /// Executes `item` using `executor` after applying the pending links to it.
+    ///
+    /// If the links cannot be applied, `item` is not executed and the negated error number
+    /// is written into its first return value instead.
+    pub fn execute(
+        &mut self,
+        executor: &mut (impl Executor + ?Sized),
+        item: &mut Item<'_>,
+    ) -> Result<()> {
+        if let Item::Link(link, _) = item {
+            self.push(link);
+            return Ok(());
+        }
+        let res = match self.apply(item) {
+            Ok(()) => item.execute(executor),
+            Err(e) => {
+                if let Some(ret) = item.ret0_mut() {
+                    *ret = -e as _;
+                }
+                Err(e)
+            }
+        };
+        if let Some(ret) = item.ret0() {
+            self.rets[self.count % BACK_MAX] = ret;
+            self.count += 1;
+        }
+        res
+    }
+}
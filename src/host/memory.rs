@@ -0,0 +1,339 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::Executor;
+use crate::item::enarxcall::Number;
+use crate::item::hello::Reply;
+use crate::item::{Enarxcall, Gdbcall, Kind, Syscall};
+use crate::Result;
+
+#[cfg(not(miri))]
+use super::syscall::raw;
+#[cfg(not(miri))]
+use crate::libc::{
+    SYS_mmap, SYS_munmap, EINVAL, ENOMEM, ENOSPC, EOVERFLOW, MAP_ANONYMOUS, MAP_PRIVATE, PROT_READ,
+    PROT_WRITE,
+};
+
+/// Smallest page size exponent accepted by [`AnonMemory`].
+#[cfg(not(miri))]
+const PAGE_SHIFT: usize = 12;
+
+/// Backend providing memory requested by the guest via [`MemInfo`](Number::MemInfo) and
+/// [`BalloonMemory`](Number::BalloonMemory) Enarx calls.
+pub trait Memory {
+    /// Returns the count of memory slots available for ballooning.
+    fn mem_info(&mut self) -> Result<usize>;
+
+    /// Allocates `pages` pages of `1 << size_exponent` bytes backing the guest physical
+    /// address `addr` and returns the host virtual address of the allocated region.
+    fn balloon_memory(&mut self, size_exponent: usize, pages: usize, addr: usize) -> Result<usize>;
+}
+
+impl<M: Memory + ?Sized> Memory for &mut M {
+    #[inline]
+    fn mem_info(&mut self) -> Result<usize> {
+        (**self).mem_info()
+    }
+
+    #[inline]
+    fn balloon_memory(&mut self, size_exponent: usize, pages: usize, addr: usize) -> Result<usize> {
+        (**self).balloon_memory(size_exponent, pages, addr)
+    }
+}
+
+/// Memory slot allocated by [`AnonMemory`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slot {
+    /// Guest physical address of the slot.
+    pub guest_addr: usize,
+
+    /// Host virtual address of the slot.
+    pub host_addr: usize,
+
+    /// Size of the slot in bytes.
+    pub size: usize,
+}
+
+/// [`Memory`] backed by anonymous private mappings of the host.
+///
+/// At most `N` slots are allocated and the total size of all slots never exceeds the ceiling
+/// passed to [`new`](AnonMemory::new). Requests exceeding the ceiling fail with [`ENOMEM`],
+/// requests exceeding the slot count with [`ENOSPC`] and requests overlapping an existing slot
+/// or not aligned to the page size with [`EINVAL`]. All slots are unmapped on drop.
+#[cfg(not(miri))]
+#[derive(Debug)]
+pub struct AnonMemory<const N: usize> {
+    slots: [Option<Slot>; N],
+    limit: usize,
+    used: usize,
+}
+
+#[cfg(not(miri))]
+impl<const N: usize> AnonMemory<N> {
+    /// Creates a new [`AnonMemory`] allocating at most `limit` bytes.
+    #[inline]
+    pub fn new(limit: usize) -> Self {
+        Self {
+            slots: [None; N],
+            limit,
+            used: 0,
+        }
+    }
+
+    /// Returns the total size of all allocated slots in bytes.
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Returns an iterator over the allocated slots.
+    #[inline]
+    pub fn slots(&self) -> impl Iterator<Item = &Slot> {
+        self.slots.iter().flatten()
+    }
+}
+
+#[cfg(not(miri))]
+impl<const N: usize> Memory for AnonMemory<N> {
+    fn mem_info(&mut self) -> Result<usize> {
+        Ok(self.slots.iter().filter(|slot| slot.is_none()).count())
+    }
+
+    fn balloon_memory(&mut self, size_exponent: usize, pages: usize, addr: usize) -> Result<usize> {
+        if !(PAGE_SHIFT..usize::BITS as _).contains(&size_exponent) || pages == 0 {
+            return Err(EINVAL);
+        }
+        let page_size = 1 << size_exponent;
+        if addr & (page_size - 1) != 0 {
+            return Err(EINVAL);
+        }
+        let size = pages.checked_mul(page_size).ok_or(EOVERFLOW)?;
+        let end = addr.checked_add(size).ok_or(EOVERFLOW)?;
+        if self
+            .slots()
+            .any(|slot| addr < slot.guest_addr + slot.size && slot.guest_addr < end)
+        {
+            return Err(EINVAL);
+        }
+        if size > self.limit - self.used {
+            return Err(ENOMEM);
+        }
+        let free = self
+            .slots
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(ENOSPC)?;
+
+        let argv = [
+            0,
+            size,
+            (PROT_READ | PROT_WRITE) as _,
+            (MAP_PRIVATE | MAP_ANONYMOUS) as _,
+            -1isize as _,
+            0,
+        ];
+        let host_addr = unsafe { raw(SYS_mmap, argv) }?;
+        *free = Some(Slot {
+            guest_addr: addr,
+            host_addr,
+            size,
+        });
+        self.used += size;
+        Ok(host_addr)
+    }
+}
+
+#[cfg(not(miri))]
+impl<const N: usize> Drop for AnonMemory<N> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter().flatten() {
+            let _ = unsafe { raw(SYS_munmap, [slot.host_addr, slot.size]) };
+        }
+    }
+}
+
+/// [`Executor`] executing [`MemInfo`](Number::MemInfo) and
+/// [`BalloonMemory`](Number::BalloonMemory) Enarx calls using a [`Memory`] backend and passing
+/// all other items to another executor.
+///
+/// [`Hello`](crate::item::Hello) items are answered with the [`reply`](Executor::reply) of
+/// the other executor extended by the ballooning Enarx calls.
+#[derive(Debug)]
+pub struct Balloon<M, E> {
+    memory: M,
+    executor: E,
+}
+
+impl<M: Memory, E: Executor> Balloon<M, E> {
+    /// Creates a new [`Balloon`] allocating memory from `memory`, which passes all other items
+    /// to `executor`.
+    #[inline]
+    pub fn new(memory: M, executor: E) -> Self {
+        Self { memory, executor }
+    }
+
+    /// Returns the memory backend.
+    #[inline]
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    /// Returns the memory backend and the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> (M, E) {
+        (self.memory, self.executor)
+    }
+}
+
+impl<M: Memory, E: Executor> Executor for Balloon<M, E> {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        self.executor.syscall(call, data)
+    }
+
+    #[inline]
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        self.executor.offload(call, data)
+    }
+
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        self.executor.gdbcall(call, data)
+    }
+
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        match call.num {
+            Number::MemInfo => call.ret = self.memory.mem_info()?,
+            Number::BalloonMemory => {
+                let [size_exponent, pages, addr, _] = call.argv;
+                call.ret = self.memory.balloon_memory(size_exponent, pages, addr)?;
+            }
+            _ => return self.executor.enarxcall(call, data),
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        let mut reply = self.executor.reply();
+        reply.add_kind(Kind::Enarxcall);
+        reply.add_enarxcall(Number::MemInfo);
+        reply.add_enarxcall(Number::BalloonMemory);
+        reply
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{execute_with, Native};
+    use crate::item::Item;
+    use crate::libc::{ENOSYS, EPERM};
+
+    fn enarxcall(executor: &mut impl Executor, num: Number, argv: [usize; 4]) -> usize {
+        let mut call = Enarxcall {
+            num,
+            argv,
+            ret: -ENOSYS as _,
+        };
+        execute_with(executor, [Item::Enarxcall(&mut call, &mut [])]).unwrap();
+        call.ret
+    }
+
+    /// Provides a single slot at a fixed address.
+    struct Fixed(bool);
+
+    impl Memory for Fixed {
+        fn mem_info(&mut self) -> Result<usize> {
+            Ok(!self.0 as _)
+        }
+
+        fn balloon_memory(&mut self, _: usize, _: usize, _: usize) -> Result<usize> {
+            if self.0 {
+                return Err(EPERM);
+            }
+            self.0 = true;
+            Ok(0x1000)
+        }
+    }
+
+    #[test]
+    fn balloon() {
+        let mut balloon = Balloon::new(Fixed(false), Native);
+        let reply = balloon.reply();
+        assert!(reply.supports_enarxcall(Number::MemInfo));
+        assert!(reply.supports_enarxcall(Number::BalloonMemory));
+
+        assert_eq!(enarxcall(&mut balloon, Number::MemInfo, [0; 4]), 1);
+        assert_eq!(
+            enarxcall(&mut balloon, Number::BalloonMemory, [12, 1, 0, 0]),
+            0x1000
+        );
+        assert_eq!(
+            enarxcall(&mut balloon, Number::BalloonMemory, [12, 1, 0, 0]),
+            -EPERM as _
+        );
+        assert_eq!(enarxcall(&mut balloon, Number::MemInfo, [0; 4]), 0);
+        assert_eq!(
+            enarxcall(&mut balloon, Number::GetSgxQuoteSize, [0; 4]),
+            -ENOSYS as _
+        );
+        assert!(balloon.into_inner().0 .0);
+    }
+
+    #[test]
+    #[cfg(not(miri))]
+    fn anon() {
+        const PAGE: usize = 1 << PAGE_SHIFT;
+
+        let mut balloon = Balloon::new(AnonMemory::<2>::new(4 * PAGE), Native);
+        assert_eq!(enarxcall(&mut balloon, Number::MemInfo, [0; 4]), 2);
+        for (argv, errno) in [
+            ([11, 1, 0, 0], EINVAL),
+            ([12, 0, 0, 0], EINVAL),
+            ([13, 1, PAGE, 0], EINVAL),
+            ([12, usize::MAX, 0, 0], EOVERFLOW),
+            ([12, 5, 0, 0], ENOMEM),
+        ] {
+            assert_eq!(
+                enarxcall(&mut balloon, Number::BalloonMemory, argv),
+                -errno as _
+            );
+        }
+
+        let addr = enarxcall(&mut balloon, Number::BalloonMemory, [12, 2, 0x10000, 0]);
+        assert_eq!(addr & (PAGE - 1), 0);
+        let region = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, 2 * PAGE) };
+        assert!(region.iter().all(|b| *b == 0));
+        region.fill(0xff);
+
+        assert_eq!(
+            enarxcall(&mut balloon, Number::BalloonMemory, [12, 1, 0x11000, 0]),
+            -EINVAL as _
+        );
+        assert_eq!(
+            enarxcall(&mut balloon, Number::BalloonMemory, [12, 3, 0x20000, 0]),
+            -ENOMEM as _
+        );
+        assert_ne!(
+            enarxcall(&mut balloon, Number::BalloonMemory, [12, 2, 0x20000, 0]) as isize,
+            -1
+        );
+        assert_eq!(enarxcall(&mut balloon, Number::MemInfo, [0; 4]), 0);
+
+        let (memory, _) = balloon.into_inner();
+        assert_eq!(memory.used(), 4 * PAGE);
+        assert_eq!(
+            memory.slots().next(),
+            Some(&Slot {
+                guest_addr: 0x10000,
+                host_addr: addr,
+                size: 2 * PAGE
+            })
+        );
+
+        let mut memory = AnonMemory::<1>::new(usize::MAX);
+        assert!(memory.balloon_memory(12, 1, 0).is_ok());
+        assert_eq!(memory.balloon_memory(12, 1, PAGE), Err(ENOSPC));
+    }
+}
@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::Executor;
+use crate::item::hello::Reply;
+use crate::item::{enarxcall, gdbcall, Enarxcall, Gdbcall, Hello, Syscall};
+use crate::Result;
+
+use core::ffi::c_int;
+
+/// Count of buckets of the latency histograms of [`CallStats`].
+pub const LATENCY_BUCKETS: usize = 32;
+
+/// Count of error numbers tracked individually by [`Snapshot::errors`].
+pub const TRACKED_ERRNOS: usize = 134;
+
+/// Largest error number returned by the Linux kernel.
+const MAX_ERRNO: usize = 4095;
+
+/// Call an item executed by the host is accounted to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CallKey {
+    /// [`Syscall`] item with the syscall number.
+    Syscall(usize),
+
+    /// [`Gdbcall`] item with the GDB call number.
+    Gdbcall(gdbcall::Number),
+
+    /// [`Enarxcall`] item with the Enarx call number.
+    Enarxcall(enarxcall::Number),
+
+    /// [`Hello`] item.
+    Hello,
+}
+
+/// Statistics of a single [`CallKey`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CallStats {
+    /// Count of executed items.
+    pub calls: u64,
+
+    /// Count of items returning an error.
+    pub errors: u64,
+
+    /// Total size of the data sections of the executed items in bytes.
+    pub bytes: u64,
+
+    /// Total time spent executing the items in nanoseconds.
+    pub time: u64,
+
+    /// Latency histogram, where bucket `i` counts the items executed in less than `2 << i`
+    /// nanoseconds, but at least `1 << i` nanoseconds for `i > 0`. The last bucket also counts
+    /// all slower items.
+    pub latency: [u64; LATENCY_BUCKETS],
+}
+
+impl CallStats {
+    fn record(&mut self, bytes: usize, time: u64, failed: bool) {
+        let bucket = (u64::BITS - (time >> 1).leading_zeros()) as usize;
+        self.calls += 1;
+        self.errors += failed as u64;
+        self.bytes += bytes as u64;
+        self.time = self.time.saturating_add(time);
+        self.latency[bucket.min(LATENCY_BUCKETS - 1)] += 1;
+    }
+}
+
+/// Snapshot of the statistics collected by [`Metrics`] for at most `N` distinct calls.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapshot<const N: usize> {
+    calls: [Option<(CallKey, CallStats)>; N],
+    errnos: [u64; TRACKED_ERRNOS],
+    untracked: u64,
+}
+
+impl<const N: usize> Default for Snapshot<N> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            calls: [None; N],
+            errnos: [0; TRACKED_ERRNOS],
+            untracked: 0,
+        }
+    }
+}
+
+impl<const N: usize> Snapshot<N> {
+    /// Returns an iterator over the calls executed and their statistics in order of first
+    /// execution.
+    #[inline]
+    pub fn calls(&self) -> impl Iterator<Item = &(CallKey, CallStats)> {
+        self.calls.iter().flatten()
+    }
+
+    /// Returns the statistics of `key`, if it was executed.
+    #[inline]
+    pub fn get(&self, key: CallKey) -> Option<&CallStats> {
+        self.calls()
+            .find_map(|(k, stats)| (*k == key).then_some(stats))
+    }
+
+    /// Returns the count of items of all calls returning `errno`.
+    ///
+    /// Error numbers of at least [`TRACKED_ERRNOS`] are not counted individually and always
+    /// yield `0`.
+    #[inline]
+    pub fn errors(&self, errno: c_int) -> u64 {
+        usize::try_from(errno)
+            .ok()
+            .and_then(|errno| self.errnos.get(errno))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the count of items, which were not accounted to a call, because the statistics
+    /// of `N` distinct calls are collected already.
+    #[inline]
+    pub fn untracked(&self) -> u64 {
+        self.untracked
+    }
+
+    fn record(&mut self, key: CallKey, bytes: usize, time: u64, errno: Option<usize>) {
+        if let Some(errno) = errno {
+            if let Some(count) = self.errnos.get_mut(errno) {
+                *count += 1;
+            }
+        }
+
+        let slot = match self.calls.iter().position(|call| match call {
+            Some((k, _)) => *k == key,
+            None => true,
+        }) {
+            Some(i) => &mut self.calls[i],
+            None => {
+                self.untracked += 1;
+                return;
+            }
+        };
+        slot.get_or_insert((key, CallStats::default()))
+            .1
+            .record(bytes, time, errno.is_some());
+    }
+}
+
+/// Returns the error number of the return value `ret` of an item, if it indicates an error.
+#[inline]
+fn errno(ret: usize) -> Option<usize> {
+    let errno = ret.wrapping_neg();
+    (1..=MAX_ERRNO).contains(&errno).then_some(errno)
+}
+
+/// [`Executor`] collecting statistics of the items executed by another executor for at most
+/// `N` distinct calls.
+///
+/// Time is measured using `clock`, which returns a monotonic timestamp in nanoseconds.
+/// Items failing with an error are accounted with the error number written into their
+/// return value.
+///
+/// Syscalls are never [offloaded](Executor::offload) to the kernel, so that all of them are
+/// measured.
+#[derive(Debug)]
+pub struct Metrics<E, C, const N: usize> {
+    executor: E,
+    clock: C,
+    snapshot: Snapshot<N>,
+}
+
+impl<E: Executor, C: FnMut() -> u64, const N: usize> Metrics<E, C, N> {
+    /// Creates a new [`Metrics`] measuring time with `clock`, which passes all items to
+    /// `executor`.
+    #[inline]
+    pub fn new(executor: E, clock: C) -> Self {
+        Self {
+            executor,
+            clock,
+            snapshot: Snapshot::default(),
+        }
+    }
+
+    /// Returns a [`Snapshot`] of the statistics collected so far.
+    #[inline]
+    pub fn snapshot(&self) -> Snapshot<N> {
+        self.snapshot
+    }
+
+    /// Returns a [`Snapshot`] of the statistics collected so far and resets them.
+    #[inline]
+    pub fn take(&mut self) -> Snapshot<N> {
+        core::mem::take(&mut self.snapshot)
+    }
+
+    /// Returns the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.executor
+    }
+
+    /// Executes `f` and records it as `key` with `bytes` transferred and the return value
+    /// returned by `ret`.
+    fn measure<T: ?Sized>(
+        &mut self,
+        key: CallKey,
+        call: &mut T,
+        data: &mut [u8],
+        f: impl FnOnce(&mut E, &mut T, &mut [u8]) -> Result<()>,
+        ret: impl FnOnce(&T) -> usize,
+    ) -> Result<()> {
+        let bytes = data.len();
+        let start = (self.clock)();
+        let res = f(&mut self.executor, call, data);
+        let time = (self.clock)().saturating_sub(start);
+        let errno = match res {
+            Ok(()) => errno(ret(call)),
+            Err(e) => Some(e as _),
+        };
+        self.snapshot.record(key, bytes, time, errno);
+        res
+    }
+}
+
+impl<E: Executor, C: FnMut() -> u64, const N: usize> Executor for Metrics<E, C, N> {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        let key = CallKey::Syscall(call.num);
+        self.measure(key, call, data, E::syscall, |call| call.ret[0])
+    }
+
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        let key = CallKey::Gdbcall(call.num);
+        self.measure(key, call, data, E::gdbcall, |call| call.ret)
+    }
+
+    #[inline]
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        let key = CallKey::Enarxcall(call.num);
+        self.measure(key, call, data, E::enarxcall, |call| call.ret)
+    }
+
+    #[inline]
+    fn hello(&mut self, call: &mut Hello, data: &mut [u8]) -> Result<()> {
+        self.measure(CallKey::Hello, call, data, E::hello, |call| call.ret)
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        self.executor.reply()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::execute_with;
+    use crate::item::Item;
+    use crate::libc::{SYS_getpid, SYS_read, EBADF, ENOSYS, EPERM};
+
+    /// Fails `read`, succeeds `getpid` and denies all other syscalls.
+    struct Deny;
+
+    impl Executor for Deny {
+        fn syscall(&mut self, call: &mut Syscall, _: &mut [u8]) -> Result<()> {
+            #[allow(non_upper_case_globals)]
+            match call.num as _ {
+                SYS_read => call.ret = [-EBADF as _, 0],
+                SYS_getpid => call.ret = [42, 0],
+                _ => return Err(EPERM),
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn metrics() {
+        let mut now = 0;
+        let mut metrics = Metrics::<_, _, 2>::new(Deny, || {
+            now += 10;
+            now
+        });
+
+        let mut items = [
+            (SYS_read as usize, 16),
+            (SYS_getpid as _, 0),
+            (SYS_getpid as _, 0),
+            (SYS_read as _, 8),
+            (0x1000, 0),
+        ]
+        .map(|(num, len)| {
+            (
+                Syscall {
+                    num,
+                    argv: [0; 6],
+                    ret: [-ENOSYS as _, 0],
+                },
+                vec![0; len],
+            )
+        });
+        execute_with(
+            &mut metrics,
+            items
+                .iter_mut()
+                .map(|(call, data)| Item::Syscall(call, data)),
+        )
+        .unwrap();
+
+        let mut hello = Hello {
+            version: 0,
+            version_len: 0,
+            requires: 0,
+            requires_len: 0,
+            reply: 0,
+            ret: -ENOSYS as _,
+        };
+        execute_with(&mut metrics, [Item::Hello(&mut hello, &mut [])]).unwrap();
+
+        let snapshot = metrics.take();
+        let read = snapshot.get(CallKey::Syscall(SYS_read as _)).unwrap();
+        assert_eq!(read.calls, 2);
+        assert_eq!(read.errors, 2);
+        assert_eq!(read.bytes, 24);
+        assert_eq!(read.time, 20);
+        assert_eq!(read.latency[3], 2);
+
+        let getpid = snapshot.get(CallKey::Syscall(SYS_getpid as _)).unwrap();
+        assert_eq!((getpid.calls, getpid.errors), (2, 0));
+        assert_eq!(snapshot.calls().count(), 2);
+        assert_eq!(snapshot.untracked(), 2);
+        assert_eq!(snapshot.errors(EBADF), 2);
+        assert_eq!(snapshot.errors(EPERM), 1);
+        assert_eq!(snapshot.errors(-1), 0);
+
+        assert_eq!(metrics.snapshot(), Snapshot::default());
+        assert_eq!(metrics.into_inner().reply(), crate::host::Native.reply());
+    }
+}
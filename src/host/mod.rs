@@ -2,52 +2,166 @@
 
 //! Host-specific functionality.
 
+mod cpuid;
 #[cfg(not(miri))]
 mod enarxcall;
+mod executor;
+mod fd;
+#[cfg(not(miri))]
+mod gdb;
+mod hello;
+mod link;
+mod memory;
+mod metrics;
+mod policy;
+mod quote;
+mod record;
+mod sim;
 #[cfg(not(miri))]
 mod syscall;
+#[cfg(not(miri))]
+mod uring;
+
+pub use cpuid::*;
+pub use executor::*;
+pub use fd::*;
+#[cfg(not(miri))]
+pub use gdb::*;
+pub use memory::*;
+pub use metrics::*;
+pub use policy::*;
+pub use quote::*;
+pub use record::*;
+pub use sim::*;
+#[cfg(not(miri))]
+pub use uring::*;
 
-use crate::item::Item;
-use crate::libc::{EFAULT, EOVERFLOW};
+use self::link::Links;
+use crate::item::ring::Consumer;
+use crate::item::{Block, BlockWriter, Item, Notification};
+use crate::libc::{EFAULT, ENOMEM, EOVERFLOW};
 use crate::Result;
 
 use core::mem::{align_of, size_of};
 use core::ptr::slice_from_raw_parts_mut;
+use core::slice;
 
 pub(super) trait Execute {
-    unsafe fn execute(self) -> Result<()>;
+    fn execute(self, executor: &mut (impl Executor + ?Sized)) -> Result<()>;
 }
 
-impl<'a> Execute for Item<'a> {
+impl Execute for &mut Item<'_> {
     #[inline]
-    unsafe fn execute(self) -> Result<()> {
+    fn execute(self, executor: &mut (impl Executor + ?Sized)) -> Result<()> {
         match self {
-            #[cfg(not(miri))]
-            Item::Syscall(call, data) => syscall::execute(call, data),
-            #[cfg(miri)]
-            Item::Syscall { .. } => Ok(()),
+            Item::Syscall(call, data) => executor
+                .syscall(call, data)
+                .inspect_err(|e| call.ret = [-e as _, 0]),
+            Item::Gdbcall(call, data) => executor
+                .gdbcall(call, data)
+                .inspect_err(|e| call.ret = -e as _),
+            Item::Enarxcall(call, data) => executor
+                .enarxcall(call, data)
+                .inspect_err(|e| call.ret = -e as _),
+            Item::Hello(call, data) => executor
+                .hello(call, data)
+                .inspect_err(|e| call.ret = -e as _),
 
-            Item::Gdbcall { .. } => Ok(()),
+            // Notifications are only passed from the host to the guest.
+            Item::Notification { .. } => Ok(()),
 
-            #[cfg(not(miri))]
-            Item::Enarxcall(call, data) => enarxcall::execute(call, data),
-            #[cfg(miri)]
-            Item::Enarxcall { .. } => Ok(()),
+            // Links are resolved during execution of the block.
+            Item::Link { .. } => Ok(()),
         }
     }
 }
 
 impl<'a, T: IntoIterator<Item = Item<'a>>> Execute for T {
     #[inline]
-    unsafe fn execute(self) -> Result<()> {
-        self.into_iter().try_for_each(|item| item.execute())
+    fn execute(self, executor: &mut (impl Executor + ?Sized)) -> Result<()> {
+        // Failures are recorded in the return value of the respective item,
+        // so execution proceeds with the next item.
+        let mut links = Links::new();
+        self.into_iter().for_each(|mut item| {
+            let _ = links.execute(executor, &mut item);
+        });
+        Ok(())
     }
 }
 
-/// Executes the passed `items`.
+/// Executes the passed `items` using the [`Native`] executor.
+///
+/// An item, which cannot be executed, for example, because it references memory outside of
+/// its data section, does not prevent execution of the following items. Instead, the negated
+/// error number is written into the first return value of such an item.
 #[inline]
 pub fn execute<'a>(items: impl IntoIterator<Item = Item<'a>>) -> Result<()> {
-    unsafe { items.execute() }
+    execute_with(&mut Native, items)
+}
+
+/// Executes the passed `items` using `executor`.
+///
+/// Failures are handled as described for [`execute`].
+#[inline]
+pub fn execute_with<'a>(
+    executor: &mut (impl Executor + ?Sized),
+    items: impl IntoIterator<Item = Item<'a>>,
+) -> Result<()> {
+    items.execute(executor)
+}
+
+/// Executes all blocks submitted to `ring` by the guest in order and returns the count of
+/// blocks executed.
+pub fn drain(ring: &mut Consumer<'_>) -> Result<usize> {
+    let mut count = 0;
+    while let Some(ret) = ring.consume(|block| execute(Block::from(block)))? {
+        ret?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Appends `notifications` to the items contained in `block`, so that the guest receives
+/// them once it gets the control back, and returns the count of notifications appended.
+///
+/// Notifications, which do not fit in the `block`, are not appended.
+#[inline]
+pub fn notify(
+    block: &mut [usize],
+    notifications: impl IntoIterator<Item = Notification>,
+) -> Result<usize> {
+    let mut writer = BlockWriter::append(block)?;
+    let mut count = 0;
+    for notification in notifications {
+        match writer.push_notification(&notification) {
+            Ok(()) => count += 1,
+            Err(ENOMEM) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(count)
+}
+
+/// Size of the largest data section [`private`] copies into host-private memory.
+const PRIVATE_SIZE: usize = 0x20000;
+
+/// Copies `data` into host-private memory, passes the copy to `f` and copies it back into
+/// `data` afterwards.
+///
+/// Executors checking or translating memory referenced by a call pass the copy to the wrapped
+/// executor, so that the guest cannot modify the memory once it was checked or translated.
+///
+/// Returns [`ENOMEM`] if `data` is larger than [`PRIVATE_SIZE`].
+#[inline(never)]
+pub(super) fn private<T>(data: &mut [u8], f: impl FnOnce(&mut [u8]) -> T) -> Result<T> {
+    // The buffer is aligned like a data section, so that aligned offsets remain aligned.
+    let mut buf = [0usize; PRIVATE_SIZE / size_of::<usize>()];
+    let buf = unsafe { slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), PRIVATE_SIZE) };
+    let copy = buf.get_mut(..data.len()).ok_or(ENOMEM)?;
+    copy.copy_from_slice(data);
+    let ret = f(copy);
+    data.copy_from_slice(copy);
+    Ok(ret)
 }
 
 /// Validates that `data` contains `len` elements of type `T` at `offset`
@@ -101,12 +215,13 @@ pub fn deref_aligned_slice<T>(data: &mut [u8], offset: usize, len: usize) -> Res
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::item::{gdbcall, Gdbcall, Syscall};
+    use crate::item::link::BACK_MAX;
+    use crate::item::{gdbcall, Gdbcall, Link, Syscall};
     use crate::NULL;
 
     use libc::{
-        SYS_close, SYS_dup2, SYS_fcntl, SYS_read, SYS_sync, SYS_write, EFAULT, ENOSYS, EOVERFLOW,
-        F_GETFD, STDIN_FILENO, STDOUT_FILENO,
+        SYS_close, SYS_dup2, SYS_fcntl, SYS_read, SYS_sync, SYS_write, EBADF, ECANCELED, EFAULT,
+        EINVAL, ENOSYS, EOVERFLOW, F_GETFD, STDIN_FILENO, STDOUT_FILENO,
     };
     use std::fmt::Debug;
 
@@ -311,6 +426,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn execute_failed_item() {
+        let mut read = Syscall {
+            num: SYS_read as _,
+            argv: [STDIN_FILENO as _, 0, 1, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut sync = Syscall {
+            num: SYS_sync as _,
+            argv: [NULL, NULL, NULL, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        assert_eq!(
+            super::execute([
+                Item::Syscall(&mut read, &mut []),
+                Item::Syscall(&mut sync, &mut []),
+            ]),
+            Ok(())
+        );
+        #[cfg(not(miri))]
+        assert_eq!((read.ret, sync.ret), ([-EFAULT as _, 0], [0, 0]));
+        #[cfg(miri)]
+        assert_eq!((read.ret, sync.ret), ([-ENOSYS as _, 0], [-ENOSYS as _, 0]));
+    }
+
+    #[test]
+    fn execute_linked_items() {
+        let fd = 43;
+        let mut dup2 = Syscall {
+            num: SYS_dup2 as _,
+            argv: [STDIN_FILENO as _, fd, NULL, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut fcntl = Syscall {
+            num: SYS_fcntl as _,
+            argv: [NULL, F_GETFD as _, NULL, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut close_invalid = Syscall {
+            num: SYS_close as _,
+            argv: [-1 as _, NULL, NULL, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut close_canceled = Syscall {
+            num: SYS_close as _,
+            argv: [NULL, NULL, NULL, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut sync_far = Syscall {
+            num: SYS_sync as _,
+            argv: [NULL, NULL, NULL, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut sync_arg = sync_far;
+        let mut close = Syscall {
+            num: SYS_close as _,
+            argv: [NULL, NULL, NULL, NULL, NULL, NULL],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut links = [
+            Link { back: 1, arg: 0 },
+            Link { back: 1, arg: 0 },
+            Link {
+                back: BACK_MAX + 1,
+                arg: 0,
+            },
+            Link { back: 5, arg: 6 },
+            Link { back: 6, arg: 0 },
+        ];
+        let [l0, l1, l2, l3, l4] = &mut links;
+        assert_eq!(
+            super::execute([
+                Item::Syscall(&mut dup2, &mut []),
+                Item::Link(l0, &mut []),
+                Item::Syscall(&mut fcntl, &mut []),
+                Item::Syscall(&mut close_invalid, &mut []),
+                Item::Link(l1, &mut []),
+                Item::Syscall(&mut close_canceled, &mut []),
+                Item::Link(l2, &mut []),
+                Item::Syscall(&mut sync_far, &mut []),
+                Item::Link(l3, &mut []),
+                Item::Syscall(&mut sync_arg, &mut []),
+                Item::Link(l4, &mut []),
+                Item::Syscall(&mut close, &mut []),
+            ]),
+            Ok(())
+        );
+        assert_eq!(close_canceled.argv[0], NULL);
+        assert_eq!(sync_far.ret, [-EINVAL as _, 0]);
+        #[cfg(not(miri))]
+        {
+            assert_eq!(sync_arg.ret, [-EINVAL as _, 0]);
+            assert_eq!(dup2.ret, [fd, 0]);
+            assert_eq!((fcntl.argv[0], fcntl.ret), (fd, [0, 0]));
+            assert_eq!(close_invalid.ret, [-EBADF as _, 0]);
+            assert_eq!(close_canceled.ret, [-ECANCELED as _, 0]);
+            assert_eq!((close.argv[0], close.ret), (fd, [0, 0]));
+        }
+        #[cfg(miri)]
+        assert_eq!(fcntl.ret, [-ECANCELED as _, 0]);
+    }
+
     #[test]
     fn execute() {
         let fd = 42;
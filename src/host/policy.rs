@@ -0,0 +1,744 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{private, Executor};
+use crate::item::hello::Reply;
+use crate::item::{Enarxcall, Gdbcall, Hello, Syscall};
+use crate::libc::{
+    mode_t, msghdr, sa_family_t, stat, SYS_connect, SYS_mkdirat, SYS_newfstatat, SYS_open,
+    SYS_openat, SYS_renameat2, SYS_sendmsg, SYS_sendto, SYS_socket, SYS_statx, SYS_unlinkat,
+    AF_INET, AF_INET6, AF_UNIX, AT_EMPTY_PATH, AT_FDCWD, AT_SYMLINK_NOFOLLOW, ENOSYS, S_IFLNK,
+    S_IFMT,
+};
+use crate::{Result, NULL};
+
+use core::ffi::{c_int, c_long};
+use core::mem::size_of;
+use core::slice;
+
+/// Socket address matched by [`Rule::Connect`] or listened on by [`GdbBridge`](super::GdbBridge).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sockaddr<'a> {
+    /// IPv4 address and port. A `port` of `0` matches any port.
+    Inet { addr: [u8; 4], port: u16 },
+
+    /// IPv6 address and port. A `port` of `0` matches any port.
+    Inet6 { addr: [u8; 16], port: u16 },
+
+    /// Path of a Unix domain socket without the trailing nul byte.
+    Unix(&'a [u8]),
+}
+
+impl<'a> Sockaddr<'a> {
+    /// Decodes a socket address of `addrlen` bytes at `offset` within `data`.
+    pub(super) fn decode(data: &'a [u8], offset: usize, addrlen: usize) -> Option<Self> {
+        let addr = data.get(offset..offset.checked_add(addrlen)?)?;
+        let family = addr.get(..size_of::<sa_family_t>())?;
+        let tail = &addr[family.len()..];
+        let port = || Some(u16::from_be_bytes(tail.get(..2)?.try_into().ok()?));
+        match sa_family_t::from_ne_bytes(family.try_into().ok()?) as c_int {
+            AF_INET => Some(Self::Inet {
+                addr: tail.get(2..6)?.try_into().ok()?,
+                port: port()?,
+            }),
+            AF_INET6 => Some(Self::Inet6 {
+                addr: tail.get(6..22)?.try_into().ok()?,
+                port: port()?,
+            }),
+            // Abstract socket addresses start with a nul byte and are not nul-terminated.
+            AF_UNIX if tail.first() == Some(&0) => Some(Self::Unix(tail)),
+            AF_UNIX => Some(Self::Unix(until_nul(tail))),
+            _ => None,
+        }
+    }
+
+    /// Encodes the socket address into `buf` and returns the length of the encoded address.
+    pub(super) fn encode(&self, buf: &mut [u8]) -> Option<usize> {
+        let (family, tail): (_, &[&[u8]]) = match self {
+            Self::Inet { addr, port } => (AF_INET, &[&port.to_be_bytes(), addr, &[0; 8]]),
+            Self::Inet6 { addr, port } => {
+                (AF_INET6, &[&port.to_be_bytes(), &[0; 4], addr, &[0; 4]])
+            }
+            Self::Unix(path) if path.first() == Some(&0) => (AF_UNIX, &[path]),
+            Self::Unix(path) => (AF_UNIX, &[path, &[0]]),
+        };
+        let family = (family as sa_family_t).to_ne_bytes();
+        let mut len = 0usize;
+        for bytes in [&family[..]].iter().chain(tail) {
+            buf.get_mut(len..len.checked_add(bytes.len())?)?
+                .copy_from_slice(bytes);
+            len += bytes.len();
+        }
+        Some(len)
+    }
+
+    /// Returns `true` if `self` matches `addr`.
+    fn matches(&self, addr: &Sockaddr<'_>) -> bool {
+        match (self, addr) {
+            (Self::Inet { addr: a, port: p }, Sockaddr::Inet { addr, port }) => {
+                a == addr && (*p == 0 || p == port)
+            }
+            (Self::Inet6 { addr: a, port: p }, Sockaddr::Inet6 { addr, port }) => {
+                a == addr && (*p == 0 || p == port)
+            }
+            (Self::Unix(a), Sockaddr::Unix(path)) => a == path,
+            _ => false,
+        }
+    }
+}
+
+/// Returns `bytes` up to the first nul byte.
+fn until_nul(bytes: &[u8]) -> &[u8] {
+    bytes.split(|b| *b == 0).next().unwrap_or(bytes)
+}
+
+/// Returns the nul-terminated string at `offset` within `data` without the nul byte, which
+/// must be contained in the `len` bytes at `offset`, if specified.
+fn nul_terminated(data: &[u8], offset: usize, len: Option<usize>) -> Option<&[u8]> {
+    let tail = data.get(offset..)?;
+    let s = match len {
+        Some(len) => tail.get(..len)?,
+        None => tail,
+    };
+    let nul = s.iter().position(|b| *b == 0)?;
+    Some(&s[..nul])
+}
+
+/// Maximum length of a path including the nul byte.
+const PATH_MAX: usize = 4096;
+
+/// Syscalls checked by [`Rule::Connect`].
+const CONNECT_SYSCALLS: &[c_long] = &[SYS_connect, SYS_sendmsg, SYS_sendto];
+
+/// Syscalls checked by [`Rule::OpenBelow`].
+const PATH_SYSCALLS: &[c_long] = &[
+    SYS_mkdirat,
+    SYS_newfstatat,
+    SYS_open,
+    SYS_openat,
+    SYS_renameat2,
+    SYS_statx,
+    SYS_unlinkat,
+];
+
+/// Returns the paths referenced by `call` to [`PATH_SYSCALLS`] in `data`.
+///
+/// An inner `None` denotes a path, which is not nul-terminated within its data. Empty paths
+/// passed along with `AT_EMPTY_PATH` refer to the directory file descriptor and are skipped.
+fn paths<'d>(call: &Syscall, data: &'d [u8]) -> [Option<Option<&'d [u8]>>; 2] {
+    let [_, arg1, arg2, arg3, arg4, _] = call.argv;
+    let path = |offset, len| Some(nul_terminated(data, offset, len));
+    let path_at = |flags: usize| match path(arg1, Some(arg2)) {
+        Some(Some(b"")) if flags as c_int & AT_EMPTY_PATH != 0 => None,
+        path => path,
+    };
+    #[allow(non_upper_case_globals)]
+    match call.num as c_long {
+        SYS_open => [path(call.argv[0], Some(arg1)), None],
+        SYS_mkdirat | SYS_openat | SYS_unlinkat => [path(arg1, Some(arg2)), None],
+        SYS_newfstatat => [path_at(arg4), None],
+        SYS_statx => [path_at(arg3), None],
+        SYS_renameat2 => [path(arg1, None), path(arg3, None)],
+        _ => [None, None],
+    }
+}
+
+/// Rule of a [`Policy`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rule<'a> {
+    /// Denies the syscall with the given number.
+    Deny(c_long),
+
+    /// Allows `connect`, `sendmsg` and `sendto` with a destination address only to one of the
+    /// addresses.
+    Connect(&'a [Sockaddr<'a>]),
+
+    /// Allows `mkdirat`, `newfstatat`, `open`, `openat`, `renameat2`, `statx` and `unlinkat`
+    /// only of absolute paths below one of the directories.
+    ///
+    /// Paths containing `..` components or traversing symbolic links below the directories
+    /// are denied.
+    OpenBelow(&'a [&'a [u8]]),
+
+    /// Denies `socket` in any of the domains.
+    SocketDomain(&'a [c_int]),
+}
+
+impl Rule<'_> {
+    /// Returns `true` if the rule allows execution of `call` referencing memory in `data`.
+    fn allows(&self, call: &Syscall, data: &[u8]) -> bool {
+        let [arg0, arg1, arg2, _, arg4, arg5] = call.argv;
+        #[allow(non_upper_case_globals)]
+        match (*self, call.num as c_long) {
+            (Self::Deny(num), _) => num != call.num as c_long,
+            (Self::Connect(addrs), SYS_connect) => Self::allows_addr(addrs, data, arg1, arg2),
+            (Self::Connect(_), SYS_sendto) if arg4 == NULL => true,
+            (Self::Connect(addrs), SYS_sendto) => Self::allows_addr(addrs, data, arg4, arg5),
+            (Self::Connect(addrs), SYS_sendmsg) => Self::allows_msg(addrs, data, arg1),
+            (Self::OpenBelow(dirs), _) => paths(call, data)
+                .iter()
+                .flatten()
+                .all(|path| path.is_some_and(|path| dirs.iter().any(|dir| is_below(path, dir)))),
+            // The kernel truncates the domain to an `int`.
+            (Self::SocketDomain(domains), SYS_socket) => !domains.contains(&(arg0 as c_int)),
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if the rule inspects memory referenced by syscall `num`.
+    fn inspects(&self, num: c_long) -> bool {
+        match self {
+            Self::Connect(_) => CONNECT_SYSCALLS.contains(&num),
+            Self::OpenBelow(_) => PATH_SYSCALLS.contains(&num),
+            Self::Deny(_) | Self::SocketDomain(_) => false,
+        }
+    }
+
+    fn allows_addr(addrs: &[Sockaddr<'_>], data: &[u8], offset: usize, addrlen: usize) -> bool {
+        match Sockaddr::decode(data, offset, addrlen) {
+            Some(addr) => addrs.iter().any(|allowed| allowed.matches(&addr)),
+            None => false,
+        }
+    }
+
+    fn allows_msg(addrs: &[Sockaddr<'_>], data: &[u8], offset: usize) -> bool {
+        let msg = match data
+            .get(offset..)
+            .and_then(|tail| tail.get(..size_of::<msghdr>()))
+        {
+            Some(msg) => unsafe { msg.as_ptr().cast::<msghdr>().read_unaligned() },
+            None => return false,
+        };
+        match msg.msg_name as usize {
+            NULL => true,
+            name => Self::allows_addr(addrs, data, name, msg.msg_namelen as _),
+        }
+    }
+}
+
+/// Returns the components of `path` other than empty and `.` components.
+fn components(path: &[u8]) -> impl Iterator<Item = &[u8]> {
+    path.split(|b| *b == b'/')
+        .filter(|c| !c.is_empty() && *c != b".")
+}
+
+/// Returns the count of components of the absolute `path` below `dir`, if `path` is equal to
+/// or below `dir` and does not contain `..` components.
+///
+/// Both paths are compared component-wise, so that repeated separators and `.` components do
+/// not matter.
+fn below(path: &[u8], dir: &[u8]) -> Option<usize> {
+    if !path.starts_with(b"/") || components(path).any(|c| c == b"..") {
+        return None;
+    }
+    let mut path = components(path);
+    for dir in components(dir) {
+        if path.next() != Some(dir) {
+            return None;
+        }
+    }
+    Some(path.count())
+}
+
+/// Returns `true` if `path` is an absolute path equal to or below `dir` without `..` components.
+fn is_below(path: &[u8], dir: &[u8]) -> bool {
+    below(path, dir).is_some()
+}
+
+/// Declarative policy restricting the syscalls a guest may ask the host to execute.
+///
+/// A syscall is denied if any of the rules denies it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Policy<'a> {
+    rules: &'a [Rule<'a>],
+    errno: c_int,
+}
+
+impl<'a> Policy<'a> {
+    /// Creates a new [`Policy`] consisting of `rules`, which denies syscalls with `errno`.
+    #[inline]
+    pub const fn new(rules: &'a [Rule<'a>], errno: c_int) -> Self {
+        Self { rules, errno }
+    }
+
+    /// Returns the first rule denying execution of `call` referencing memory in `data`, if any.
+    ///
+    /// Symbolic links traversed by paths are not considered, as these can only be checked by
+    /// an [`Enforcer`] with access to the file system.
+    #[inline]
+    pub fn check(&self, call: &Syscall, data: &[u8]) -> Option<&'a Rule<'a>> {
+        self.rules.iter().find(|rule| !rule.allows(call, data))
+    }
+
+    /// Returns `true` if any rule inspects memory referenced by syscall `num`.
+    #[inline]
+    fn inspects(&self, num: c_long) -> bool {
+        self.rules.iter().any(|rule| rule.inspects(num))
+    }
+}
+
+/// Syscall denied by a [`Policy`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Denial<'a, 'b> {
+    /// The denied syscall.
+    pub call: &'b Syscall,
+
+    /// The rule denying the syscall.
+    pub rule: &'a Rule<'a>,
+}
+
+/// [`Executor`] enforcing a [`Policy`] before each syscall executed by another executor.
+///
+/// Denied syscalls are not executed and fail with the errno of the policy. Each denial is
+/// counted and passed to the `log` callback.
+///
+/// Syscalls and the memory inspected by the policy are copied into host-private memory before
+/// the check and the copies are passed to the wrapped executor, so that the guest cannot modify
+/// them after the check. Syscalls referencing data sections larger than the host-private memory
+/// fail with [`ENOMEM`](crate::libc::ENOMEM) if the policy inspects them.
+///
+/// Symbolic links traversed by paths checked by [`Rule::OpenBelow`] are detected using
+/// `newfstatat` of the wrapped executor.
+///
+/// Syscalls [offloaded](Executor::offload) to the kernel are checked likewise, apart from
+/// syscalls the policy inspects memory of, which are always executed by the [`Enforcer`].
+#[derive(Debug)]
+pub struct Enforcer<'a, E, F> {
+    policy: Policy<'a>,
+    executor: E,
+    log: F,
+    denials: usize,
+}
+
+impl<'a, E: Executor, F: FnMut(&Denial<'a, '_>)> Enforcer<'a, E, F> {
+    /// Creates a new [`Enforcer`] of `policy`, which passes allowed items to `executor`
+    /// and denials to `log`.
+    #[inline]
+    pub fn new(policy: Policy<'a>, executor: E, log: F) -> Self {
+        Self {
+            policy,
+            executor,
+            log,
+            denials: 0,
+        }
+    }
+
+    /// Returns the count of syscalls denied so far.
+    #[inline]
+    pub fn denials(&self) -> usize {
+        self.denials
+    }
+
+    /// Returns the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.executor
+    }
+
+    /// Checks `call` referencing memory in host-private `data` and executes it if allowed.
+    fn enforce(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        let rule = self
+            .policy
+            .check(call, data)
+            .or_else(|| self.traversed_symlink(call, data));
+        if let Some(rule) = rule {
+            return Err(self.deny(call, rule));
+        }
+        self.executor.syscall(call, data)
+    }
+
+    /// Counts and logs the denial of `call` by `rule` and returns the errno of the policy.
+    fn deny(&mut self, call: &Syscall, rule: &'a Rule<'a>) -> c_int {
+        self.denials += 1;
+        (self.log)(&Denial { call, rule });
+        self.policy.errno
+    }
+
+    /// Returns the first [`Rule::OpenBelow`] denying `call`, because one of its paths traverses
+    /// a symbolic link below the directory it is allowed by.
+    fn traversed_symlink(&mut self, call: &Syscall, data: &[u8]) -> Option<&'a Rule<'a>> {
+        let rules = self.policy.rules;
+        rules.iter().find(|rule| match rule {
+            Rule::OpenBelow(dirs) => paths(call, data).iter().flatten().flatten().any(|path| {
+                dirs.iter()
+                    .find_map(|dir| below(path, dir))
+                    .is_some_and(|depth| self.traverses_symlink(path, depth))
+            }),
+            _ => false,
+        })
+    }
+
+    /// Returns `true` if any of the last `depth` components of the absolute `path` is a
+    /// symbolic link.
+    ///
+    /// Checking stops at the first component, which cannot be inspected, for example, because
+    /// it does not exist.
+    fn traverses_symlink(&mut self, path: &[u8], depth: usize) -> bool {
+        let mut skip = components(path).count() - depth;
+        let mut start = 0;
+        for component in path.split(|b| *b == b'/') {
+            let end = start + component.len();
+            start = end + 1;
+            if component.is_empty() || component == b"." {
+                continue;
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            match self.lstat_mode(&path[..end]) {
+                Some(mode) if mode & S_IFMT == S_IFLNK => return true,
+                Some(_) => {}
+                None => break,
+            }
+        }
+        false
+    }
+
+    /// Returns the mode of the file at absolute `path` without following a final symbolic link.
+    fn lstat_mode(&mut self, path: &[u8]) -> Option<mode_t> {
+        const LEN: usize = size_of::<stat>() + PATH_MAX;
+        let mut buf = [0usize; LEN / size_of::<usize>()];
+        let data = unsafe { slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), LEN) };
+        // The path is followed by at least one nul byte.
+        let pathname = data.get_mut(size_of::<stat>()..)?.get_mut(..=path.len())?;
+        pathname[..path.len()].copy_from_slice(path);
+
+        let mut call = Syscall {
+            num: SYS_newfstatat as _,
+            argv: [
+                AT_FDCWD as _,
+                size_of::<stat>(),
+                path.len() + 1,
+                0,
+                AT_SYMLINK_NOFOLLOW as _,
+                0,
+            ],
+            ret: [-ENOSYS as _, 0],
+        };
+        self.executor.syscall(&mut call, data).ok()?;
+        if call.ret[0] != 0 {
+            return None;
+        }
+        let stat = unsafe { data.as_ptr().cast::<stat>().read() };
+        Some(stat.st_mode)
+    }
+}
+
+impl<'a, E: Executor, F: FnMut(&Denial<'a, '_>)> Executor for Enforcer<'a, E, F> {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        // The syscall is executed using a host-private copy, so that the guest cannot modify it
+        // after the check.
+        let mut copy = *call;
+        let ret = if self.policy.inspects(copy.num as _) {
+            private(data, |data| self.enforce(&mut copy, data))?
+        } else {
+            self.enforce(&mut copy, data)
+        };
+        call.ret = copy.ret;
+        ret
+    }
+
+    #[inline]
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        // Memory inspected by the policy is only checked in host-private copies, which the
+        // kernel does not access when executing the syscall directly.
+        if self.policy.inspects(call.num as _) {
+            return Ok(false);
+        }
+        if let Some(rule) = self.policy.check(call, data) {
+            return Err(self.deny(call, rule));
+        }
+        self.executor.offload(call, data)
+    }
+
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        self.executor.gdbcall(call, data)
+    }
+
+    #[inline]
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        self.executor.enarxcall(call, data)
+    }
+
+    #[inline]
+    fn hello(&mut self, call: &mut Hello, data: &mut [u8]) -> Result<()> {
+        self.executor.hello(call, data)
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        self.executor.reply()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{execute_with, Native};
+    use crate::item::Item;
+    use crate::libc::{SYS_sync, AF_PACKET, EACCES, ENOENT, ENOSYS, O_RDONLY, SOCK_STREAM};
+
+    use std::format;
+    use std::vec::Vec;
+
+    fn syscall(num: c_long, argv: [usize; 6]) -> Syscall {
+        Syscall {
+            num: num as _,
+            argv,
+            ret: [-ENOSYS as _, 0],
+        }
+    }
+
+    fn sockaddr_in(addr: [u8; 4], port: u16) -> [u8; 16] {
+        let addr = crate::libc::sockaddr_in {
+            sin_family: AF_INET as _,
+            sin_port: port.to_be(),
+            sin_addr: crate::libc::in_addr {
+                s_addr: u32::from_ne_bytes(addr),
+            },
+            sin_zero: [0xff; 8],
+        };
+        unsafe { *(&addr as *const _ as *const _) }
+    }
+
+    #[test]
+    fn decode() {
+        let data = sockaddr_in([127, 0, 0, 1], 8080);
+        assert_eq!(
+            Sockaddr::decode(&data, 0, data.len()),
+            Some(Sockaddr::Inet {
+                addr: [127, 0, 0, 1],
+                port: 8080
+            })
+        );
+        assert_eq!(Sockaddr::decode(&data, 0, 4), None);
+        assert_eq!(Sockaddr::decode(&data, 1, data.len()), None);
+
+        let mut data = [0u8; 12];
+        data[..2].copy_from_slice(&(AF_UNIX as sa_family_t).to_ne_bytes());
+        data[2..7].copy_from_slice(b"/sock");
+        assert_eq!(
+            Sockaddr::decode(&data, 0, data.len()),
+            Some(Sockaddr::Unix(b"/sock"))
+        );
+        data[2] = 0;
+        assert_eq!(
+            Sockaddr::decode(&data, 0, 7),
+            Some(Sockaddr::Unix(b"\0sock"))
+        );
+    }
+
+    #[test]
+    fn below() {
+        assert!(is_below(b"/srv/data", b"/srv"));
+        assert!(is_below(b"/srv/data/file", b"/srv/"));
+        assert!(is_below(b"/srv", b"/srv"));
+        assert!(!is_below(b"/srvdata", b"/srv"));
+        assert!(!is_below(b"/srv/../etc/passwd", b"/srv"));
+        assert!(!is_below(b"srv/data", b"srv"));
+        assert!(is_below(b"//srv/./data", b"/srv"));
+        assert!(!is_below(b"/srv/data/..", b"/srv"));
+        assert_eq!(super::below(b"/srv//data/./file", b"/srv/"), Some(2));
+    }
+
+    #[test]
+    fn check() {
+        const ALLOWED: &[Sockaddr] = &[
+            Sockaddr::Inet {
+                addr: [127, 0, 0, 1],
+                port: 0,
+            },
+            Sockaddr::Inet {
+                addr: [10, 0, 0, 1],
+                port: 443,
+            },
+        ];
+        const RULES: &[Rule] = &[
+            Rule::Deny(SYS_sync),
+            Rule::Connect(ALLOWED),
+            Rule::OpenBelow(&[b"/srv"]),
+            Rule::SocketDomain(&[AF_PACKET]),
+        ];
+        let policy = Policy::new(RULES, EACCES);
+
+        let check = |num, argv, data: &[u8]| policy.check(&syscall(num, argv), data);
+        assert_eq!(check(SYS_sync, [0; 6], &[]), Some(&RULES[0]));
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&sockaddr_in([127, 0, 0, 1], 22));
+        data.extend_from_slice(&sockaddr_in([10, 0, 0, 1], 80));
+        assert_eq!(check(SYS_connect, [3, 0, 16, 0, 0, 0], &data), None);
+        assert_eq!(
+            check(SYS_connect, [3, 16, 16, 0, 0, 0], &data),
+            Some(&RULES[1])
+        );
+        assert_eq!(
+            check(SYS_connect, [3, 16, 32, 0, 0, 0], &data),
+            Some(&RULES[1])
+        );
+        assert_eq!(check(SYS_sendto, [3, 0, 0, 0, NULL, 0], &data), None);
+        assert_eq!(
+            check(SYS_sendto, [3, 0, 0, 0, 16, 16], &data),
+            Some(&RULES[1])
+        );
+        for (name, namelen, denied) in [(NULL, 0, None), (0, 16, None), (16, 16, Some(&RULES[1]))] {
+            let msg = msghdr {
+                msg_name: name as _,
+                msg_namelen: namelen,
+                msg_iov: NULL as _,
+                msg_iovlen: 0,
+                msg_control: NULL as _,
+                msg_controllen: 0,
+                msg_flags: 0,
+            };
+            let mut data = data.clone();
+            data.extend_from_slice(unsafe {
+                core::slice::from_raw_parts(&msg as *const _ as *const u8, size_of::<msghdr>())
+            });
+            assert_eq!(check(SYS_sendmsg, [3, 32, 0, 0, 0, 0], &data), denied);
+        }
+        assert_eq!(
+            check(SYS_sendmsg, [3, 32, 0, 0, 0, 0], &data),
+            Some(&RULES[1])
+        );
+
+        assert_eq!(check(SYS_open, [0, 10, 0, 0, 0, 0], b"/srv/file\0"), None);
+        assert_eq!(
+            check(SYS_open, [0, 11, 0, 0, 0, 0], b"/etc/passwd"),
+            Some(&RULES[2])
+        );
+        assert_eq!(
+            check(
+                SYS_open,
+                [0, 9, 0, 0, 0, 0],
+                b"/srv/file/../../etc/passwd\0"
+            ),
+            Some(&RULES[2])
+        );
+        assert_eq!(check(SYS_openat, [3, 0, 10, 0, 0, 0], b"/srv/file\0"), None);
+        assert_eq!(
+            check(SYS_openat, [3, 0, 5, 0, 0, 0], b"file\0"),
+            Some(&RULES[2])
+        );
+
+        assert_eq!(
+            check(SYS_mkdirat, [3, 0, 10, 0, 0, 0], b"/srv/dir\0\0"),
+            None
+        );
+        assert_eq!(
+            check(SYS_unlinkat, [3, 0, 10, 0, 0, 0], b"/etc/file\0"),
+            Some(&RULES[2])
+        );
+        let data = b"/srv/old\0/srv/new\0/etc/new\0";
+        assert_eq!(check(SYS_renameat2, [3, 0, 3, 9, 0, 0], data), None);
+        assert_eq!(
+            check(SYS_renameat2, [3, 0, 3, 18, 0, 0], data),
+            Some(&RULES[2])
+        );
+        assert_eq!(
+            check(SYS_renameat2, [3, 18, 3, 9, 0, 0], data),
+            Some(&RULES[2])
+        );
+        let empty = AT_EMPTY_PATH as usize;
+        assert_eq!(check(SYS_newfstatat, [3, 0, 1, 1, empty, 0], b"\0"), None);
+        assert_eq!(
+            check(SYS_newfstatat, [3, 0, 1, 1, 0, 0], b"\0"),
+            Some(&RULES[2])
+        );
+        assert_eq!(check(SYS_statx, [3, 0, 1, empty, 0, 1], b"\0"), None);
+        assert_eq!(
+            check(SYS_statx, [3, 0, 5, 0, 0, 5], b"/etc\0"),
+            Some(&RULES[2])
+        );
+
+        let socket = |domain: c_int| [domain as _, SOCK_STREAM as _, 0, 0, 0, 0];
+        assert_eq!(check(SYS_socket, socket(AF_INET), &[]), None);
+        assert_eq!(check(SYS_socket, socket(AF_PACKET), &[]), Some(&RULES[3]));
+        let mut truncated = socket(AF_PACKET);
+        truncated[0] |= 1 << 32;
+        assert_eq!(check(SYS_socket, truncated, &[]), Some(&RULES[3]));
+    }
+
+    #[test]
+    fn enforce() {
+        let rules = [Rule::Deny(SYS_sync)];
+        let mut denied = Vec::new();
+        let mut enforcer = Enforcer::new(Policy::new(&rules, EACCES), Native, |denial: &Denial| {
+            denied.push(denial.call.num)
+        });
+
+        let mut calls = [syscall(SYS_sync, [0; 6]); 2];
+        let [first, second] = &mut calls;
+        assert_eq!(
+            execute_with(
+                &mut enforcer,
+                [
+                    Item::Syscall(first, &mut []),
+                    Item::Syscall(second, &mut []),
+                ]
+            ),
+            Ok(())
+        );
+        assert_eq!(enforcer.denials(), 2);
+        let _ = enforcer.into_inner();
+        assert_eq!(denied, [SYS_sync as usize; 2]);
+        assert_eq!(calls.map(|call| call.ret), [[-EACCES as _, 0]; 2]);
+    }
+
+    #[test]
+    fn enforce_private() {
+        /// Executor checking that it is passed a host-private copy of the data section.
+        struct Private(*const u8);
+
+        impl Executor for Private {
+            fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+                if call.num == SYS_newfstatat as _ {
+                    return Err(ENOENT);
+                }
+                assert_ne!(data.as_ptr(), self.0);
+                assert_eq!(&data[..10], b"/srv/file\0");
+                data[0] = b'x';
+                call.ret = [0, 0];
+                Ok(())
+            }
+        }
+
+        let rules = [Rule::OpenBelow(&[b"/srv"])];
+        let mut data = *b"/srv/file\0";
+        let mut enforcer =
+            Enforcer::new(Policy::new(&rules, EACCES), Private(data.as_ptr()), |_| {});
+        let mut call = syscall(SYS_open, [0, data.len(), 0, 0, 0, 0]);
+        assert_eq!(enforcer.syscall(&mut call, &mut data), Ok(()));
+        assert_eq!(call.ret, [0, 0]);
+        assert_eq!(&data, b"xsrv/file\0");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn enforce_symlink() {
+        let dir = std::env::temp_dir().join("sallyport-test-policy-symlink");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::os::unix::fs::symlink("/etc", dir.join("link")).unwrap();
+        let dir = dir.to_str().unwrap();
+
+        let dirs = [dir.as_bytes()];
+        let rules = [Rule::OpenBelow(&dirs)];
+        let mut enforcer = Enforcer::new(Policy::new(&rules, EACCES), Native, |_| {});
+        let mut open = |path: &str| {
+            let mut data = Vec::from(path);
+            data.push(0);
+            let mut call = syscall(SYS_open, [0, data.len(), O_RDONLY as _, 0, 0, 0]);
+            enforcer.syscall(&mut call, &mut data).map(|()| {
+                let _ = unsafe { libc::close(call.ret[0] as _) };
+            })
+        };
+        assert_eq!(open(&format!("{dir}/sub")), Ok(()));
+        assert_eq!(open(&format!("{dir}/./sub/missing")), Ok(()));
+        assert_eq!(open(&format!("{dir}/link/passwd")), Err(EACCES));
+        assert_eq!(open(&format!("{dir}//link")), Err(EACCES));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
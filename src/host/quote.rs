@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{deref, deref_slice, Executor};
+use crate::item::enarxcall::sgx::{Report, ReportPayload, TargetInfo};
+use crate::item::enarxcall::Number;
+use crate::item::hello::Reply;
+use crate::item::{Enarxcall, Gdbcall, Kind, Syscall};
+use crate::libc::EMSGSIZE;
+use crate::Result;
+
+use core::mem::size_of;
+
+/// Size of the header of quotes fabricated by [`MockQuote`].
+const HEADER_SIZE: usize = 48;
+
+/// Provider of SGX quotes requested by the guest via [`GetSgxTargetInfo`](Number::GetSgxTargetInfo),
+/// [`GetSgxQuoteSize`](Number::GetSgxQuoteSize) and [`GetSgxQuote`](Number::GetSgxQuote)
+/// Enarx calls.
+pub trait QuoteProvider {
+    /// Writes the [`TargetInfo`] of the quoting enclave into `info`.
+    fn target_info(&mut self, info: &mut TargetInfo) -> Result<()>;
+
+    /// Returns the size of the quotes in bytes.
+    fn quote_size(&mut self) -> Result<usize>;
+
+    /// Writes the quote of `report` into `quote` and returns the length of the quote.
+    fn quote(&mut self, report: &Report, quote: &mut [u8]) -> Result<usize>;
+}
+
+impl<Q: QuoteProvider + ?Sized> QuoteProvider for &mut Q {
+    #[inline]
+    fn target_info(&mut self, info: &mut TargetInfo) -> Result<()> {
+        (**self).target_info(info)
+    }
+
+    #[inline]
+    fn quote_size(&mut self) -> Result<usize> {
+        (**self).quote_size()
+    }
+
+    #[inline]
+    fn quote(&mut self, report: &Report, quote: &mut [u8]) -> Result<usize> {
+        (**self).quote(report, quote)
+    }
+}
+
+/// Deterministic [`QuoteProvider`] fabricating quotes without SGX hardware.
+///
+/// The [`TargetInfo`] describes a 64-bit initialized quoting enclave with
+/// [`MRENCLAVE`](MockQuote::MRENCLAVE). Quotes follow the layout of an ECDSA quote of version 3
+/// consisting of a header with [`USER_DATA`](MockQuote::USER_DATA) as the user data, the
+/// [`ReportPayload`] of the report and an empty signature, so they are well-formed, but not
+/// verifiable.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MockQuote;
+
+impl MockQuote {
+    /// `MRENCLAVE` of the mock quoting enclave.
+    pub const MRENCLAVE: [u8; 32] = [0xee; 32];
+
+    /// User data contained in the quote header.
+    pub const USER_DATA: [u8; 20] = *b"sallyport mock quote";
+
+    /// Size of the quotes in bytes.
+    pub const QUOTE_SIZE: usize = HEADER_SIZE + size_of::<ReportPayload>() + size_of::<u32>();
+}
+
+impl QuoteProvider for MockQuote {
+    fn target_info(&mut self, info: &mut TargetInfo) -> Result<()> {
+        const INIT: u64 = 1 << 0;
+        const MODE64BIT: u64 = 1 << 2;
+        const XFRM_X87_SSE: u64 = 0b11;
+
+        *info = TargetInfo::default();
+        info.mrenclave = Self::MRENCLAVE;
+        info.attributes = [INIT | MODE64BIT, XFRM_X87_SSE];
+        Ok(())
+    }
+
+    fn quote_size(&mut self) -> Result<usize> {
+        Ok(Self::QUOTE_SIZE)
+    }
+
+    fn quote(&mut self, report: &Report, quote: &mut [u8]) -> Result<usize> {
+        const VERSION: u16 = 3;
+        const ECDSA_P256: u16 = 2;
+
+        let quote = quote.get_mut(..Self::QUOTE_SIZE).ok_or(EMSGSIZE)?;
+        quote.fill(0);
+        let (header, tail) = quote.split_at_mut(HEADER_SIZE);
+        let (body, _signature_len) = tail.split_at_mut(size_of::<ReportPayload>());
+        header[0..2].copy_from_slice(&VERSION.to_le_bytes());
+        header[2..4].copy_from_slice(&ECDSA_P256.to_le_bytes());
+        header[HEADER_SIZE - Self::USER_DATA.len()..].copy_from_slice(&Self::USER_DATA);
+        body.copy_from_slice(&report.as_ref()[..size_of::<ReportPayload>()]);
+        Ok(Self::QUOTE_SIZE)
+    }
+}
+
+/// [`Executor`] executing SGX quote Enarx calls using a [`QuoteProvider`] and passing all
+/// other items to another executor.
+///
+/// [`Hello`](crate::item::Hello) items are answered with the [`reply`](Executor::reply) of
+/// the other executor extended by the SGX quote Enarx calls.
+#[derive(Debug)]
+pub struct Attestation<Q, E> {
+    provider: Q,
+    executor: E,
+}
+
+impl<Q: QuoteProvider, E: Executor> Attestation<Q, E> {
+    /// Creates a new [`Attestation`] requesting quotes from `provider`, which passes all other
+    /// items to `executor`.
+    #[inline]
+    pub fn new(provider: Q, executor: E) -> Self {
+        Self { provider, executor }
+    }
+
+    /// Returns the quote provider and the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> (Q, E) {
+        (self.provider, self.executor)
+    }
+}
+
+impl<Q: QuoteProvider, E: Executor> Executor for Attestation<Q, E> {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        self.executor.syscall(call, data)
+    }
+
+    #[inline]
+    fn offload(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<bool> {
+        self.executor.offload(call, data)
+    }
+
+    #[inline]
+    fn gdbcall(&mut self, call: &mut Gdbcall, data: &mut [u8]) -> Result<()> {
+        self.executor.gdbcall(call, data)
+    }
+
+    fn enarxcall(&mut self, call: &mut Enarxcall, data: &mut [u8]) -> Result<()> {
+        match call.num {
+            Number::GetSgxTargetInfo => {
+                let info_ptr = unsafe { deref::<u8>(data, call.argv[0], size_of::<TargetInfo>()) }?;
+                let mut info = TargetInfo::default();
+                self.provider.target_info(&mut info)?;
+                // The block is not aligned to the alignment of `TargetInfo`.
+                unsafe {
+                    info_ptr
+                        .copy_from_nonoverlapping(info.as_mut().as_ptr(), size_of::<TargetInfo>())
+                };
+                call.ret = 0;
+            }
+            Number::GetSgxQuoteSize => call.ret = self.provider.quote_size()?,
+            Number::GetSgxQuote => {
+                let [report, quote, quote_len, _] = call.argv;
+                let report = unsafe { deref::<u8>(data, report, size_of::<Report>()) }?;
+                let report = unsafe { report.cast::<Report>().read_unaligned() };
+                let quote = unsafe { &mut *deref_slice::<u8>(data, quote, quote_len)? };
+                call.ret = self.provider.quote(&report, quote)?;
+            }
+            _ => return self.executor.enarxcall(call, data),
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn reply(&self) -> Reply {
+        let mut reply = self.executor.reply();
+        reply.add_kind(Kind::Enarxcall);
+        reply.add_enarxcall(Number::GetSgxQuote);
+        reply.add_enarxcall(Number::GetSgxQuoteSize);
+        reply.add_enarxcall(Number::GetSgxTargetInfo);
+        reply
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{execute_with, Native};
+    use crate::item::Item;
+    use crate::libc::{EFAULT, ENOSYS};
+
+    #[test]
+    fn mock() {
+        let mut info = TargetInfo::default();
+        assert_eq!(MockQuote.target_info(&mut info), Ok(()));
+        assert_eq!(info.mrenclave, MockQuote::MRENCLAVE);
+        assert_eq!(info.attributes, [0b101, 0b11]);
+
+        let mut report = Report::default();
+        report.payload.mrenclave = [0x11; 32];
+        let mut quote = [0xff; MockQuote::QUOTE_SIZE + 1];
+        assert_eq!(
+            MockQuote.quote(&report, &mut quote),
+            Ok(MockQuote::QUOTE_SIZE)
+        );
+        assert_eq!(quote[..4], [3, 0, 2, 0]);
+        assert_eq!(quote[28..HEADER_SIZE], MockQuote::USER_DATA);
+        assert_eq!(quote[HEADER_SIZE + 64..HEADER_SIZE + 96], [0x11; 32]);
+        assert_eq!(quote[MockQuote::QUOTE_SIZE - 4..], [0, 0, 0, 0, 0xff]);
+        assert_eq!(
+            MockQuote.quote(&report, &mut quote[..MockQuote::QUOTE_SIZE - 1]),
+            Err(EMSGSIZE)
+        );
+    }
+
+    #[test]
+    fn attestation() {
+        let mut attestation = Attestation::new(MockQuote, Native);
+        assert!(attestation
+            .reply()
+            .supports_enarxcall(Number::GetSgxQuoteSize));
+
+        let mut size = Enarxcall {
+            num: Number::GetSgxQuoteSize,
+            argv: [0; 4],
+            ret: -ENOSYS as _,
+        };
+        let mut info = Enarxcall {
+            num: Number::GetSgxTargetInfo,
+            argv: [1, 0, 0, 0],
+            ret: -ENOSYS as _,
+        };
+        let mut info_data = [0; size_of::<TargetInfo>() + 1];
+        let mut quote = Enarxcall {
+            num: Number::GetSgxQuote,
+            argv: [0, size_of::<Report>(), MockQuote::QUOTE_SIZE, 0],
+            ret: -ENOSYS as _,
+        };
+        let mut quote_data = [0; size_of::<Report>() + MockQuote::QUOTE_SIZE];
+        let mut fault = Enarxcall {
+            num: Number::GetSgxTargetInfo,
+            argv: [2, 0, 0, 0],
+            ret: -ENOSYS as _,
+        };
+        assert_eq!(
+            execute_with(
+                &mut attestation,
+                [
+                    Item::Enarxcall(&mut size, &mut []),
+                    Item::Enarxcall(&mut info, &mut info_data),
+                    Item::Enarxcall(&mut quote, &mut quote_data),
+                    Item::Enarxcall(&mut fault, &mut [0; size_of::<TargetInfo>()]),
+                ]
+            ),
+            Ok(())
+        );
+        assert_eq!(size.ret, MockQuote::QUOTE_SIZE);
+        assert_eq!(info.ret, 0);
+        assert_eq!(info_data[1..33], MockQuote::MRENCLAVE);
+        assert_eq!(quote.ret, MockQuote::QUOTE_SIZE);
+        assert_eq!(fault.ret, -EFAULT as _);
+    }
+}
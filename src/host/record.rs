@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::execute;
+use crate::item::record::{write_block, write_header};
+use crate::item::Block;
+use crate::Result;
+
+/// Recorder of the blocks executed by the host in the [recording format](crate::item::record).
+///
+/// The recording is written into the `sink`, which is called with consecutive chunks of bytes.
+#[derive(Debug)]
+pub struct Recorder<F> {
+    sink: F,
+}
+
+impl<F: FnMut(&[u8]) -> Result<()>> Recorder<F> {
+    /// Creates a new [`Recorder`] and writes the recording header into `sink`.
+    #[inline]
+    pub fn new(mut sink: F) -> Result<Self> {
+        write_header(&mut sink)?;
+        Ok(Self { sink })
+    }
+
+    /// Records the contents of `block`, [executes](execute) it and records the contents
+    /// of `block` after execution.
+    ///
+    /// Errors returned by the sink take precedence over the result of execution.
+    pub fn execute(&mut self, block: &mut [usize]) -> Result<()> {
+        write_block(&mut self.sink, block)?;
+        let ret = execute(Block::from(&mut *block));
+        write_block(&mut self.sink, block)?;
+        ret
+    }
+
+    /// Returns the `sink`.
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.sink
+    }
+}
@@ -0,0 +1,1796 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{deref_aligned, deref_aligned_slice, deref_slice, Executor, Sockaddr};
+use crate::item::hello::Reply;
+use crate::item::{Enarxcall, Kind, Syscall};
+use crate::libc::{
+    epoll_event, iovec, pollfd, socklen_t, timespec, SYS_accept, SYS_accept4, SYS_bind,
+    SYS_clock_getres, SYS_clock_gettime, SYS_close, SYS_connect, SYS_dup, SYS_dup2, SYS_dup3,
+    SYS_epoll_create1, SYS_epoll_ctl, SYS_epoll_pwait, SYS_epoll_wait, SYS_eventfd2, SYS_exit,
+    SYS_exit_group, SYS_fcntl, SYS_getsockname, SYS_ioctl, SYS_listen, SYS_nanosleep, SYS_open,
+    SYS_poll, SYS_read, SYS_readv, SYS_recvfrom, SYS_sendto, SYS_setsockopt, SYS_socket, SYS_sync,
+    SYS_write, SYS_writev, AF_INET, EADDRINUSE, EADDRNOTAVAIL, EAFNOSUPPORT, EAGAIN, EBADF,
+    ECONNREFUSED, EDESTADDRREQ, EEXIST, EFD_CLOEXEC, EFD_NONBLOCK, EFD_SEMAPHORE, EINVAL, EISCONN,
+    EMFILE, EMSGSIZE, ENETUNREACH, ENFILE, ENOENT, ENOMEM, ENOSPC, ENOSYS, ENOTCONN, ENOTSOCK,
+    ENOTSUP, ENOTTY, EPIPE, EPOLL_CLOEXEC, EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD, FD_CLOEXEC,
+    FIONBIO, FIONREAD, F_DUPFD, F_DUPFD_CLOEXEC, F_GETFD, F_GETFL, F_SETFD, F_SETFL, MSG_PEEK,
+    O_ACCMODE, O_APPEND, O_CLOEXEC, O_NONBLOCK, O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY, POLLERR,
+    POLLHUP, POLLIN, POLLNVAL, POLLOUT, SOCK_CLOEXEC, SOCK_DGRAM, SOCK_NONBLOCK, SOCK_STREAM,
+    UIO_MAXIOV,
+};
+use crate::{Result, NULL, VERSION};
+
+use core::ffi::{c_int, c_long, c_short};
+use core::mem::size_of;
+
+/// Capacity of the buffers of pipes and sockets of [`SimKernel`] in bytes.
+pub const SIM_BUFFER_SIZE: usize = 4096;
+
+/// Count of pending connections queued by a listening socket.
+const BACKLOG: usize = 8;
+
+/// Count of file descriptors an epoll instance can watch.
+const INTEREST: usize = 8;
+
+/// First port assigned to sockets bound to port `0`.
+const EPHEMERAL_PORT: u16 = 32768;
+
+/// Loopback address all sockets are bound to.
+const LOOPBACK: [u8; 4] = [127, 0, 0, 1];
+
+/// Size of the header preceding each datagram in a [`Queue`].
+const DATAGRAM_HEADER: usize = 2 * size_of::<u16>();
+
+/// Count of nanoseconds per second.
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+
+/// Syscalls supported by [`SimKernel`], sorted alphabetically.
+const SYSCALLS: &[c_long] = &[
+    SYS_accept,
+    SYS_accept4,
+    SYS_bind,
+    SYS_clock_getres,
+    SYS_clock_gettime,
+    SYS_close,
+    SYS_connect,
+    SYS_dup,
+    SYS_dup2,
+    SYS_dup3,
+    SYS_epoll_create1,
+    SYS_epoll_ctl,
+    SYS_epoll_pwait,
+    SYS_epoll_wait,
+    SYS_eventfd2,
+    SYS_exit,
+    SYS_exit_group,
+    SYS_fcntl,
+    SYS_getsockname,
+    SYS_ioctl,
+    SYS_listen,
+    SYS_nanosleep,
+    SYS_open,
+    SYS_poll,
+    SYS_read,
+    SYS_readv,
+    SYS_recvfrom,
+    SYS_sendto,
+    SYS_setsockopt,
+    SYS_socket,
+    SYS_sync,
+    SYS_write,
+    SYS_writev,
+];
+
+/// Returns `len` bytes at `offset` within `data`.
+#[inline]
+fn bytes(data: &mut [u8], offset: usize, len: usize) -> Result<&mut [u8]> {
+    Ok(unsafe { &mut *deref_slice::<u8>(data, offset, len)? })
+}
+
+/// Reads a `T` at `offset` within `data`.
+#[inline]
+fn load<T: Copy>(data: &mut [u8], offset: usize) -> Result<T> {
+    deref_aligned::<T>(data, offset, 1).map(|ptr| unsafe { ptr.read() })
+}
+
+/// Writes `val` at `offset` within `data`.
+#[inline]
+fn store<T>(data: &mut [u8], offset: usize, val: T) -> Result<()> {
+    deref_aligned::<T>(data, offset, 1).map(|ptr| unsafe { ptr.write(val) })
+}
+
+/// Decodes the port of a loopback IPv4 address of `addrlen` bytes at `offset` within `data`.
+///
+/// The wildcard address is accepted if `any` is `true`, other addresses fail with `unreachable`.
+fn decode_port(
+    data: &mut [u8],
+    offset: usize,
+    addrlen: usize,
+    any: bool,
+    unreachable: c_int,
+) -> Result<u16> {
+    bytes(data, offset, addrlen)?;
+    match Sockaddr::decode(data, offset, addrlen).ok_or(EINVAL)? {
+        Sockaddr::Inet { addr, port } if addr == LOOPBACK || any && addr == [0; 4] => Ok(port),
+        Sockaddr::Inet { .. } => Err(unreachable),
+        _ => Err(EAFNOSUPPORT),
+    }
+}
+
+/// Writes the loopback IPv4 address with `port` at `offset` within `data` truncated to the
+/// [`socklen_t`] at `addrlen_offset`, which is set to the size of the address.
+fn encode_port(data: &mut [u8], offset: usize, addrlen_offset: usize, port: u16) -> Result<()> {
+    let addr = match port {
+        0 => [0; 4],
+        _ => LOOPBACK,
+    };
+    let mut buf = [0; 16];
+    let len = Sockaddr::Inet { addr, port }
+        .encode(&mut buf)
+        .ok_or(EINVAL)?;
+    let addrlen = load::<socklen_t>(data, addrlen_offset)? as usize;
+    let n = addrlen.min(len);
+    bytes(data, offset, n)?.copy_from_slice(&buf[..n]);
+    store(data, addrlen_offset, len as socklen_t)
+}
+
+/// Ring buffer of bytes backing pipes and sockets.
+#[derive(Clone, Copy, Debug)]
+struct Queue {
+    buf: [u8; SIM_BUFFER_SIZE],
+    head: usize,
+    len: usize,
+
+    /// Whether the reading end is open.
+    reader: bool,
+
+    /// Whether the writing end is open.
+    writer: bool,
+}
+
+impl Queue {
+    const fn new() -> Self {
+        Self {
+            buf: [0; SIM_BUFFER_SIZE],
+            head: 0,
+            len: 0,
+            reader: true,
+            writer: true,
+        }
+    }
+
+    fn free(&self) -> usize {
+        SIM_BUFFER_SIZE - self.len
+    }
+
+    /// Appends as many bytes of `bytes` as fit and returns their count.
+    fn push(&mut self, bytes: &[u8]) -> usize {
+        let n = bytes.len().min(self.free());
+        for (i, b) in bytes[..n].iter().enumerate() {
+            self.buf[(self.head + self.len + i) % SIM_BUFFER_SIZE] = *b;
+        }
+        self.len += n;
+        n
+    }
+
+    /// Copies bytes starting `skip` bytes after the head into `buf` without consuming them
+    /// and returns their count.
+    fn peek(&self, skip: usize, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.len.saturating_sub(skip));
+        for (i, b) in buf[..n].iter_mut().enumerate() {
+            *b = self.buf[(self.head + skip + i) % SIM_BUFFER_SIZE];
+        }
+        n
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.head = (self.head + n) % SIM_BUFFER_SIZE;
+        self.len -= n;
+    }
+
+    /// Reads bytes into `buf`, consuming them unless `peek` is `true`.
+    fn read(&mut self, buf: &mut [u8], peek: bool) -> Result<usize> {
+        if self.len == 0 && !buf.is_empty() {
+            return if self.writer { Err(EAGAIN) } else { Ok(0) };
+        }
+        let n = self.peek(0, buf);
+        if !peek {
+            self.consume(n);
+        }
+        Ok(n)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if !self.reader {
+            Err(EPIPE)
+        } else if self.free() == 0 && !buf.is_empty() {
+            Err(EAGAIN)
+        } else {
+            Ok(self.push(buf))
+        }
+    }
+
+    /// Returns the source port and the length of the next datagram.
+    fn next_datagram(&self) -> Option<(u16, usize)> {
+        let mut header = [0; DATAGRAM_HEADER];
+        if self.peek(0, &mut header) < DATAGRAM_HEADER {
+            return None;
+        }
+        let port = u16::from_ne_bytes([header[0], header[1]]);
+        let len = u16::from_ne_bytes([header[2], header[3]]);
+        Some((port, len as _))
+    }
+
+    /// Appends a datagram sent from `port`. Datagrams, which do not fit, are dropped.
+    fn send(&mut self, port: u16, buf: &[u8]) {
+        if self.free() >= DATAGRAM_HEADER + buf.len() {
+            self.push(&port.to_ne_bytes());
+            self.push(&(buf.len() as u16).to_ne_bytes());
+            self.push(buf);
+        }
+    }
+
+    /// Receives the next datagram truncated to `buf` and returns its length and source port.
+    fn recv(&mut self, buf: &mut [u8], peek: bool) -> Result<(usize, u16)> {
+        let (port, len) = self.next_datagram().ok_or(EAGAIN)?;
+        let n = buf.len().min(len);
+        let n = self.peek(DATAGRAM_HEADER, &mut buf[..n]);
+        if !peek {
+            self.consume(DATAGRAM_HEADER + len);
+        }
+        Ok((n, port))
+    }
+}
+
+/// Entry of the interest list of an epoll instance.
+#[derive(Clone, Copy, Debug)]
+struct Interest {
+    fd: c_int,
+    event: epoll_event,
+}
+
+/// State of a simulated socket.
+#[derive(Clone, Copy, Debug)]
+enum Socket {
+    /// Stream socket, which is neither listening nor connected, bound to `port` unless `0`.
+    Stream { port: u16 },
+
+    /// Listening stream socket with connections waiting to be accepted.
+    Listener {
+        port: u16,
+        backlog: [Option<usize>; BACKLOG],
+    },
+
+    /// Connected stream socket receiving from queue `rx` and sending to queue `tx`.
+    Connected {
+        port: u16,
+        peer: u16,
+        rx: usize,
+        tx: usize,
+    },
+
+    /// Datagram socket receiving from queue `rx` once bound and sending to `peer` by default
+    /// unless `0`.
+    Datagram {
+        port: u16,
+        peer: u16,
+        rx: Option<usize>,
+    },
+}
+
+impl Socket {
+    fn port(&self) -> u16 {
+        match *self {
+            Self::Stream { port }
+            | Self::Listener { port, .. }
+            | Self::Connected { port, .. }
+            | Self::Datagram { port, .. } => port,
+        }
+    }
+}
+
+/// Object referenced by an open file description.
+#[derive(Clone, Copy, Debug)]
+enum Object {
+    File {
+        file: usize,
+        offset: usize,
+        access: c_int,
+        append: bool,
+    },
+    Reader(usize),
+    Writer(usize),
+    EventFd {
+        counter: u64,
+        semaphore: bool,
+    },
+    Socket(Socket),
+    Epoll([Option<Interest>; INTEREST]),
+}
+
+/// Open file description shared by duplicated file descriptors.
+#[derive(Clone, Copy, Debug)]
+struct Description {
+    object: Object,
+    nonblock: bool,
+    refs: usize,
+}
+
+/// Entry of the file descriptor table.
+#[derive(Clone, Copy, Debug)]
+struct Fd {
+    description: usize,
+    cloexec: bool,
+}
+
+/// Regular file registered with [`SimKernel::add_file`].
+#[derive(Debug)]
+struct File<'a> {
+    path: &'a [u8],
+    data: &'a mut [u8],
+    len: usize,
+}
+
+/// [`Executor`] executing syscalls against an in-memory model of a host kernel instead of the
+/// host, so that guest code can be tested deterministically and under Miri.
+///
+/// The model consists of up to `N` file descriptors, open file descriptions, pipe or socket
+/// buffers of [`SIM_BUFFER_SIZE`] bytes and regular files and provides:
+///
+/// - regular files [registered](SimKernel::add_file) by the host backed by caller-provided
+///   storage, which cannot grow and cannot be created by the guest,
+/// - pipes created by the host with [`pipe`](SimKernel::pipe),
+/// - event file descriptors,
+/// - stream and datagram IPv4 sockets connected over the loopback interface,
+/// - level-triggered epoll instances, which cannot be nested, and `poll`,
+/// - a fake clock backing all clocks, which only advances on
+///   [`advance`](SimKernel::advance), `nanosleep` and waits timing out.
+///
+/// The simulated kernel never blocks: operations, which would block, fail with [`EAGAIN`] and
+/// waits without ready file descriptors time out immediately. Syscalls not supported by the
+/// model fail with [`ENOSYS`] and so do Enarx calls.
+#[derive(Debug)]
+pub struct SimKernel<'a, const N: usize> {
+    fds: [Option<Fd>; N],
+    descriptions: [Option<Description>; N],
+    queues: [Option<Queue>; N],
+    files: [Option<File<'a>>; N],
+    now: u64,
+    next_port: u16,
+    exit_status: Option<c_int>,
+}
+
+impl<const N: usize> Default for SimKernel<'_, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> SimKernel<'a, N> {
+    /// Creates a new [`SimKernel`] without open file descriptors, with no files registered
+    /// and with the clock at `0`.
+    pub fn new() -> Self {
+        Self {
+            fds: [None; N],
+            descriptions: [None; N],
+            queues: [None; N],
+            files: core::array::from_fn(|_| None),
+            now: 0,
+            next_port: EPHEMERAL_PORT,
+            exit_status: None,
+        }
+    }
+
+    /// Registers a regular file at `path` backed by `storage` and containing the first `len`
+    /// bytes of it.
+    ///
+    /// Fails with [`EINVAL`] if `len` exceeds `storage`, with [`EEXIST`] if `path` is
+    /// registered already and with [`ENOSPC`] if `N` files are registered already.
+    pub fn add_file(&mut self, path: &'a [u8], storage: &'a mut [u8], len: usize) -> Result<()> {
+        if len > storage.len() {
+            return Err(EINVAL);
+        }
+        if self.find_file(path).is_some() {
+            return Err(EEXIST);
+        }
+        let free = self.files.iter_mut().find(|f| f.is_none()).ok_or(ENOSPC)?;
+        *free = Some(File {
+            path,
+            data: storage,
+            len,
+        });
+        Ok(())
+    }
+
+    /// Returns the contents of the file registered at `path`.
+    pub fn file(&self, path: &[u8]) -> Option<&[u8]> {
+        self.find_file(path)
+            .and_then(|i| self.files[i].as_ref())
+            .map(|file| &file.data[..file.len])
+    }
+
+    /// Creates a pipe akin to `pipe2` with `flags` and returns the file descriptors of its
+    /// reading and writing end.
+    pub fn pipe(&mut self, flags: c_int) -> Result<[c_int; 2]> {
+        if flags & !(O_CLOEXEC | O_NONBLOCK) != 0 {
+            return Err(EINVAL);
+        }
+        let queue = self.alloc_queue()?;
+        let reader = self.open(Object::Reader(queue), flags);
+        let writer = reader.and_then(|reader| {
+            self.open(Object::Writer(queue), flags)
+                .inspect_err(|_| self.close(reader).unwrap())
+        });
+        if writer.is_err() {
+            self.queues[queue] = None;
+        }
+        Ok([reader?, writer?])
+    }
+
+    /// Reads from `fd` into `buf` akin to `read`.
+    #[inline]
+    pub fn read(&mut self, fd: c_int, buf: &mut [u8]) -> Result<usize> {
+        self.recv(fd, buf, 0).map(|(n, _)| n)
+    }
+
+    /// Writes `buf` to `fd` akin to `write`.
+    #[inline]
+    pub fn write(&mut self, fd: c_int, buf: &[u8]) -> Result<usize> {
+        self.send(fd, buf, None)
+    }
+
+    /// Closes `fd` akin to `close`.
+    pub fn close(&mut self, fd: c_int) -> Result<()> {
+        let entry = self.fd(fd)?;
+        self.fds[fd as usize] = None;
+        for description in self.descriptions.iter_mut().flatten() {
+            if let Object::Epoll(interest) = &mut description.object {
+                interest
+                    .iter_mut()
+                    .filter(|i| matches!(i, Some(i) if i.fd == fd))
+                    .for_each(|i| *i = None);
+            }
+        }
+        self.release(entry.description);
+        Ok(())
+    }
+
+    /// Returns the time of the fake clock in nanoseconds.
+    #[inline]
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Advances the fake clock by `nsec` nanoseconds.
+    #[inline]
+    pub fn advance(&mut self, nsec: u64) {
+        self.now = self.now.saturating_add(nsec)
+    }
+
+    /// Returns the status passed to `exit` or `exit_group` by the guest, if any.
+    #[inline]
+    pub fn exit_status(&self) -> Option<c_int> {
+        self.exit_status
+    }
+
+    fn find_file(&self, path: &[u8]) -> Option<usize> {
+        self.files
+            .iter()
+            .position(|f| matches!(f, Some(f) if f.path == path))
+    }
+
+    fn alloc_queue(&mut self) -> Result<usize> {
+        let i = self.queues.iter().position(|q| q.is_none()).ok_or(ENOMEM)?;
+        self.queues[i] = Some(Queue::new());
+        Ok(i)
+    }
+
+    fn queue(&mut self, i: usize) -> &mut Queue {
+        self.queues[i].as_mut().unwrap()
+    }
+
+    /// Frees queue `i` once both of its ends are closed.
+    fn collect_queue(&mut self, i: usize) {
+        if matches!(
+            self.queues[i],
+            Some(Queue {
+                reader: false,
+                writer: false,
+                ..
+            })
+        ) {
+            self.queues[i] = None;
+        }
+    }
+
+    fn fd(&self, fd: c_int) -> Result<Fd> {
+        usize::try_from(fd)
+            .ok()
+            .and_then(|fd| self.fds.get(fd).copied().flatten())
+            .ok_or(EBADF)
+    }
+
+    fn description(&mut self, fd: c_int) -> Result<&mut Description> {
+        let i = self.fd(fd)?.description;
+        Ok(self.descriptions[i].as_mut().unwrap())
+    }
+
+    fn object(&mut self, fd: c_int) -> Result<&mut Object> {
+        self.description(fd).map(|d| &mut d.object)
+    }
+
+    fn socket(&mut self, fd: c_int) -> Result<&mut Socket> {
+        match self.object(fd)? {
+            Object::Socket(socket) => Ok(socket),
+            _ => Err(ENOTSOCK),
+        }
+    }
+
+    /// Creates a new open file description of `object` with `refs` references.
+    fn alloc_description(&mut self, object: Object, nonblock: bool, refs: usize) -> Result<usize> {
+        let i = self
+            .descriptions
+            .iter()
+            .position(|d| d.is_none())
+            .ok_or(ENFILE)?;
+        self.descriptions[i] = Some(Description {
+            object,
+            nonblock,
+            refs,
+        });
+        Ok(i)
+    }
+
+    /// Installs a new reference to `description` as the lowest free file descriptor of at
+    /// least `min`.
+    fn install(&mut self, description: usize, min: usize, cloexec: bool) -> Result<c_int> {
+        let fd = (min..N).find(|fd| self.fds[*fd].is_none()).ok_or(EMFILE)?;
+        self.fds[fd] = Some(Fd {
+            description,
+            cloexec,
+        });
+        Ok(fd as _)
+    }
+
+    /// Opens a new file descriptor of `object` with `O_CLOEXEC` and `O_NONBLOCK` in `flags`.
+    fn open(&mut self, object: Object, flags: c_int) -> Result<c_int> {
+        let description = self.alloc_description(object, flags & O_NONBLOCK != 0, 1)?;
+        self.install(description, 0, flags & O_CLOEXEC != 0)
+            .inspect_err(|_| self.descriptions[description] = None)
+    }
+
+    /// Drops a reference to `description` and releases its object with the last reference.
+    fn release(&mut self, description: usize) {
+        let d = self.descriptions[description].as_mut().unwrap();
+        d.refs -= 1;
+        if d.refs > 0 {
+            return;
+        }
+        let object = d.object;
+        self.descriptions[description] = None;
+        match object {
+            Object::Reader(q) => {
+                self.queue(q).reader = false;
+                self.collect_queue(q);
+            }
+            Object::Writer(q) => {
+                self.queue(q).writer = false;
+                self.collect_queue(q);
+            }
+            Object::Socket(Socket::Connected { rx, tx, .. }) => {
+                self.queue(rx).reader = false;
+                self.queue(tx).writer = false;
+                self.collect_queue(rx);
+                self.collect_queue(tx);
+            }
+            Object::Socket(Socket::Datagram { rx: Some(q), .. }) => self.queues[q] = None,
+            Object::Socket(Socket::Listener { backlog, .. }) => {
+                backlog.into_iter().flatten().for_each(|d| self.release(d))
+            }
+            _ => {}
+        }
+    }
+
+    /// Duplicates `fd` as the lowest free file descriptor of at least `min`.
+    fn dup(&mut self, fd: c_int, min: usize, cloexec: bool) -> Result<c_int> {
+        let description = self.fd(fd)?.description;
+        let new = self.install(description, min, cloexec)?;
+        self.descriptions[description].as_mut().unwrap().refs += 1;
+        Ok(new)
+    }
+
+    /// Duplicates `fd` as `new`, closing `new` first if it is open.
+    fn dup_to(&mut self, fd: c_int, new: c_int, cloexec: bool) -> Result<c_int> {
+        let description = self.fd(fd)?.description;
+        let slot = usize::try_from(new).ok().filter(|n| *n < N).ok_or(EBADF)?;
+        if self.fds[slot].is_some() {
+            self.close(new)?;
+        }
+        self.fds[slot] = Some(Fd {
+            description,
+            cloexec,
+        });
+        self.descriptions[description].as_mut().unwrap().refs += 1;
+        Ok(new)
+    }
+
+    /// Returns whether a datagram socket, if `datagram` is `true`, or a stream socket otherwise
+    /// is bound to `port`.
+    fn bound(&self, port: u16, datagram: bool) -> bool {
+        self.descriptions.iter().flatten().any(|d| match d.object {
+            Object::Socket(Socket::Datagram { port: p, .. }) => datagram && p == port,
+            Object::Socket(socket) => !datagram && socket.port() == port,
+            _ => false,
+        })
+    }
+
+    /// Returns `port` or a free ephemeral port if `port` is `0`.
+    fn assign_port(&mut self, port: u16, datagram: bool) -> Result<u16> {
+        if port != 0 {
+            return match self.bound(port, datagram) {
+                true => Err(EADDRINUSE),
+                false => Ok(port),
+            };
+        }
+        for _ in EPHEMERAL_PORT..=u16::MAX {
+            let port = self.next_port;
+            self.next_port = port.checked_add(1).unwrap_or(EPHEMERAL_PORT);
+            if !self.bound(port, datagram) {
+                return Ok(port);
+            }
+        }
+        Err(EADDRINUSE)
+    }
+
+    /// Returns the receive queue of the datagram socket bound to `port`.
+    fn datagram_queue(&self, port: u16) -> Option<usize> {
+        self.descriptions
+            .iter()
+            .flatten()
+            .find_map(|d| match d.object {
+                Object::Socket(Socket::Datagram { port: p, rx, .. }) if p == port => rx,
+                _ => None,
+            })
+    }
+
+    /// Binds the datagram socket `fd` to `port`, allocating its receive queue.
+    fn bind_datagram(&mut self, fd: c_int, port: u16) -> Result<u16> {
+        let port = self.assign_port(port, true)?;
+        let queue = self.alloc_queue()?;
+        if let Socket::Datagram { port: p, rx: r, .. } = self.socket(fd)? {
+            (*p, *r) = (port, Some(queue));
+        }
+        Ok(port)
+    }
+
+    /// Returns the events `fd` is ready for as `poll` flags.
+    fn events(&self, fd: c_int) -> Result<c_short> {
+        let description = self.fd(fd)?.description;
+        let queue = |q: usize| self.queues[q].as_ref().unwrap();
+        let stream = |rx: &Queue, tx: &Queue| {
+            let mut events = 0;
+            if rx.len > 0 || !rx.writer {
+                events |= POLLIN;
+            }
+            if tx.free() > 0 && tx.reader {
+                events |= POLLOUT;
+            }
+            if !tx.reader {
+                events |= POLLERR;
+            }
+            if !rx.writer && !tx.reader {
+                events |= POLLHUP;
+            }
+            events
+        };
+        Ok(match self.descriptions[description].unwrap().object {
+            Object::File { .. } => POLLIN | POLLOUT,
+            Object::Reader(q) => {
+                let q = queue(q);
+                match (q.len > 0, q.writer) {
+                    (true, _) => POLLIN,
+                    (false, true) => 0,
+                    (false, false) => POLLHUP,
+                }
+            }
+            Object::Writer(q) => match queue(q) {
+                q if !q.reader => POLLERR,
+                q if q.free() > 0 => POLLOUT,
+                _ => 0,
+            },
+            Object::EventFd { counter, .. } => {
+                let mut events = 0;
+                if counter > 0 {
+                    events |= POLLIN;
+                }
+                if counter < u64::MAX - 1 {
+                    events |= POLLOUT;
+                }
+                events
+            }
+            Object::Socket(Socket::Stream { .. }) => POLLOUT | POLLHUP,
+            Object::Socket(Socket::Listener { backlog, .. }) => match backlog[0] {
+                Some(_) => POLLIN,
+                None => 0,
+            },
+            Object::Socket(Socket::Connected { rx, tx, .. }) => stream(queue(rx), queue(tx)),
+            Object::Socket(Socket::Datagram { rx, .. }) => match rx {
+                Some(q) if queue(q).len > 0 => POLLIN | POLLOUT,
+                _ => POLLOUT,
+            },
+            Object::Epoll(interest) => match interest.iter().flatten().any(|i| {
+                self.events(i.fd)
+                    .is_ok_and(|e| e as u32 & (i.event.events | (POLLERR | POLLHUP) as u32) != 0)
+            }) {
+                true => POLLIN,
+                false => 0,
+            },
+        })
+    }
+
+    /// Reads from `fd` into `buf` with `recvfrom` `flags` and returns the count of bytes read
+    /// and the source port of datagrams.
+    fn recv(&mut self, fd: c_int, buf: &mut [u8], flags: c_int) -> Result<(usize, u16)> {
+        let peek = flags & MSG_PEEK != 0;
+        let object = *self.object(fd)?;
+        match object {
+            Object::File {
+                file,
+                offset,
+                access,
+                ..
+            } => {
+                if access == O_WRONLY {
+                    return Err(EBADF);
+                }
+                let file = self.files[file].as_ref().unwrap();
+                let contents = file.data[..file.len].get(offset..).unwrap_or(&[]);
+                let n = contents.len().min(buf.len());
+                buf[..n].copy_from_slice(&contents[..n]);
+                if let Object::File { offset, .. } = self.object(fd)? {
+                    *offset += n;
+                }
+                Ok((n, 0))
+            }
+            Object::Reader(q) => self.queue(q).read(buf, peek).map(|n| (n, 0)),
+            Object::Writer(_) => Err(EBADF),
+            Object::EventFd { counter, semaphore } => {
+                let buf = buf.get_mut(..size_of::<u64>()).ok_or(EINVAL)?;
+                if counter == 0 {
+                    return Err(EAGAIN);
+                }
+                let value = if semaphore { 1 } else { counter };
+                buf.copy_from_slice(&value.to_ne_bytes());
+                if let Object::EventFd { counter, .. } = self.object(fd)? {
+                    *counter -= value;
+                }
+                Ok((buf.len(), 0))
+            }
+            Object::Socket(Socket::Connected { rx, peer, .. }) => {
+                self.queue(rx).read(buf, peek).map(|n| (n, peer))
+            }
+            Object::Socket(Socket::Datagram { rx: Some(q), .. }) => self.queue(q).recv(buf, peek),
+            Object::Socket(Socket::Datagram { rx: None, .. }) => Err(EAGAIN),
+            Object::Socket(_) => Err(ENOTCONN),
+            Object::Epoll(_) => Err(EINVAL),
+        }
+    }
+
+    /// Writes `buf` to `fd` or, for datagram sockets, sends it to port `dest`.
+    fn send(&mut self, fd: c_int, buf: &[u8], dest: Option<u16>) -> Result<usize> {
+        let object = *self.object(fd)?;
+        match object {
+            Object::File {
+                file,
+                offset,
+                access,
+                append,
+            } => {
+                if access == O_RDONLY {
+                    return Err(EBADF);
+                }
+                let file = self.files[file].as_mut().unwrap();
+                let start = if append { file.len } else { offset };
+                let n = file.data.len().saturating_sub(start).min(buf.len());
+                if n == 0 && !buf.is_empty() {
+                    return Err(ENOSPC);
+                }
+                if start > file.len {
+                    file.data[file.len..start].fill(0);
+                }
+                file.data[start..start + n].copy_from_slice(&buf[..n]);
+                file.len = file.len.max(start + n);
+                if let Object::File { offset, .. } = self.object(fd)? {
+                    *offset = start + n;
+                }
+                Ok(n)
+            }
+            Object::Reader(_) => Err(EBADF),
+            Object::Writer(q) => self.queue(q).write(buf),
+            Object::EventFd { counter, .. } => {
+                let value = buf.get(..size_of::<u64>()).ok_or(EINVAL)?;
+                let value = u64::from_ne_bytes(value.try_into().unwrap());
+                if value == u64::MAX {
+                    return Err(EINVAL);
+                }
+                if value > u64::MAX - 1 - counter {
+                    return Err(EAGAIN);
+                }
+                if let Object::EventFd { counter, .. } = self.object(fd)? {
+                    *counter += value;
+                }
+                Ok(size_of::<u64>())
+            }
+            Object::Socket(Socket::Connected { tx, .. }) => self.queue(tx).write(buf),
+            Object::Socket(Socket::Datagram { port, peer, .. }) => {
+                let dest = dest.or((peer != 0).then_some(peer)).ok_or(EDESTADDRREQ)?;
+                if buf.len() > SIM_BUFFER_SIZE - DATAGRAM_HEADER {
+                    return Err(EMSGSIZE);
+                }
+                let port = match port {
+                    0 => self.bind_datagram(fd, 0)?,
+                    port => port,
+                };
+                // Datagrams sent to unbound ports are lost.
+                if let Some(q) = self.datagram_queue(dest) {
+                    self.queue(q).send(port, buf);
+                }
+                Ok(buf.len())
+            }
+            Object::Socket(_) => Err(EPIPE),
+            Object::Epoll(_) => Err(EINVAL),
+        }
+    }
+
+    /// Connects the stream socket `fd` to the socket listening on `port`.
+    fn connect_stream(&mut self, fd: c_int, port: u16) -> Result<()> {
+        let listener = self
+            .descriptions
+            .iter()
+            .position(|d| {
+                matches!(d, Some(Description {
+                    object: Object::Socket(Socket::Listener { port: p, backlog }),
+                    ..
+                }) if *p == port && backlog[BACKLOG - 1].is_none())
+            })
+            .ok_or(ECONNREFUSED)?;
+        let local = match self.socket(fd)?.port() {
+            0 => self.assign_port(0, false)?,
+            local => local,
+        };
+
+        let rx = self.alloc_queue()?;
+        let tx = self.alloc_queue().inspect_err(|_| self.queues[rx] = None)?;
+        let server = Socket::Connected {
+            port,
+            peer: local,
+            rx: tx,
+            tx: rx,
+        };
+        let server = self
+            .alloc_description(Object::Socket(server), false, 1)
+            .inspect_err(|_| (self.queues[rx], self.queues[tx]) = (None, None))?;
+        if let Some(Description {
+            object: Object::Socket(Socket::Listener { backlog, .. }),
+            ..
+        }) = &mut self.descriptions[listener]
+        {
+            *backlog.iter_mut().find(|c| c.is_none()).unwrap() = Some(server);
+        }
+        *self.socket(fd)? = Socket::Connected {
+            port: local,
+            peer: port,
+            rx,
+            tx,
+        };
+        Ok(())
+    }
+
+    /// Accepts a connection on the listening socket `fd` with `accept4` `flags` and returns
+    /// the new file descriptor and the port of the peer.
+    fn accept(&mut self, fd: c_int, flags: c_int) -> Result<(c_int, u16)> {
+        if flags & !(SOCK_CLOEXEC | SOCK_NONBLOCK) != 0 {
+            return Err(EINVAL);
+        }
+        let Socket::Listener { backlog, .. } = self.socket(fd)? else {
+            return Err(EINVAL);
+        };
+        let description = backlog[0].ok_or(EAGAIN)?;
+        let new = self.install(description, 0, flags & SOCK_CLOEXEC != 0)?;
+        if let Socket::Listener { backlog, .. } = self.socket(fd)? {
+            backlog.rotate_left(1);
+            backlog[BACKLOG - 1] = None;
+        }
+        let d = self.descriptions[description].as_mut().unwrap();
+        d.nonblock = flags & SOCK_NONBLOCK != 0;
+        match d.object {
+            Object::Socket(Socket::Connected { peer, .. }) => Ok((new, peer)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Waits for the file descriptors to get ready. As nothing can happen during the wait,
+    /// the clock is advanced by `timeout` milliseconds.
+    fn wait(&mut self, timeout: c_int) {
+        if timeout > 0 {
+            self.advance(timeout as u64 * 1_000_000);
+        }
+    }
+
+    fn epoll_ctl(&mut self, epfd: c_int, op: c_int, fd: c_int, event: epoll_event) -> Result<()> {
+        // Nested epoll instances are not supported.
+        if matches!(self.object(fd)?, Object::Epoll(_))
+            || !matches!(self.object(epfd)?, Object::Epoll(_))
+        {
+            return Err(EINVAL);
+        }
+        let Object::Epoll(interest) = self.object(epfd)? else {
+            unreachable!()
+        };
+        let entry = interest
+            .iter_mut()
+            .find(|i| matches!(i, Some(i) if i.fd == fd));
+        match (op, entry) {
+            (EPOLL_CTL_ADD, Some(_)) => Err(EEXIST),
+            (EPOLL_CTL_ADD, None) => {
+                let free = interest.iter_mut().find(|i| i.is_none()).ok_or(ENOSPC)?;
+                *free = Some(Interest { fd, event });
+                Ok(())
+            }
+            (EPOLL_CTL_MOD, Some(Some(entry))) => {
+                entry.event = event;
+                Ok(())
+            }
+            (EPOLL_CTL_DEL, Some(entry)) => {
+                *entry = None;
+                Ok(())
+            }
+            (EPOLL_CTL_MOD | EPOLL_CTL_DEL, _) => Err(ENOENT),
+            _ => Err(EINVAL),
+        }
+    }
+
+    fn epoll_wait(
+        &mut self,
+        epfd: c_int,
+        events: &mut [epoll_event],
+        timeout: c_int,
+    ) -> Result<usize> {
+        let Object::Epoll(interest) = *self.object(epfd)? else {
+            return Err(EINVAL);
+        };
+        if events.is_empty() {
+            return Err(EINVAL);
+        }
+        let mut count = 0;
+        for Interest { fd, event } in interest.into_iter().flatten() {
+            let ready = self.events(fd)? as u32 & (event.events | (POLLERR | POLLHUP) as u32);
+            if ready != 0 && count < events.len() {
+                events[count] = epoll_event {
+                    events: ready,
+                    u64: event.u64,
+                };
+                count += 1;
+            }
+        }
+        if count == 0 {
+            self.wait(timeout);
+        }
+        Ok(count)
+    }
+
+    fn poll(&mut self, fds: &mut [pollfd], timeout: c_int) -> usize {
+        let mut count = 0;
+        for fd in fds.iter_mut() {
+            fd.revents = match self.events(fd.fd) {
+                _ if fd.fd < 0 => 0,
+                Ok(events) => events & (fd.events | POLLERR | POLLHUP),
+                Err(_) => POLLNVAL,
+            };
+            count += (fd.revents != 0) as usize;
+        }
+        if count == 0 {
+            self.wait(timeout);
+        }
+        count
+    }
+
+    fn fcntl(&mut self, fd: c_int, cmd: c_int, arg: usize) -> Result<usize> {
+        let entry = self.fd(fd)?;
+        match cmd {
+            F_DUPFD | F_DUPFD_CLOEXEC => {
+                let min = arg as c_int;
+                if min < 0 || min as usize >= N {
+                    return Err(EINVAL);
+                }
+                self.dup(fd, min as _, cmd == F_DUPFD_CLOEXEC)
+                    .map(|fd| fd as _)
+            }
+            F_GETFD => Ok(if entry.cloexec { FD_CLOEXEC as _ } else { 0 }),
+            F_SETFD => {
+                self.fds[fd as usize] = Some(Fd {
+                    cloexec: arg as c_int & FD_CLOEXEC != 0,
+                    ..entry
+                });
+                Ok(0)
+            }
+            F_GETFL => {
+                let description = self.description(fd)?;
+                let flags = match description.object {
+                    Object::File { access, append, .. } => {
+                        access | if append { O_APPEND } else { 0 }
+                    }
+                    Object::Reader(_) => O_RDONLY,
+                    Object::Writer(_) => O_WRONLY,
+                    _ => O_RDWR,
+                };
+                Ok((flags | if description.nonblock { O_NONBLOCK } else { 0 }) as _)
+            }
+            F_SETFL => {
+                let flags = arg as c_int;
+                let description = self.description(fd)?;
+                description.nonblock = flags & O_NONBLOCK != 0;
+                if let Object::File { append, .. } = &mut description.object {
+                    *append = flags & O_APPEND != 0;
+                }
+                Ok(0)
+            }
+            _ => Err(EINVAL),
+        }
+    }
+
+    fn ioctl(&mut self, fd: c_int, request: c_int, data: &mut [u8], argp: usize) -> Result<usize> {
+        match request {
+            FIONBIO => {
+                let nonblock = load::<c_int>(data, argp)? != 0;
+                self.description(fd)?.nonblock = nonblock;
+                Ok(0)
+            }
+            FIONREAD => {
+                let available = match *self.object(fd)? {
+                    Object::File { file, offset, .. } => self.files[file]
+                        .as_ref()
+                        .unwrap()
+                        .len
+                        .saturating_sub(offset),
+                    Object::Reader(q) | Object::Socket(Socket::Connected { rx: q, .. }) => {
+                        self.queue(q).len
+                    }
+                    Object::Socket(Socket::Datagram { rx: Some(q), .. }) => {
+                        self.queue(q).next_datagram().map_or(0, |(_, len)| len)
+                    }
+                    Object::Socket(Socket::Datagram { rx: None, .. }) => 0,
+                    _ => return Err(ENOTTY),
+                };
+                store(data, argp, available as c_int).map(|_| 0)
+            }
+            _ => Err(ENOTTY),
+        }
+    }
+
+    /// Reads from `fd` or, if `write` is set, writes to `fd` akin to `readv` or `writev` using
+    /// the `iovcnt` [`iovec`] elements at `iov` within `data`, whose buffers reside in `data`.
+    ///
+    /// The buffers are transferred at once, so that at most [`SIM_BUFFER_SIZE`] bytes are
+    /// transferred and datagram boundaries are preserved.
+    fn transfer_iovec(
+        &mut self,
+        fd: c_int,
+        data: &mut [u8],
+        iov: usize,
+        iovcnt: usize,
+        write: bool,
+    ) -> Result<usize> {
+        if iovcnt > UIO_MAXIOV as _ {
+            return Err(EINVAL);
+        }
+        deref_aligned_slice::<iovec>(data, iov, iovcnt)?;
+        let iovs = (0..iovcnt).map(|i| iov + i * size_of::<iovec>());
+        let mut len = 0;
+        for offset in iovs.clone() {
+            let iov = load::<iovec>(data, offset)?;
+            len += bytes(data, iov.iov_base as _, iov.iov_len)?.len();
+        }
+
+        let mut buf = [0; SIM_BUFFER_SIZE];
+        let buf = &mut buf[..len.min(SIM_BUFFER_SIZE)];
+        let copy = |data: &mut [u8], buf: &mut [u8], n: usize| -> Result<()> {
+            let mut pos = 0;
+            for offset in iovs.clone() {
+                let iov = load::<iovec>(data, offset)?;
+                let dest = bytes(data, iov.iov_base as _, iov.iov_len)?;
+                let len = dest.len().min(n - pos);
+                match write {
+                    true => buf[pos..pos + len].copy_from_slice(&dest[..len]),
+                    false => dest[..len].copy_from_slice(&buf[pos..pos + len]),
+                }
+                pos += len;
+            }
+            Ok(())
+        };
+        if write {
+            copy(data, buf, buf.len())?;
+            self.write(fd, buf)
+        } else {
+            let n = self.read(fd, buf)?;
+            copy(data, buf, n).map(|_| n)
+        }
+    }
+
+    /// Executes syscall `num` with `argv` referencing memory in `data`.
+    fn execute(&mut self, num: usize, argv: [usize; 6], data: &mut [u8]) -> Result<usize> {
+        let [a0, a1, a2, a3, a4, a5] = argv;
+        #[allow(non_upper_case_globals)]
+        match num as c_long {
+            SYS_accept | SYS_accept4 => {
+                let flags = if num as c_long == SYS_accept4 {
+                    a3 as _
+                } else {
+                    0
+                };
+                if a1 != NULL {
+                    load::<socklen_t>(data, a2)?;
+                }
+                let (fd, peer) = self.accept(a0 as _, flags)?;
+                if a1 != NULL {
+                    encode_port(data, a1, a2, peer)?;
+                }
+                Ok(fd as _)
+            }
+            SYS_bind => {
+                let port = decode_port(data, a1, a2, true, EADDRNOTAVAIL)?;
+                match *self.socket(a0 as _)? {
+                    Socket::Stream { port: 0 } => {
+                        let port = self.assign_port(port, false)?;
+                        *self.socket(a0 as _)? = Socket::Stream { port };
+                    }
+                    Socket::Datagram { port: 0, .. } => {
+                        self.bind_datagram(a0 as _, port)?;
+                    }
+                    _ => return Err(EINVAL),
+                }
+                Ok(0)
+            }
+            SYS_clock_getres => {
+                if !(0..=11).contains(&(a0 as c_int)) {
+                    return Err(EINVAL);
+                }
+                if a1 != NULL {
+                    let res = timespec {
+                        tv_sec: 0,
+                        tv_nsec: 1,
+                    };
+                    store(data, a1, res)?;
+                }
+                Ok(0)
+            }
+            SYS_clock_gettime => {
+                if !(0..=11).contains(&(a0 as c_int)) {
+                    return Err(EINVAL);
+                }
+                let tp = timespec {
+                    tv_sec: (self.now / NSEC_PER_SEC) as _,
+                    tv_nsec: (self.now % NSEC_PER_SEC) as _,
+                };
+                store(data, a1, tp).map(|_| 0)
+            }
+            SYS_close => self.close(a0 as _).map(|_| 0),
+            SYS_connect => {
+                let port = decode_port(data, a1, a2, true, ENETUNREACH)?;
+                match *self.socket(a0 as _)? {
+                    Socket::Stream { .. } => self.connect_stream(a0 as _, port)?,
+                    Socket::Datagram { port: local, .. } => {
+                        if local == 0 {
+                            self.bind_datagram(a0 as _, 0)?;
+                        }
+                        if let Socket::Datagram { peer, .. } = self.socket(a0 as _)? {
+                            *peer = port;
+                        }
+                    }
+                    Socket::Connected { .. } => return Err(EISCONN),
+                    Socket::Listener { .. } => return Err(EINVAL),
+                }
+                Ok(0)
+            }
+            SYS_dup => self.dup(a0 as _, 0, false).map(|fd| fd as _),
+            SYS_dup2 if a0 == a1 => self.fd(a0 as _).map(|_| a1),
+            SYS_dup2 => self.dup_to(a0 as _, a1 as _, false).map(|fd| fd as _),
+            SYS_dup3 => {
+                let flags = a2 as c_int;
+                if a0 == a1 || flags & !O_CLOEXEC != 0 {
+                    return Err(EINVAL);
+                }
+                self.dup_to(a0 as _, a1 as _, flags & O_CLOEXEC != 0)
+                    .map(|fd| fd as _)
+            }
+            SYS_epoll_create1 => {
+                let flags = a0 as c_int;
+                if flags & !EPOLL_CLOEXEC != 0 {
+                    return Err(EINVAL);
+                }
+                self.open(Object::Epoll([None; INTEREST]), flags & EPOLL_CLOEXEC)
+                    .map(|fd| fd as _)
+            }
+            SYS_epoll_ctl => {
+                let op = a1 as c_int;
+                let event = match op {
+                    EPOLL_CTL_DEL => epoll_event { events: 0, u64: 0 },
+                    _ => load(data, a3)?,
+                };
+                self.epoll_ctl(a0 as _, op, a2 as _, event).map(|_| 0)
+            }
+            SYS_epoll_pwait | SYS_epoll_wait => {
+                let events = deref_aligned_slice::<epoll_event>(data, a1, a2)?;
+                self.epoll_wait(a0 as _, unsafe { &mut *events }, a3 as _)
+            }
+            SYS_eventfd2 => {
+                let flags = a1 as c_int;
+                if flags & !(EFD_CLOEXEC | EFD_NONBLOCK | EFD_SEMAPHORE) != 0 {
+                    return Err(EINVAL);
+                }
+                let object = Object::EventFd {
+                    counter: a0 as u32 as _,
+                    semaphore: flags & EFD_SEMAPHORE != 0,
+                };
+                self.open(object, flags).map(|fd| fd as _)
+            }
+            SYS_exit | SYS_exit_group => {
+                self.exit_status = Some(a0 as _);
+                Ok(0)
+            }
+            SYS_fcntl => self.fcntl(a0 as _, a1 as _, a2),
+            SYS_getsockname => {
+                load::<socklen_t>(data, a2)?;
+                let port = self.socket(a0 as _)?.port();
+                encode_port(data, a1, a2, port).map(|_| 0)
+            }
+            SYS_ioctl => self.ioctl(a0 as _, a1 as _, data, a2),
+            SYS_listen => match *self.socket(a0 as _)? {
+                Socket::Stream { port } => {
+                    let port = match port {
+                        0 => self.assign_port(0, false)?,
+                        port => port,
+                    };
+                    *self.socket(a0 as _)? = Socket::Listener {
+                        port,
+                        backlog: [None; BACKLOG],
+                    };
+                    Ok(0)
+                }
+                Socket::Listener { .. } => Ok(0),
+                Socket::Connected { .. } => Err(EINVAL),
+                Socket::Datagram { .. } => Err(ENOTSUP),
+            },
+            SYS_nanosleep => {
+                let req = load::<timespec>(data, a0)?;
+                if req.tv_sec < 0 || !(0..NSEC_PER_SEC as _).contains(&req.tv_nsec) {
+                    return Err(EINVAL);
+                }
+                self.advance(
+                    (req.tv_sec as u64)
+                        .saturating_mul(NSEC_PER_SEC)
+                        .saturating_add(req.tv_nsec as u64),
+                );
+                if a1 != NULL {
+                    let rem = timespec {
+                        tv_sec: 0,
+                        tv_nsec: 0,
+                    };
+                    store(data, a1, rem)?;
+                }
+                Ok(0)
+            }
+            SYS_open => {
+                let path = bytes(data, a0, a1)?;
+                let path = path.split(|b| *b == 0).next().unwrap_or(path);
+                let file = self.find_file(path).ok_or(ENOENT)?;
+                let flags = a2 as c_int;
+                let access = flags & O_ACCMODE;
+                if flags & O_TRUNC != 0 && access != O_RDONLY {
+                    self.files[file].as_mut().unwrap().len = 0;
+                }
+                let object = Object::File {
+                    file,
+                    offset: 0,
+                    access,
+                    append: flags & O_APPEND != 0,
+                };
+                self.open(object, flags).map(|fd| fd as _)
+            }
+            SYS_poll => {
+                let fds = deref_aligned_slice::<pollfd>(data, a0, a1)?;
+                Ok(self.poll(unsafe { &mut *fds }, a2 as _))
+            }
+            SYS_read => self.read(a0 as _, bytes(data, a1, a2)?),
+            SYS_readv => self.transfer_iovec(a0 as _, data, a1, a2, false),
+            SYS_recvfrom => {
+                if a4 != NULL {
+                    load::<socklen_t>(data, a5)?;
+                }
+                let datagram = matches!(self.socket(a0 as _), Ok(Socket::Datagram { .. }));
+                let (n, port) = self.recv(a0 as _, bytes(data, a1, a2)?, a3 as _)?;
+                if a4 != NULL && datagram {
+                    encode_port(data, a4, a5, port)?;
+                }
+                Ok(n)
+            }
+            SYS_sendto => {
+                let dest = match a4 {
+                    NULL => None,
+                    _ => Some(decode_port(data, a4, a5, false, ENETUNREACH)?),
+                };
+                let buf = bytes(data, a1, a2)?;
+                self.send(a0 as _, buf, dest)
+            }
+            SYS_setsockopt => {
+                if a3 != NULL {
+                    bytes(data, a3, a4)?;
+                }
+                self.socket(a0 as _).map(|_| 0)
+            }
+            SYS_socket => {
+                let (domain, typ) = (a0 as c_int, a1 as c_int);
+                if domain != AF_INET {
+                    return Err(EAFNOSUPPORT);
+                }
+                let socket = match typ & !(SOCK_CLOEXEC | SOCK_NONBLOCK) {
+                    SOCK_STREAM => Socket::Stream { port: 0 },
+                    SOCK_DGRAM => Socket::Datagram {
+                        port: 0,
+                        peer: 0,
+                        rx: None,
+                    },
+                    _ => return Err(EINVAL),
+                };
+                self.open(Object::Socket(socket), typ).map(|fd| fd as _)
+            }
+            SYS_sync => Ok(0),
+            SYS_write => self.write(a0 as _, bytes(data, a1, a2)?),
+            SYS_writev => self.transfer_iovec(a0 as _, data, a1, a2, true),
+            _ => Err(ENOSYS),
+        }
+    }
+}
+
+impl<const N: usize> Executor for SimKernel<'_, N> {
+    #[inline]
+    fn syscall(&mut self, call: &mut Syscall, data: &mut [u8]) -> Result<()> {
+        call.ret[0] = self.execute(call.num, call.argv, data)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn enarxcall(&mut self, _: &mut Enarxcall, _: &mut [u8]) -> Result<()> {
+        Err(ENOSYS)
+    }
+
+    fn reply(&self) -> Reply {
+        let mut reply = Reply::new();
+        reply.set_version(VERSION);
+        reply.add_kind(Kind::End);
+        reply.add_kind(Kind::Hello);
+        reply.add_kind(Kind::Notification);
+        reply.add_kind(Kind::Link);
+        reply.add_kind(Kind::Syscall);
+        SYSCALLS.iter().for_each(|num| {
+            reply.add_syscall(*num);
+        });
+        reply
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::execute_with;
+    use crate::item::Item;
+    use crate::libc::{SYS_getpid, EFAULT};
+
+    /// Data section aligned like the data section of a block.
+    #[repr(C, align(8))]
+    struct Data([u8; 64]);
+
+    fn syscall<const N: usize>(
+        sim: &mut SimKernel<'_, N>,
+        num: c_long,
+        argv: [usize; 6],
+        data: &mut [u8],
+    ) -> usize {
+        let mut call = Syscall {
+            num: num as _,
+            argv,
+            ret: [-ENOSYS as _, 0],
+        };
+        execute_with(sim, [Item::Syscall(&mut call, data)]).unwrap();
+        call.ret[0]
+    }
+
+    fn sockaddr(addr: [u8; 4], port: u16) -> Data {
+        let mut data = Data([0; 64]);
+        Sockaddr::Inet { addr, port }.encode(&mut data.0).unwrap();
+        data
+    }
+
+    fn port(data: &Data) -> u16 {
+        u16::from_be_bytes([data.0[2], data.0[3]])
+    }
+
+    #[test]
+    fn pipe() {
+        let mut sim = SimKernel::<'_, 4>::new();
+        assert_eq!(sim.pipe(O_NONBLOCK), Ok([0, 1]));
+        let mut data = *b"hello";
+        assert_eq!(
+            syscall(&mut sim, SYS_write, [1, 0, 5, 0, 0, 0], &mut data),
+            5
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_read, [1, 0, 5, 0, 0, 0], &mut data),
+            -EBADF as _
+        );
+
+        assert_eq!(syscall(&mut sim, SYS_dup, [0; 6], &mut []), 2);
+        assert_eq!(
+            syscall(&mut sim, SYS_dup3, [2, 3, O_CLOEXEC as _, 0, 0, 0], &mut []),
+            3
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_fcntl, [3, F_GETFD as _, 0, 0, 0, 0], &mut []),
+            1
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_fcntl, [3, F_GETFL as _, 0, 0, 0, 0], &mut []),
+            (O_RDONLY | O_NONBLOCK) as _
+        );
+        assert_eq!(syscall(&mut sim, SYS_dup, [0; 6], &mut []), -EMFILE as _);
+        assert_eq!(syscall(&mut sim, SYS_close, [0; 6], &mut []), 0);
+        assert_eq!(syscall(&mut sim, SYS_close, [0; 6], &mut []), -EBADF as _);
+
+        let mut buf = [0; 8];
+        assert_eq!(sim.read(3, &mut buf[..2]), Ok(2));
+        assert_eq!(sim.read(2, &mut buf), Ok(3));
+        assert_eq!(&buf[..3], b"llo");
+        assert_eq!(sim.read(2, &mut buf), Err(EAGAIN));
+        assert_eq!(sim.close(1), Ok(()));
+        assert_eq!(sim.read(2, &mut buf), Ok(0));
+
+        let [reader, writer] = sim.pipe(0).unwrap();
+        assert_eq!(
+            sim.write(writer, &[0; SIM_BUFFER_SIZE + 1]),
+            Ok(SIM_BUFFER_SIZE)
+        );
+        assert_eq!(sim.write(writer, b"x"), Err(EAGAIN));
+        assert_eq!(sim.close(reader), Ok(()));
+        assert_eq!(sim.write(writer, b"x"), Err(EPIPE));
+    }
+
+    #[test]
+    fn iovec() {
+        fn iovecs(data: &mut Data, iovs: [(usize, usize); 2]) {
+            for (i, (base, len)) in iovs.into_iter().enumerate() {
+                data.0[i * 16..i * 16 + 8].copy_from_slice(&base.to_ne_bytes());
+                data.0[i * 16 + 8..i * 16 + 16].copy_from_slice(&len.to_ne_bytes());
+            }
+        }
+
+        let mut sim = SimKernel::<'_, 4>::new();
+        assert_eq!(sim.pipe(O_NONBLOCK), Ok([0, 1]));
+
+        let mut data = Data([0; 64]);
+        iovecs(&mut data, [(32, 3), (40, 2)]);
+        data.0[32..35].copy_from_slice(b"hel");
+        data.0[40..42].copy_from_slice(b"lo");
+        assert_eq!(
+            syscall(&mut sim, SYS_writev, [1, 0, 2, 0, 0, 0], &mut data.0),
+            5
+        );
+
+        let mut data = Data([0; 64]);
+        iovecs(&mut data, [(32, 2), (40, 8)]);
+        assert_eq!(
+            syscall(&mut sim, SYS_readv, [0, 0, 2, 0, 0, 0], &mut data.0),
+            5
+        );
+        assert_eq!(&data.0[32..34], b"he");
+        assert_eq!(&data.0[40..48], b"llo\0\0\0\0\0");
+
+        iovecs(&mut data, [(32, 2), (60, 8)]);
+        assert_eq!(
+            syscall(&mut sim, SYS_readv, [0, 0, 2, 0, 0, 0], &mut data.0),
+            -EFAULT as _
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_readv, [0, 4, 1, 0, 0, 0], &mut data.0),
+            -EFAULT as _
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_readv, [0, 0, 1025, 0, 0, 0], &mut data.0),
+            -EINVAL as _
+        );
+    }
+
+    #[test]
+    fn file() {
+        let mut storage = *b"nameserver 127.0.0.1\n..";
+        let mut sim = SimKernel::<'_, 4>::new();
+        assert_eq!(sim.add_file(b"/etc/resolv.conf", &mut storage, 21), Ok(()));
+
+        let mut path = *b"/etc/resolv.conf\0";
+        let open = |sim: &mut SimKernel<'_, 4>, path: &mut [u8], flags: c_int| {
+            syscall(sim, SYS_open, [0, path.len(), flags as _, 0, 0, 0], path)
+        };
+        assert_eq!(
+            open(&mut sim, &mut { *b"/etc/hosts\0" }, O_RDONLY),
+            -ENOENT as _
+        );
+        assert_eq!(open(&mut sim, &mut path, O_RDONLY), 0);
+        let mut buf = [0; 32];
+        assert_eq!(sim.read(0, &mut buf), Ok(21));
+        assert_eq!(&buf[..21], b"nameserver 127.0.0.1\n");
+        assert_eq!(sim.read(0, &mut buf), Ok(0));
+        assert_eq!(sim.write(0, b"x"), Err(EBADF));
+
+        assert_eq!(open(&mut sim, &mut path, O_WRONLY | O_TRUNC), 1);
+        assert_eq!(sim.write(1, b"search local\n"), Ok(13));
+        assert_eq!(sim.write(1, b"0123456789"), Ok(10));
+        assert_eq!(sim.write(1, b"x"), Err(ENOSPC));
+        assert_eq!(
+            sim.file(b"/etc/resolv.conf"),
+            Some(&b"search local\n0123456789"[..])
+        );
+    }
+
+    #[test]
+    fn stream() {
+        let mut sim = SimKernel::<'_, 8>::new();
+        let socket = [AF_INET as _, SOCK_STREAM as _, 0, 0, 0, 0];
+        assert_eq!(syscall(&mut sim, SYS_socket, socket, &mut []), 0);
+        assert_eq!(syscall(&mut sim, SYS_socket, socket, &mut []), 1);
+
+        let mut addr = sockaddr(LOOPBACK, 0);
+        assert_eq!(
+            syscall(&mut sim, SYS_bind, [0, 0, 16, 0, 0, 0], &mut addr.0),
+            0
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_listen, [0, 1, 0, 0, 0, 0], &mut []),
+            0
+        );
+        let mut name = Data([0; 64]);
+        name.0[16..20].copy_from_slice(&16u32.to_ne_bytes());
+        assert_eq!(
+            syscall(&mut sim, SYS_getsockname, [0, 0, 16, 0, 0, 0], &mut name.0),
+            0
+        );
+        assert_eq!(port(&name), EPHEMERAL_PORT);
+
+        let mut unbound = sockaddr(LOOPBACK, 1);
+        assert_eq!(
+            syscall(&mut sim, SYS_connect, [1, 0, 16, 0, 0, 0], &mut unbound.0),
+            -ECONNREFUSED as _
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_accept, [0, NULL, 0, 0, 0, 0], &mut []),
+            -EAGAIN as _
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_connect, [1, 0, 16, 0, 0, 0], &mut name.0),
+            0
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_connect, [1, 0, 16, 0, 0, 0], &mut name.0),
+            -EISCONN as _
+        );
+        let mut peer = Data([0; 64]);
+        peer.0[16..20].copy_from_slice(&16u32.to_ne_bytes());
+        assert_eq!(
+            syscall(
+                &mut sim,
+                SYS_accept4,
+                [0, 0, 16, SOCK_CLOEXEC as _, 0, 0],
+                &mut peer.0
+            ),
+            2
+        );
+        assert_eq!(port(&peer), EPHEMERAL_PORT + 1);
+
+        let mut data = *b"ping";
+        assert_eq!(
+            syscall(&mut sim, SYS_sendto, [1, 0, 4, 0, NULL, 0], &mut data),
+            4
+        );
+        let mut buf = [0; 8];
+        assert_eq!(sim.read(2, &mut buf), Ok(4));
+        assert_eq!(&buf[..4], b"ping");
+        assert_eq!(sim.write(2, b"pong"), Ok(4));
+        assert_eq!(sim.close(2), Ok(()));
+        assert_eq!(sim.read(1, &mut buf), Ok(4));
+        assert_eq!(sim.read(1, &mut buf), Ok(0));
+        assert_eq!(sim.write(1, b"x"), Err(EPIPE));
+    }
+
+    #[test]
+    fn datagram() {
+        let mut sim = SimKernel::<'_, 4>::new();
+        let socket = [AF_INET as _, SOCK_DGRAM as _, 0, 0, 0, 0];
+        assert_eq!(syscall(&mut sim, SYS_socket, socket, &mut []), 0);
+        assert_eq!(syscall(&mut sim, SYS_socket, socket, &mut []), 1);
+        let mut addr = sockaddr(LOOPBACK, 5353);
+        assert_eq!(
+            syscall(&mut sim, SYS_bind, [0, 0, 16, 0, 0, 0], &mut addr.0),
+            0
+        );
+        assert_eq!(
+            syscall(&mut sim, SYS_bind, [1, 0, 16, 0, 0, 0], &mut addr.0),
+            -EADDRINUSE as _
+        );
+
+        let mut remote = sockaddr([10, 0, 0, 1], 53);
+        remote.0[16..20].copy_from_slice(b"ping");
+        assert_eq!(
+            syscall(&mut sim, SYS_sendto, [1, 16, 4, 0, 0, 16], &mut remote.0),
+            -ENETUNREACH as _
+        );
+        addr.0[16..20].copy_from_slice(b"ping");
+        assert_eq!(
+            syscall(&mut sim, SYS_sendto, [1, 16, 4, 0, 0, 16], &mut addr.0),
+            4
+        );
+        assert_eq!(sim.write(1, b"x"), Err(EDESTADDRREQ));
+
+        let mut src = Data([0; 64]);
+        src.0[16..20].copy_from_slice(&16u32.to_ne_bytes());
+        let recvfrom = [0, 24, 2, MSG_PEEK as _, 0, 16];
+        assert_eq!(syscall(&mut sim, SYS_recvfrom, recvfrom, &mut src.0), 2);
+        assert_eq!(port(&src), EPHEMERAL_PORT);
+        assert_eq!(&src.0[24..26], b"pi");
+        let mut buf = [0; 2];
+        assert_eq!(sim.read(0, &mut buf), Ok(2));
+        assert_eq!(sim.read(0, &mut buf), Err(EAGAIN));
+    }
+
+    #[test]
+    fn wait() {
+        let mut sim = SimKernel::<'_, 4>::new();
+        assert_eq!(
+            syscall(&mut sim, SYS_eventfd2, [0, 0, 0, 0, 0, 0], &mut []),
+            0
+        );
+        assert_eq!(syscall(&mut sim, SYS_epoll_create1, [0; 6], &mut []), 1);
+
+        let mut data = Data([0; 64]);
+        let event = epoll_event {
+            events: POLLIN as _,
+            u64: 42,
+        };
+        store(&mut data.0, 0, event).unwrap();
+        let ctl = |sim: &mut SimKernel<'_, 4>, op: c_int, fd: usize, data: &mut [u8]| {
+            syscall(sim, SYS_epoll_ctl, [1, op as _, fd, 0, 0, 0], data)
+        };
+        assert_eq!(ctl(&mut sim, EPOLL_CTL_ADD, 0, &mut data.0), 0);
+        assert_eq!(ctl(&mut sim, EPOLL_CTL_ADD, 0, &mut data.0), -EEXIST as _);
+        assert_eq!(ctl(&mut sim, EPOLL_CTL_ADD, 1, &mut data.0), -EINVAL as _);
+
+        let epoll_wait = [1, 16, 2, 10, 0, 0];
+        assert_eq!(
+            syscall(&mut sim, SYS_epoll_wait, epoll_wait, &mut data.0),
+            0
+        );
+        assert_eq!(sim.now(), 10_000_000);
+        assert_eq!(sim.write(0, &2u64.to_ne_bytes()), Ok(8));
+        assert_eq!(
+            syscall(&mut sim, SYS_epoll_wait, epoll_wait, &mut data.0),
+            1
+        );
+        assert_eq!(load::<epoll_event>(&mut data.0, 16), Ok(event));
+
+        let fds = [
+            pollfd {
+                fd: 0,
+                events: POLLIN | POLLOUT,
+                revents: 0,
+            },
+            pollfd {
+                fd: 3,
+                events: POLLIN,
+                revents: 0,
+            },
+        ];
+        store(&mut data.0, 0, fds).unwrap();
+        assert_eq!(
+            syscall(&mut sim, SYS_poll, [0, 2, 0, 0, 0, 0], &mut data.0),
+            2
+        );
+        let fds = load::<[pollfd; 2]>(&mut data.0, 0).unwrap();
+        assert_eq!(fds[0].revents, POLLIN | POLLOUT);
+        assert_eq!(fds[1].revents, POLLNVAL);
+
+        let mut buf = [0; 8];
+        assert_eq!(sim.read(0, &mut buf), Ok(8));
+        assert_eq!(u64::from_ne_bytes(buf), 2);
+        assert_eq!(sim.close(0), Ok(()));
+        assert_eq!(
+            syscall(&mut sim, SYS_epoll_wait, epoll_wait, &mut data.0),
+            0
+        );
+        assert_eq!(ctl(&mut sim, EPOLL_CTL_DEL, 0, &mut []), -EBADF as _);
+    }
+
+    #[test]
+    fn clock() {
+        let mut sim = SimKernel::<'_, 1>::new();
+        let mut data = Data([0; 64]);
+        let req = timespec {
+            tv_sec: 1,
+            tv_nsec: 500,
+        };
+        store(&mut data.0, 0, req).unwrap();
+        assert_eq!(
+            syscall(&mut sim, SYS_nanosleep, [0, 16, 0, 0, 0, 0], &mut data.0),
+            0
+        );
+        sim.advance(1);
+        assert_eq!(
+            syscall(
+                &mut sim,
+                SYS_clock_gettime,
+                [1, 32, 0, 0, 0, 0],
+                &mut data.0
+            ),
+            0
+        );
+        assert_eq!(
+            load::<timespec>(&mut data.0, 32),
+            Ok(timespec {
+                tv_sec: 1,
+                tv_nsec: 501
+            })
+        );
+        assert_eq!(
+            syscall(
+                &mut sim,
+                SYS_clock_gettime,
+                [1, 33, 0, 0, 0, 0],
+                &mut data.0
+            ),
+            -EFAULT as _
+        );
+
+        // Sleeping longer than the clock can represent saturates the clock.
+        let req = timespec {
+            tv_sec: i64::MAX as _,
+            tv_nsec: 999_999_999,
+        };
+        store(&mut data.0, 0, req).unwrap();
+        assert_eq!(
+            syscall(&mut sim, SYS_nanosleep, [0, 16, 0, 0, 0, 0], &mut data.0),
+            0
+        );
+        assert_eq!(
+            syscall(
+                &mut sim,
+                SYS_clock_gettime,
+                [1, 32, 0, 0, 0, 0],
+                &mut data.0
+            ),
+            0
+        );
+        assert_eq!(
+            load::<timespec>(&mut data.0, 32),
+            Ok(timespec {
+                tv_sec: (u64::MAX / NSEC_PER_SEC) as _,
+                tv_nsec: (u64::MAX % NSEC_PER_SEC) as _,
+            })
+        );
+
+        assert_eq!(
+            syscall(&mut sim, SYS_exit_group, [3, 0, 0, 0, 0, 0], &mut []),
+            0
+        );
+        assert_eq!(sim.exit_status(), Some(3));
+        assert_eq!(syscall(&mut sim, SYS_getpid, [0; 6], &mut []), -ENOSYS as _);
+        assert!(sim.reply().supports_syscall(SYS_epoll_wait));
+        assert!(!sim.reply().supports_syscall(SYS_getpid));
+    }
+}
@@ -1,8 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{deref, deref_aligned};
+use super::{deref, deref_aligned, deref_aligned_slice};
 use crate::libc::{
-    self, epoll_event, pollfd, sigset_t, sockaddr_storage, socklen_t, timespec, EFAULT,
+    self, cmsghdr, epoll_event, iovec, msghdr, pollfd, sigset_t, sockaddr_storage, socklen_t, stat,
+    statx, timespec, EFAULT, EINVAL, EMSGSIZE,
 };
 use crate::{item, Result, NULL};
 
@@ -11,21 +12,21 @@ use core::ffi::c_long;
 use core::mem::align_of;
 use core::ptr::{null, null_mut};
 
-trait Execute {
+pub(super) trait Execute {
     unsafe fn execute(self);
 }
 
-struct Syscall<'a, const ARGS: usize, const RETS: usize> {
+pub(super) struct Syscall<'a, const ARGS: usize, const RETS: usize> {
     /// The syscall number for the request.
     ///
     /// See, for example, [`libc::SYS_exit`](libc::SYS_exit).
-    num: c_long,
+    pub num: c_long,
 
     /// The syscall argument vector.
-    argv: [usize; ARGS],
+    pub argv: [usize; ARGS],
 
     /// Return values.
-    ret: [&'a mut usize; RETS],
+    pub ret: [&'a mut usize; RETS],
 }
 
 impl Execute for Syscall<'_, 0, 1> {
@@ -133,10 +134,32 @@ impl Execute for Syscall<'_, 6, 1> {
     }
 }
 
+/// Executes syscall `num` with `argv` directly.
+pub(super) unsafe fn raw<const ARGS: usize>(num: c_long, argv: [usize; ARGS]) -> Result<usize>
+where
+    for<'a> Syscall<'a, ARGS, 1>: Execute,
+{
+    let mut ret = 0;
+    Syscall {
+        num,
+        argv,
+        ret: [&mut ret],
+    }
+    .execute();
+    match ret as isize {
+        err @ -4095..=-1 => Err(-err as _),
+        _ => Ok(ret),
+    }
+}
+
 /// Validates that `data` contains aligned sockaddr input at `addr_offset` of `addrlen` size
 /// and returns an immutable pointer to address buffer on success.
 #[inline]
-fn deref_sockaddr_input(data: &mut [u8], addr_offset: usize, addrlen: usize) -> Result<*const u8> {
+pub(super) fn deref_sockaddr_input(
+    data: &mut [u8],
+    addr_offset: usize,
+    addrlen: usize,
+) -> Result<*const u8> {
     let addr = unsafe { deref::<u8>(data, addr_offset, addrlen) }?;
     if addr.align_offset(align_of::<sockaddr_storage>()) != 0 {
         Err(EFAULT)
@@ -148,7 +171,7 @@ fn deref_sockaddr_input(data: &mut [u8], addr_offset: usize, addrlen: usize) ->
 /// Validates that `data` contains aligned sockaddr output at `addr_offset` and `addrlen_offset`
 /// and returns mutable pointers to the address buffer and length on success.
 #[inline]
-fn deref_sockaddr_output(
+pub(super) fn deref_sockaddr_output(
     data: &mut [u8],
     addr_offset: usize,
     addrlen_offset: usize,
@@ -162,6 +185,188 @@ fn deref_sockaddr_output(
     }
 }
 
+/// Validates that `data` contains a nul-terminated string at `offset`
+/// and returns an immutable pointer to the string on success.
+#[inline]
+pub(super) fn deref_str(data: &mut [u8], offset: usize) -> Result<*const u8> {
+    match data.get(offset..) {
+        Some(s) if s.contains(&0) => Ok(s.as_ptr()),
+        _ => Err(EFAULT),
+    }
+}
+
+/// Validates that `data` contains a path of `len` bytes at `offset`, which is nul-terminated
+/// within these bytes, and returns an immutable pointer to the path on success.
+#[inline]
+pub(super) fn deref_path(data: &mut [u8], offset: usize, len: usize) -> Result<*const u8> {
+    match offset
+        .checked_add(len)
+        .and_then(|end| data.get(offset..end))
+    {
+        Some(path) if path.contains(&0) => Ok(path.as_ptr()),
+        _ => Err(EFAULT),
+    }
+}
+
+/// Maximum count of [`iovec`] elements passed to vectored I/O syscalls.
+const IOV_MAX: usize = libc::UIO_MAXIOV as _;
+
+const EMPTY_IOVEC: iovec = iovec {
+    iov_base: null_mut(),
+    iov_len: 0,
+};
+
+/// Copies the aligned array of `iovcnt` [`iovec`] elements at `offset` within `data` into
+/// `iovs` and translates the copies to pointers to buffers within `data`.
+///
+/// The array is copied before validating the buffers, so that it cannot be modified anymore
+/// once validated.
+#[inline]
+unsafe fn copy_iovecs<'a>(
+    data: &mut [u8],
+    offset: usize,
+    iovcnt: usize,
+    iovs: &'a mut [iovec; IOV_MAX],
+) -> Result<&'a mut [iovec]> {
+    let iovs = iovs.get_mut(..iovcnt).ok_or(EINVAL)?;
+    let src = deref_aligned_slice::<iovec>(data, offset, iovs.len())?;
+    iovs.as_mut_ptr()
+        .copy_from_nonoverlapping(src.cast(), iovs.len());
+    for iov in iovs.iter_mut() {
+        iov.iov_base = deref::<u8>(data, iov.iov_base as _, iov.iov_len)? as _;
+    }
+    Ok(iovs)
+}
+
+/// Validates that `data` contains an aligned array of `argv[2]` [`iovec`] elements at offset
+/// `argv[1]` referencing buffers within `data` and executes the vectored I/O syscall `num`
+/// with `argv`, where the array is replaced by a copy translated to pointers.
+#[inline(never)]
+unsafe fn execute_iovec<const ARGS: usize>(
+    num: c_long,
+    mut argv: [usize; ARGS],
+    data: &mut [u8],
+    ret: &mut usize,
+) -> Result<()>
+where
+    for<'a> Syscall<'a, ARGS, 1>: Execute,
+{
+    let mut iovs = [EMPTY_IOVEC; IOV_MAX];
+    argv[1] = copy_iovecs(data, argv[1], argv[2], &mut iovs)?.as_ptr() as _;
+    Syscall {
+        num,
+        argv,
+        ret: [ret],
+    }
+    .execute();
+    Ok(())
+}
+
+/// Validates that `data` contains an aligned [`msghdr`] at `offset` referencing an optional
+/// name, an array of [`iovec`] elements and optional control messages within `data` and
+/// executes `sendmsg` or `recvmsg` `num` with a copy translated to pointers.
+///
+/// The name length, control messages length and flags returned by `recvmsg` are written back
+/// into the [`msghdr`] within `data`.
+#[inline(never)]
+unsafe fn execute_msg(
+    num: c_long,
+    sockfd: usize,
+    offset: usize,
+    flags: usize,
+    data: &mut [u8],
+    ret: &mut usize,
+) -> Result<()> {
+    let mut msg = deref_aligned::<msghdr>(data, offset, 1)?.read();
+    if msg.msg_iovlen > IOV_MAX {
+        return Err(EMSGSIZE);
+    }
+    let mut iovs = [EMPTY_IOVEC; IOV_MAX];
+    msg.msg_iov = copy_iovecs(data, msg.msg_iov as _, msg.msg_iovlen, &mut iovs)?.as_mut_ptr();
+    if msg.msg_name as usize != NULL {
+        msg.msg_name = deref_sockaddr_input(data, msg.msg_name as _, msg.msg_namelen as _)? as _;
+    } else {
+        msg.msg_name = null_mut();
+    }
+    if msg.msg_control as usize != NULL {
+        let control = deref::<u8>(data, msg.msg_control as _, msg.msg_controllen)?;
+        if control.align_offset(align_of::<cmsghdr>()) != 0 {
+            return Err(EFAULT);
+        }
+        msg.msg_control = control as _;
+    } else {
+        msg.msg_control = null_mut();
+    }
+    Syscall {
+        num,
+        argv: [sockfd, &mut msg as *mut _ as _, flags],
+        ret: [ret],
+    }
+    .execute();
+
+    if num == libc::SYS_recvmsg {
+        let dest = deref_aligned::<msghdr>(data, offset, 1)?;
+        (*dest).msg_namelen = msg.msg_namelen;
+        (*dest).msg_controllen = msg.msg_controllen;
+        (*dest).msg_flags = msg.msg_flags;
+    }
+    Ok(())
+}
+
+/// Syscalls supported by [`execute`], sorted alphabetically.
+pub(super) const SYSCALLS: &[c_long] = &[
+    libc::SYS_accept,
+    libc::SYS_accept4,
+    libc::SYS_bind,
+    libc::SYS_clock_getres,
+    libc::SYS_clock_gettime,
+    libc::SYS_close,
+    libc::SYS_connect,
+    libc::SYS_dup,
+    libc::SYS_dup2,
+    libc::SYS_dup3,
+    libc::SYS_epoll_create1,
+    libc::SYS_epoll_ctl,
+    libc::SYS_epoll_pwait,
+    libc::SYS_epoll_wait,
+    libc::SYS_eventfd2,
+    libc::SYS_exit,
+    libc::SYS_exit_group,
+    libc::SYS_fchmod,
+    libc::SYS_fcntl,
+    libc::SYS_fstat,
+    libc::SYS_ftruncate,
+    libc::SYS_getdents64,
+    libc::SYS_getsockname,
+    libc::SYS_ioctl,
+    libc::SYS_listen,
+    libc::SYS_lseek,
+    libc::SYS_mkdirat,
+    libc::SYS_nanosleep,
+    libc::SYS_newfstatat,
+    libc::SYS_open,
+    libc::SYS_openat,
+    libc::SYS_poll,
+    libc::SYS_pread64,
+    libc::SYS_preadv,
+    libc::SYS_pwrite64,
+    libc::SYS_pwritev,
+    libc::SYS_read,
+    libc::SYS_readv,
+    libc::SYS_recvfrom,
+    libc::SYS_recvmsg,
+    libc::SYS_renameat2,
+    libc::SYS_sendmsg,
+    libc::SYS_sendto,
+    libc::SYS_setsockopt,
+    libc::SYS_socket,
+    libc::SYS_statx,
+    libc::SYS_sync,
+    libc::SYS_unlinkat,
+    libc::SYS_write,
+    libc::SYS_writev,
+];
+
 pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Result<()> {
     match call {
         item::Syscall {
@@ -391,6 +596,17 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
         }
         .execute(),
 
+        item::Syscall {
+            num,
+            argv: [fd, mode, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_fchmod as _ => Syscall {
+            num: libc::SYS_fchmod,
+            argv: [*fd, *mode],
+            ret: [ret],
+        }
+        .execute(),
+
         item::Syscall {
             num,
             argv: [fd, cmd, arg, ..],
@@ -402,6 +618,45 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
         }
         .execute(),
 
+        item::Syscall {
+            num,
+            argv: [fd, statbuf_offset, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_fstat as _ => {
+            let statbuf = deref_aligned::<stat>(data, *statbuf_offset, 1)?;
+            Syscall {
+                num: libc::SYS_fstat,
+                argv: [*fd, statbuf as _],
+                ret: [ret],
+            }
+            .execute()
+        }
+
+        item::Syscall {
+            num,
+            argv: [fd, length, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_ftruncate as _ => Syscall {
+            num: libc::SYS_ftruncate,
+            argv: [*fd, *length],
+            ret: [ret],
+        }
+        .execute(),
+
+        item::Syscall {
+            num,
+            argv: [fd, dirp_offset, count, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_getdents64 as _ => {
+            let dirp = deref::<u8>(data, *dirp_offset, *count)?;
+            Syscall {
+                num: libc::SYS_getdents64,
+                argv: [*fd, dirp as _, *count],
+                ret: [ret],
+            }
+            .execute()
+        }
+
         item::Syscall {
             num,
             argv: [sockfd, addr_offset, addrlen_offset, ..],
@@ -445,6 +700,31 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
         }
         .execute(),
 
+        item::Syscall {
+            num,
+            argv: [fd, offset, whence, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_lseek as _ => Syscall {
+            num: libc::SYS_lseek,
+            argv: [*fd, *offset, *whence],
+            ret: [ret],
+        }
+        .execute(),
+
+        item::Syscall {
+            num,
+            argv: [dirfd, pathname_offset, pathname_len, mode, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_mkdirat as _ => {
+            let pathname = deref_path(data, *pathname_offset, *pathname_len)?;
+            Syscall {
+                num: libc::SYS_mkdirat,
+                argv: [*dirfd, pathname as _, *mode],
+                ret: [ret],
+            }
+            .execute()
+        }
+
         item::Syscall {
             num,
             argv: [req_offset, rem_offset, ..],
@@ -464,12 +744,27 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
             .execute()
         }
 
+        item::Syscall {
+            num,
+            argv: [dirfd, pathname_offset, pathname_len, statbuf_offset, flags, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_newfstatat as _ => {
+            let pathname = deref_path(data, *pathname_offset, *pathname_len)?;
+            let statbuf = deref_aligned::<stat>(data, *statbuf_offset, 1)?;
+            Syscall {
+                num: libc::SYS_newfstatat,
+                argv: [*dirfd, pathname as _, statbuf as _, *flags],
+                ret: [ret],
+            }
+            .execute()
+        }
+
         item::Syscall {
             num,
             argv: [pathname_offset, pathname_len, flags, mode, ..],
             ret: [ret, ..],
         } if *num == libc::SYS_open as _ => {
-            let pathname = deref::<u8>(data, *pathname_offset, *pathname_len)?;
+            let pathname = deref_path(data, *pathname_offset, *pathname_len)?;
             Syscall {
                 num: libc::SYS_open,
                 argv: [pathname as _, *flags, *mode],
@@ -478,6 +773,20 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
             .execute()
         }
 
+        item::Syscall {
+            num,
+            argv: [dirfd, pathname_offset, pathname_len, flags, mode, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_openat as _ => {
+            let pathname = deref_path(data, *pathname_offset, *pathname_len)?;
+            Syscall {
+                num: libc::SYS_openat,
+                argv: [*dirfd, pathname as _, *flags, *mode],
+                ret: [ret],
+            }
+            .execute()
+        }
+
         item::Syscall {
             num,
             argv: [fds_offset, nfds, timeout, ..],
@@ -492,6 +801,56 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
             .execute()
         }
 
+        item::Syscall {
+            num,
+            argv: [fd, buf_offset, count, offset, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_pread64 as _ => {
+            let buf = deref::<u8>(data, *buf_offset, *count)?;
+            Syscall {
+                num: libc::SYS_pread64,
+                argv: [*fd, buf as _, *count, *offset],
+                ret: [ret],
+            }
+            .execute();
+        }
+
+        item::Syscall {
+            num,
+            argv: [fd, iov_offset, iovcnt, offset, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_preadv as _ => execute_iovec(
+            libc::SYS_preadv,
+            [*fd, *iov_offset, *iovcnt, *offset, 0],
+            data,
+            ret,
+        )?,
+
+        item::Syscall {
+            num,
+            argv: [fd, buf_offset, count, offset, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_pwrite64 as _ => {
+            let buf = deref::<u8>(data, *buf_offset, *count)?;
+            Syscall {
+                num: libc::SYS_pwrite64,
+                argv: [*fd, buf as _, *count, *offset],
+                ret: [ret],
+            }
+            .execute();
+        }
+
+        item::Syscall {
+            num,
+            argv: [fd, iov_offset, iovcnt, offset, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_pwritev as _ => execute_iovec(
+            libc::SYS_pwritev,
+            [*fd, *iov_offset, *iovcnt, *offset, 0],
+            data,
+            ret,
+        )?,
+
         item::Syscall {
             num,
             argv: [fd, buf_offset, count, ..],
@@ -506,6 +865,14 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
             .execute();
         }
 
+        item::Syscall {
+            num,
+            argv: [fd, iov_offset, iovcnt, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_readv as _ => {
+            execute_iovec(libc::SYS_readv, [*fd, *iov_offset, *iovcnt], data, ret)?
+        }
+
         item::Syscall {
             num,
             argv: [sockfd, buf_offset, len, flags, src_addr_offset, addrlen_offset],
@@ -525,6 +892,37 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
             .execute();
         }
 
+        item::Syscall {
+            num,
+            argv: [sockfd, msg_offset, flags, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_recvmsg as _ => {
+            execute_msg(libc::SYS_recvmsg, *sockfd, *msg_offset, *flags, data, ret)?
+        }
+
+        item::Syscall {
+            num,
+            argv: [olddirfd, oldpath_offset, newdirfd, newpath_offset, flags, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_renameat2 as _ => {
+            let oldpath = deref_str(data, *oldpath_offset)?;
+            let newpath = deref_str(data, *newpath_offset)?;
+            Syscall {
+                num: libc::SYS_renameat2,
+                argv: [*olddirfd, oldpath as _, *newdirfd, newpath as _, *flags],
+                ret: [ret],
+            }
+            .execute()
+        }
+
+        item::Syscall {
+            num,
+            argv: [sockfd, msg_offset, flags, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_sendmsg as _ => {
+            execute_msg(libc::SYS_sendmsg, *sockfd, *msg_offset, *flags, data, ret)?
+        }
+
         item::Syscall {
             num,
             argv: [sockfd, buf_offset, len, flags, dest_addr_offset, addrlen],
@@ -580,6 +978,21 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
         }
         .execute(),
 
+        item::Syscall {
+            num,
+            argv: [dirfd, pathname_offset, pathname_len, flags, mask, statxbuf_offset],
+            ret: [ret, ..],
+        } if *num == libc::SYS_statx as _ => {
+            let pathname = deref_path(data, *pathname_offset, *pathname_len)?;
+            let statxbuf = deref_aligned::<statx>(data, *statxbuf_offset, 1)?;
+            Syscall {
+                num: libc::SYS_statx,
+                argv: [*dirfd, pathname as _, *flags, *mask, statxbuf as _],
+                ret: [ret],
+            }
+            .execute()
+        }
+
         item::Syscall {
             num,
             argv: _,
@@ -591,6 +1004,20 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
         }
         .execute(),
 
+        item::Syscall {
+            num,
+            argv: [dirfd, pathname_offset, pathname_len, flags, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_unlinkat as _ => {
+            let pathname = deref_path(data, *pathname_offset, *pathname_len)?;
+            Syscall {
+                num: libc::SYS_unlinkat,
+                argv: [*dirfd, pathname as _, *flags],
+                ret: [ret],
+            }
+            .execute()
+        }
+
         item::Syscall {
             num,
             argv: [fd, buf_offset, count, ..],
@@ -605,8 +1032,98 @@ pub(super) unsafe fn execute(call: &mut item::Syscall, data: &mut [u8]) -> Resul
             .execute();
         }
 
+        item::Syscall {
+            num,
+            argv: [fd, iov_offset, iovcnt, ..],
+            ret: [ret, ..],
+        } if *num == libc::SYS_writev as _ => {
+            execute_iovec(libc::SYS_writev, [*fd, *iov_offset, *iovcnt], data, ret)?
+        }
+
         // Silently skip unsupported items
         _ => {}
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::hello::SYSCALL_MAX;
+
+    /// Return value left untouched by syscalls, which [`execute`] skips.
+    const SKIPPED: usize = 0x5a11;
+
+    /// Executes syscall `num` with invalid arguments and returns `true` if it was handled.
+    fn handled(num: c_long) -> bool {
+        let mut call = item::Syscall {
+            num: num as _,
+            argv: [usize::MAX; 6],
+            ret: [SKIPPED, 0],
+        };
+        unsafe { execute(&mut call, &mut []) }.is_err() || call.ret[0] != SKIPPED
+    }
+
+    /// Executes exiting syscall `num` in a child process and returns `true` if it was handled.
+    fn handled_exit(num: c_long) -> bool {
+        match unsafe { ::libc::fork() } {
+            0 => {
+                let mut call = item::Syscall {
+                    num: num as _,
+                    argv: [42, 0, 0, 0, 0, 0],
+                    ret: [SKIPPED, 0],
+                };
+                let _ = unsafe { execute(&mut call, &mut []) };
+                unsafe { ::libc::_exit(0) }
+            }
+            pid => {
+                let mut status = 0;
+                assert_eq!(unsafe { ::libc::waitpid(pid, &mut status, 0) }, pid);
+                ::libc::WIFEXITED(status) && ::libc::WEXITSTATUS(status) == 42
+            }
+        }
+    }
+
+    #[test]
+    fn unterminated_path() {
+        for (num, argv) in [
+            (libc::SYS_mkdirat, [0, 0, 4, 0, 0, 0]),
+            (libc::SYS_newfstatat, [0, 0, 4, 8, 0, 0]),
+            (libc::SYS_open, [0, 4, 0, 0, 0, 0]),
+            (libc::SYS_openat, [0, 0, 4, 0, 0, 0]),
+            (libc::SYS_statx, [0, 0, 4, 0, 0, 8]),
+            (libc::SYS_unlinkat, [0, 0, 4, 0, 0, 0]),
+        ] {
+            let mut call = item::Syscall {
+                num: num as _,
+                argv,
+                ret: [SKIPPED, 0],
+            };
+            // The path is only terminated beyond its length.
+            let mut data = [0usize; 64];
+            let bytes = unsafe { core::slice::from_raw_parts_mut(data.as_mut_ptr().cast(), 512) };
+            bytes[..4].copy_from_slice(b"/tmp");
+            assert_eq!(
+                unsafe { execute(&mut call, bytes) },
+                Err(EFAULT),
+                "syscall {num}"
+            );
+            assert_eq!(call.ret[0], SKIPPED);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn syscalls() {
+        for (i, num) in SYSCALLS.iter().enumerate() {
+            assert!(!SYSCALLS[..i].contains(num), "duplicate syscall {}", num);
+        }
+        for num in 0..SYSCALL_MAX as c_long {
+            let handled = match num {
+                libc::SYS_exit | libc::SYS_exit_group => handled_exit(num),
+                _ => handled(num),
+            };
+            assert_eq!(handled, SYSCALLS.contains(&num), "syscall {}", num);
+        }
+    }
+}
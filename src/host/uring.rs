@@ -0,0 +1,866 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::syscall::{deref_sockaddr_input, deref_sockaddr_output, raw};
+use super::{deref, deref_aligned, execute_with, Execute, Executor};
+use crate::item::{self, Block, Item, Syscall};
+use crate::libc::{
+    self, pollfd, EAGAIN, EBUSY, EINTR, EIO, ENOMEM, MAP_POPULATE, MAP_SHARED, PROT_READ,
+    PROT_WRITE,
+};
+use crate::{Result, NULL};
+
+use core::ffi::{c_int, c_long};
+use core::mem::size_of;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+#[allow(non_upper_case_globals)]
+const SYS_io_uring_setup: c_long = 425;
+#[allow(non_upper_case_globals)]
+const SYS_io_uring_enter: c_long = 426;
+
+const IORING_OFF_SQ_RING: usize = 0;
+const IORING_OFF_CQ_RING: usize = 0x800_0000;
+const IORING_OFF_SQES: usize = 0x1000_0000;
+const IORING_ENTER_GETEVENTS: usize = 1;
+const IOSQE_IO_HARDLINK: u8 = 1 << 3;
+
+/// Maximum count of items submitted at once.
+const ENTRIES: usize = 64;
+
+/// User data of the entries canceling pending items.
+const CANCEL: u64 = u64::MAX;
+
+/// io_uring operation codes.
+#[derive(Clone, Copy, Debug)]
+#[repr(u8)]
+enum Op {
+    PollAdd = 6,
+    Accept = 13,
+    AsyncCancel = 14,
+    Connect = 16,
+    Close = 19,
+    Read = 22,
+    Write = 23,
+    Send = 26,
+    Recv = 27,
+}
+
+/// `struct io_sqring_offsets`
+#[derive(Debug, Default)]
+#[repr(C)]
+struct SqOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+/// `struct io_cqring_offsets`
+#[derive(Debug, Default)]
+#[repr(C)]
+struct CqOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+/// `struct io_uring_params`
+#[derive(Debug, Default)]
+#[repr(C)]
+struct Params {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqOffsets,
+    cq_off: CqOffsets,
+}
+
+/// `struct io_uring_sqe`
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+struct Sqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    off: u64,
+    addr: u64,
+    len: u32,
+    op_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    splice_fd_in: i32,
+    pad: [u64; 2],
+}
+
+impl Sqe {
+    #[inline]
+    fn new(op: Op, fd: usize, addr: usize, len: u32) -> Self {
+        Self {
+            opcode: op as _,
+            fd: fd as _,
+            addr: addr as _,
+            len,
+            ..Default::default()
+        }
+    }
+}
+
+/// `struct io_uring_cqe`
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+struct Cqe {
+    user_data: u64,
+    res: i32,
+    flags: u32,
+}
+
+/// Shared memory mapping of an io_uring instance.
+#[derive(Debug)]
+struct Mmap {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl Mmap {
+    unsafe fn new(fd: c_int, len: usize, offset: usize) -> Result<Self> {
+        let ptr = raw(
+            libc::SYS_mmap,
+            [
+                0,
+                len,
+                (PROT_READ | PROT_WRITE) as _,
+                (MAP_SHARED | MAP_POPULATE) as _,
+                fd as _,
+                offset,
+            ],
+        )?;
+        Ok(Self { ptr: ptr as _, len })
+    }
+
+    /// Returns a pointer to `T` at `offset` within the mapping.
+    #[inline]
+    fn at<T>(&self, offset: u32) -> *mut T {
+        debug_assert!(offset as usize + size_of::<T>() <= self.len);
+        unsafe { self.ptr.add(offset as _) as _ }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        let _ = unsafe { raw(libc::SYS_munmap, [self.ptr as _, self.len]) };
+    }
+}
+
+/// File descriptor of an io_uring instance.
+#[derive(Debug)]
+struct Fd(c_int);
+
+impl Drop for Fd {
+    fn drop(&mut self) {
+        let _ = unsafe { raw(libc::SYS_close, [self.0 as _]) };
+    }
+}
+
+/// Item submitted to the kernel and pending completion.
+#[derive(Debug)]
+struct Pending<'a> {
+    item: Item<'a>,
+
+    /// `pollfd` to write the returned events into.
+    poll: Option<*mut pollfd>,
+}
+
+/// Prepares a submission queue entry for `call` referencing memory in `data`.
+///
+/// Returns `None` if `call` cannot be executed by io_uring.
+unsafe fn prepare(
+    call: &mut item::Syscall,
+    data: &mut [u8],
+) -> Result<Option<(Sqe, Option<*mut pollfd>)>> {
+    let [a0, a1, a2, a3, a4, _] = call.argv;
+    // Buffers exceeding the length supported by io_uring are left to the native executor.
+    let buf = |data: &mut [u8]| match u32::try_from(a2) {
+        Ok(len) => deref::<u8>(data, a1, a2).map(|buf| Some((buf as usize, len))),
+        Err(_) => Ok(None),
+    };
+    let accept = |data: &mut [u8], flags: usize| -> Result<Sqe> {
+        let (addr, addrlen) = if a1 == NULL {
+            (null_mut(), null_mut())
+        } else {
+            deref_sockaddr_output(data, a1, a2)?
+        };
+        Ok(Sqe {
+            off: addrlen as _,
+            op_flags: flags as _,
+            ..Sqe::new(Op::Accept, a0, addr as _, 0)
+        })
+    };
+    let sqe = match call.num as c_long {
+        libc::SYS_accept => accept(data, 0)?,
+        libc::SYS_accept4 => accept(data, a3)?,
+        libc::SYS_close => Sqe::new(Op::Close, a0, 0, 0),
+        libc::SYS_connect => {
+            let addr = deref_sockaddr_input(data, a1, a2)?;
+            Sqe {
+                off: a2 as _,
+                ..Sqe::new(Op::Connect, a0, addr as _, 0)
+            }
+        }
+        libc::SYS_poll if a1 == 1 && (a2 as c_int) < 0 => {
+            let fds = deref_aligned::<pollfd>(data, a0, 1)?;
+            if (*fds).fd < 0 {
+                return Ok(None);
+            }
+            let sqe = Sqe {
+                op_flags: (*fds).events as u16 as _,
+                ..Sqe::new(Op::PollAdd, (*fds).fd as _, 0, 0)
+            };
+            return Ok(Some((sqe, Some(fds))));
+        }
+        // A negative offset would select the current file position.
+        libc::SYS_pread64 if (a3 as isize) >= 0 => match buf(data)? {
+            Some((buf, len)) => Sqe {
+                off: a3 as _,
+                ..Sqe::new(Op::Read, a0, buf, len)
+            },
+            None => return Ok(None),
+        },
+        libc::SYS_pwrite64 if (a3 as isize) >= 0 => match buf(data)? {
+            Some((buf, len)) => Sqe {
+                off: a3 as _,
+                ..Sqe::new(Op::Write, a0, buf, len)
+            },
+            None => return Ok(None),
+        },
+        libc::SYS_read => match buf(data)? {
+            Some((buf, len)) => Sqe {
+                off: u64::MAX,
+                ..Sqe::new(Op::Read, a0, buf, len)
+            },
+            None => return Ok(None),
+        },
+        libc::SYS_recvfrom if a4 == NULL => match buf(data)? {
+            Some((buf, len)) => Sqe {
+                op_flags: a3 as _,
+                ..Sqe::new(Op::Recv, a0, buf, len)
+            },
+            None => return Ok(None),
+        },
+        libc::SYS_sendto if a4 == NULL => match buf(data)? {
+            Some((buf, len)) => Sqe {
+                op_flags: a3 as _,
+                ..Sqe::new(Op::Send, a0, buf, len)
+            },
+            None => return Ok(None),
+        },
+        libc::SYS_write => match buf(data)? {
+            Some((buf, len)) => Sqe {
+                off: u64::MAX,
+                ..Sqe::new(Op::Write, a0, buf, len)
+            },
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some((sqe, None)))
+}
+
+/// io_uring instance executing the items of a block with a single entry into the kernel.
+///
+/// `accept`, `accept4`, `close`, `connect`, `read`, `write`, `pread64` and `pwrite64` with a
+/// non-negative offset, `recvfrom` and `sendto` without an address, and `poll` of a single file
+/// descriptor without a timeout are submitted to the kernel at once and executed in order.
+/// Other items are passed to the wrapped executor after all items preceding them are
+/// complete. Blocks containing [`Link`](Item::Link) items are passed to the wrapped executor
+/// entirely.
+///
+/// Syscalls are only submitted to the kernel if the wrapped executor allows to
+/// [offload](Executor::offload) a host-private copy of them, which executors checking or
+/// translating items, like [`Enforcer`](super::Enforcer) or [`FdTable`](super::FdTable),
+/// check and translate before the submission queue entry is prepared from it.
+///
+/// Requires Linux 5.6 or newer.
+#[derive(Debug)]
+pub struct Uring<E> {
+    executor: E,
+    fd: Fd,
+    sq: Mmap,
+    cq: Mmap,
+    sqes: Mmap,
+    params: Params,
+
+    /// Tail of the submission queue not yet published to the kernel.
+    tail: u32,
+
+    /// Whether the kernel failed to report completion of canceled items.
+    broken: bool,
+}
+
+impl<E: Executor> Uring<E> {
+    /// Creates a new io_uring instance, which passes items not submitted to the kernel
+    /// to `executor`.
+    pub fn new(executor: E) -> Result<Self> {
+        let mut params = Params::default();
+        let fd = unsafe { raw(SYS_io_uring_setup, [ENTRIES, &mut params as *mut _ as _]) }?;
+        unsafe { Self::map(executor, Fd(fd as _), params) }
+    }
+
+    /// Returns the wrapped executor.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.executor
+    }
+
+    unsafe fn map(executor: E, fd: Fd, params: Params) -> Result<Self> {
+        if (params.sq_entries as usize) < ENTRIES {
+            return Err(ENOMEM);
+        }
+        let sq_len = params.sq_off.array as usize + params.sq_entries as usize * size_of::<u32>();
+        let cq_len = params.cq_off.cqes as usize + params.cq_entries as usize * size_of::<Cqe>();
+        let sqes_len = params.sq_entries as usize * size_of::<Sqe>();
+        let sq = Mmap::new(fd.0, sq_len, IORING_OFF_SQ_RING)?;
+        let cq = Mmap::new(fd.0, cq_len, IORING_OFF_CQ_RING)?;
+        let sqes = Mmap::new(fd.0, sqes_len, IORING_OFF_SQES)?;
+        let tail = (*sq.at::<AtomicU32>(params.sq_off.tail)).load(Ordering::Relaxed);
+        Ok(Self {
+            executor,
+            fd,
+            sq,
+            cq,
+            sqes,
+            params,
+            tail,
+            broken: false,
+        })
+    }
+
+    /// Executes the items contained in `block`.
+    ///
+    /// Items, which cannot be executed, are handled as described for [`execute`](super::execute).
+    /// Returns an error if the io_uring instance fails. In that case, items submitted to the
+    /// kernel are canceled and all remaining items of the block are passed to the wrapped
+    /// executor once the kernel completed all submitted items.
+    ///
+    /// If the kernel fails to report completion of the canceled items, it may still access the
+    /// block, so the remaining items are not executed and the instance becomes unusable. All
+    /// later calls fail with [`EIO`] then.
+    pub fn execute(&mut self, block: &mut [usize]) -> Result<()> {
+        if self.broken {
+            return Err(EIO);
+        }
+        let linked = Block::from(&mut *block)
+            .into_iter()
+            .any(|item| matches!(item, Item::Link(..)));
+        if linked {
+            return execute_with(&mut self.executor, Block::from(block));
+        }
+
+        let mut pending: [Option<Pending<'_>>; ENTRIES] = core::array::from_fn(|_| None);
+        let mut len = 0;
+        let mut failed = None;
+        for mut item in Block::from(block) {
+            if self.broken {
+                break;
+            }
+            if failed.is_some() {
+                let _ = item.execute(&mut self.executor);
+                continue;
+            }
+            let sqe = match &mut item {
+                Item::Syscall(call, data) => unsafe { self.offload(call, data) },
+                _ => Ok(None),
+            };
+            match sqe {
+                Ok(Some((sqe, poll))) => {
+                    if len == ENTRIES {
+                        failed = self.submit(&mut pending[..len]).err();
+                        len = 0;
+                        if failed.is_some() {
+                            let _ = item.execute(&mut self.executor);
+                            continue;
+                        }
+                    }
+                    unsafe { self.push(sqe, len) };
+                    pending[len] = Some(Pending { item, poll });
+                    len += 1;
+                }
+                Ok(None) => {
+                    failed = self.submit(&mut pending[..len]).err();
+                    len = 0;
+                    let _ = item.execute(&mut self.executor);
+                }
+                Err(e) => {
+                    if let Some(ret) = item.ret0_mut() {
+                        *ret = -e as _;
+                    }
+                }
+            }
+        }
+        match failed {
+            Some(e) => Err(e),
+            None => self.submit(&mut pending[..len]),
+        }
+    }
+
+    /// Prepares a submission queue entry for a host-private copy of `call` referencing memory
+    /// in `data`, if the wrapped executor allows to [offload](Executor::offload) it.
+    unsafe fn offload(
+        &mut self,
+        call: &Syscall,
+        data: &mut [u8],
+    ) -> Result<Option<(Sqe, Option<*mut pollfd>)>> {
+        let mut copy = *call;
+        if !self.executor.offload(&mut copy, data)? {
+            return Ok(None);
+        }
+        prepare(&mut copy, data)
+    }
+
+    /// Writes `sqe` into the submission queue as the entry with `index` in the current batch.
+    ///
+    /// Entries of a batch are hard-linked, so that they are executed in order, but failures
+    /// do not cancel the following entries.
+    unsafe fn push(&mut self, sqe: Sqe, index: usize) {
+        if index > 0 {
+            let mask = *self.sq.at::<u32>(self.params.sq_off.ring_mask);
+            let prev = self.tail.wrapping_sub(1) & mask;
+            (*self.sqes.at::<Sqe>(prev * size_of::<Sqe>() as u32)).flags |= IOSQE_IO_HARDLINK;
+        }
+        self.write(Sqe {
+            user_data: index as _,
+            ..sqe
+        });
+    }
+
+    /// Writes `sqe` into the submission queue without publishing it to the kernel.
+    unsafe fn write(&mut self, sqe: Sqe) {
+        let mask = *self.sq.at::<u32>(self.params.sq_off.ring_mask);
+        let slot = self.tail & mask;
+        self.sqes
+            .at::<Sqe>(slot * size_of::<Sqe>() as u32)
+            .write(sqe);
+        self.sq
+            .at::<u32>(self.params.sq_off.array)
+            .add(slot as _)
+            .write(slot);
+        self.tail = self.tail.wrapping_add(1);
+    }
+
+    /// Publishes the entries written into the submission queue to the kernel.
+    fn publish(&self) {
+        let sq_tail = unsafe { &*self.sq.at::<AtomicU32>(self.params.sq_off.tail) };
+        sq_tail.store(self.tail, Ordering::Release);
+    }
+
+    /// Submits the queued entries and waits for completion of all `pending` items.
+    ///
+    /// If the io_uring instance fails, the items are [aborted](Self::abort) and the error
+    /// is returned.
+    fn submit(&mut self, pending: &mut [Option<Pending<'_>>]) -> Result<()> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+        self.publish();
+
+        let (mut submitted, mut completed) = (0, 0);
+        while completed < pending.len() {
+            let argv = [
+                self.fd.0 as _,
+                pending.len() - submitted,
+                pending.len() - completed,
+                IORING_ENTER_GETEVENTS,
+                0,
+                0,
+            ];
+            match unsafe { raw(SYS_io_uring_enter, argv) } {
+                Ok(count) => submitted += count,
+                Err(EINTR) => {}
+                Err(e) => return self.abort(pending).and(Err(e)),
+            }
+            completed += self.complete(pending);
+        }
+        Ok(())
+    }
+
+    /// Removes the entries not consumed by the kernel from the submission queue, cancels the
+    /// `pending` items submitted, waits for their completion and passes the items, which were
+    /// not submitted, to the wrapped executor afterwards.
+    ///
+    /// If waiting fails, the instance is marked unusable and the error is returned without
+    /// executing the items, which were not submitted.
+    fn abort(&mut self, pending: &mut [Option<Pending<'_>>]) -> Result<()> {
+        // The kernel only consumes entries when entered, so unconsumed entries can be removed.
+        let sq_head = unsafe { &*self.sq.at::<AtomicU32>(self.params.sq_off.head) };
+        let head = sq_head.load(Ordering::Acquire);
+        let unsubmitted = self.tail.wrapping_sub(head) as usize;
+        self.tail = head;
+        let (submitted, unsubmitted) = pending.split_at_mut(pending.len() - unsubmitted);
+
+        let mut cancels = 0;
+        for (index, _) in submitted.iter().enumerate().filter(|(_, p)| p.is_some()) {
+            let sqe = Sqe {
+                fd: -1,
+                user_data: CANCEL,
+                ..Sqe::new(Op::AsyncCancel, 0, index, 0)
+            };
+            unsafe { self.write(sqe) };
+            cancels += 1;
+        }
+        self.publish();
+
+        let mut outstanding = cancels;
+        while outstanding > 0 {
+            let argv = [
+                self.fd.0 as _,
+                cancels,
+                outstanding,
+                IORING_ENTER_GETEVENTS,
+                0,
+                0,
+            ];
+            match unsafe { raw(SYS_io_uring_enter, argv) } {
+                Ok(count) => cancels -= count.min(cancels),
+                Err(EINTR | EAGAIN | EBUSY) => {}
+                Err(e) => {
+                    self.broken = true;
+                    return Err(e);
+                }
+            }
+            outstanding -= self.complete(submitted);
+        }
+
+        for pending in unsubmitted.iter_mut().filter_map(Option::take) {
+            let Pending { mut item, .. } = pending;
+            let _ = item.execute(&mut self.executor);
+        }
+        Ok(())
+    }
+
+    /// Writes the results of the `pending` items completed by the kernel and returns the
+    /// count of items completed.
+    fn complete(&mut self, pending: &mut [Option<Pending<'_>>]) -> usize {
+        let (cq_head, cq_tail, cq_mask) = unsafe {
+            (
+                &*self.cq.at::<AtomicU32>(self.params.cq_off.head),
+                &*self.cq.at::<AtomicU32>(self.params.cq_off.tail),
+                *self.cq.at::<u32>(self.params.cq_off.ring_mask),
+            )
+        };
+        let mut completed = 0;
+        let mut head = cq_head.load(Ordering::Relaxed);
+        let tail = cq_tail.load(Ordering::Acquire);
+        while head != tail {
+            let cqe = unsafe {
+                self.cq
+                    .at::<Cqe>(self.params.cq_off.cqes)
+                    .add((head & cq_mask) as _)
+                    .read()
+            };
+            head = head.wrapping_add(1);
+            let Pending { mut item, poll } = match pending
+                .get_mut(cqe.user_data as usize)
+                .and_then(Option::take)
+            {
+                Some(pending) => pending,
+                None => continue,
+            };
+            completed += 1;
+
+            let ret = match poll {
+                Some(fds) if cqe.res >= 0 => {
+                    unsafe { (*fds).revents = cqe.res as _ };
+                    1
+                }
+                _ => cqe.res as isize as _,
+            };
+            if let Some(ret0) = item.ret0_mut() {
+                *ret0 = ret;
+            }
+        }
+        cq_head.store(head, Ordering::Release);
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{Denial, Enforcer, FdTable, Native, Policy, Rule};
+    use crate::item::{BlockWriter, Link};
+    use crate::libc::{EACCES, EBADF, ECANCELED, EFAULT, ENOSYS, EPERM, POLLIN};
+
+    use crate::libc::{SYS_close, SYS_eventfd2, SYS_getpid, SYS_poll, SYS_read, SYS_write};
+
+    fn syscall(num: c_long, argv: [usize; 6]) -> item::Syscall {
+        item::Syscall {
+            num: num as _,
+            argv,
+            ret: [-ENOSYS as _, 0],
+        }
+    }
+
+    /// Returns the first return values of the syscall items in `block`.
+    fn rets<const N: usize>(block: &mut [usize]) -> [usize; N] {
+        let mut rets = [0; N];
+        let mut items = Block::from(block)
+            .into_iter()
+            .filter_map(|mut item| item.ret0_mut().map(|ret| *ret));
+        rets.fill_with(|| items.next().unwrap());
+        assert!(items.next().is_none());
+        rets
+    }
+
+    fn uring<E: Executor>(executor: E) -> Option<Uring<E>> {
+        match Uring::new(executor) {
+            Ok(uring) => Some(uring),
+            // io_uring may be disabled or filtered in the test environment.
+            Err(ENOSYS | EPERM) => None,
+            Err(e) => panic!("failed to create io_uring instance: {e}"),
+        }
+    }
+
+    #[test]
+    fn execute() {
+        let Some(mut uring) = uring(Native) else {
+            return;
+        };
+
+        let mut block = [0; 128];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_eventfd2, [0; 6]), &[])
+            .unwrap();
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        let [fd] = rets(&mut block);
+        assert!(fd as isize > 2);
+
+        let poll = pollfd {
+            fd: fd as _,
+            events: POLLIN,
+            revents: 0,
+        };
+        let poll: [u8; size_of::<pollfd>()] = unsafe { core::mem::transmute(poll) };
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        for (call, data) in [
+            (
+                syscall(SYS_write, [fd, 0, 8, 0, 0, 0]),
+                &3u64.to_ne_bytes()[..],
+            ),
+            (syscall(SYS_poll, [0, 1, -1 as c_int as _, 0, 0, 0]), &poll),
+            (syscall(SYS_getpid, [0; 6]), &[]),
+            (syscall(SYS_read, [fd, 0, 8, 0, 0, 0]), &[0; 8]),
+            (syscall(SYS_read, [fd, 8, 8, 0, 0, 0]), &[0; 8]),
+            (syscall(SYS_close, [fd, 0, 0, 0, 0, 0]), &[]),
+            (syscall(SYS_close, [fd, 0, 0, 0, 0, 0]), &[]),
+        ] {
+            writer.push_syscall(&call, data).unwrap();
+        }
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        assert_eq!(
+            rets(&mut block),
+            [8, 1, -ENOSYS as _, 8, -EFAULT as _, 0, -EBADF as _]
+        );
+
+        let mut items = Block::from(&mut block[..]).into_iter();
+        assert!(matches!(
+            items.nth(1),
+            Some(Item::Syscall(_, data)) if data[6..8] == POLLIN.to_ne_bytes()
+        ));
+        assert!(matches!(
+            items.nth(1),
+            Some(Item::Syscall(_, data)) if data == 3u64.to_ne_bytes()
+        ));
+    }
+
+    #[test]
+    fn abort() {
+        let Some(mut uring) = uring(Native) else {
+            return;
+        };
+
+        let mut block = [0; 128];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_eventfd2, [0; 6]), &[])
+            .unwrap();
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        let [fd] = rets(&mut block);
+
+        let poll = pollfd {
+            fd: fd as _,
+            events: POLLIN,
+            revents: 0,
+        };
+        let poll: [u8; size_of::<pollfd>()] = unsafe { core::mem::transmute(poll) };
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_poll, [0, 1, -1 as c_int as _, 0, 0, 0]), &poll)
+            .unwrap();
+        writer
+            .push_syscall(
+                &syscall(SYS_write, [fd, 0, 8, 0, 0, 0]),
+                &1u64.to_ne_bytes(),
+            )
+            .unwrap();
+
+        // Only the blocking `poll` is submitted before the io_uring instance is aborted.
+        let mut pending: [Option<Pending<'_>>; 2] = [None, None];
+        for (index, mut item) in Block::from(&mut block[..]).into_iter().enumerate() {
+            let Item::Syscall(call, data) = &mut item else {
+                unreachable!()
+            };
+            let (sqe, poll) = unsafe { prepare(call, data) }.unwrap().unwrap();
+            unsafe { uring.push(sqe, index) };
+            pending[index] = Some(Pending { item, poll });
+        }
+        uring.publish();
+        let argv = [uring.fd.0 as _, 1, 0, 0, 0, 0];
+        assert_eq!(unsafe { raw(SYS_io_uring_enter, argv) }, Ok(1));
+        assert_eq!(uring.abort(&mut pending), Ok(()));
+        assert!(pending.iter().all(Option::is_none));
+        assert_eq!(rets(&mut block), [-ECANCELED as _, 8]);
+
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_close, [fd, 0, 0, 0, 0, 0]), &[])
+            .unwrap();
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        assert_eq!(rets(&mut block), [0]);
+    }
+
+    #[test]
+    fn broken() {
+        let Some(mut uring) = uring(Native) else {
+            return;
+        };
+
+        let mut block = [0; 128];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_eventfd2, [0; 6]), &[])
+            .unwrap();
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        let [fd] = rets(&mut block);
+
+        let poll = pollfd {
+            fd: fd as _,
+            events: POLLIN,
+            revents: 0,
+        };
+        let poll: [u8; size_of::<pollfd>()] = unsafe { core::mem::transmute(poll) };
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_poll, [0, 1, -1 as c_int as _, 0, 0, 0]), &poll)
+            .unwrap();
+
+        let mut pending: [Option<Pending<'_>>; 1] = [None];
+        let mut item = Block::from(&mut block[..]).into_iter().next().unwrap();
+        let Item::Syscall(call, data) = &mut item else {
+            unreachable!()
+        };
+        let (sqe, poll) = unsafe { prepare(call, data) }.unwrap().unwrap();
+        unsafe { uring.push(sqe, 0) };
+        pending[0] = Some(Pending { item, poll });
+        uring.publish();
+        let argv = [uring.fd.0 as _, 1, 0, 0, 0, 0];
+        assert_eq!(unsafe { raw(SYS_io_uring_enter, argv) }, Ok(1));
+
+        // Waiting for the canceled `poll` fails, as the file descriptor is invalid.
+        let ring_fd = core::mem::replace(&mut uring.fd.0, -1);
+        assert_eq!(uring.abort(&mut pending), Err(EBADF));
+        uring.fd.0 = ring_fd;
+
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_close, [fd, 0, 0, 0, 0, 0]), &[])
+            .unwrap();
+        assert_eq!(uring.execute(&mut block), Err(EIO));
+        assert_eq!(rets(&mut block), [-ENOSYS as _]);
+        assert_eq!(unsafe { raw(SYS_close, [fd]) }, Ok(0));
+    }
+
+    #[test]
+    fn wrapped() {
+        let rules = [Rule::Deny(SYS_write)];
+        let enforcer = Enforcer::new(
+            Policy::new(&rules, EACCES),
+            FdTable::<_, 4>::new(Native),
+            |_: &Denial<'_, '_>| {},
+        );
+        let Some(mut uring) = uring(enforcer) else {
+            return;
+        };
+
+        let mut block = [0; 128];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_eventfd2, [3, 0, 0, 0, 0, 0]), &[])
+            .unwrap();
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        assert_eq!(rets(&mut block), [0]);
+
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        for (call, data) in [
+            (syscall(SYS_read, [0, 0, 8, 0, 0, 0]), &[0; 8][..]),
+            (syscall(SYS_write, [0, 0, 8, 0, 0, 0]), &1u64.to_ne_bytes()),
+            (syscall(SYS_close, [0; 6]), &[]),
+            (syscall(SYS_read, [0, 0, 8, 0, 0, 0]), &[0; 8]),
+        ] {
+            writer.push_syscall(&call, data).unwrap();
+        }
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        assert_eq!(rets(&mut block), [8, -EACCES as _, 0, -EBADF as _]);
+        assert!(matches!(
+            Block::from(&mut block[..]).into_iter().next(),
+            Some(Item::Syscall(_, data)) if data == 3u64.to_ne_bytes()
+        ));
+        assert_eq!(uring.into_inner().denials(), 1);
+    }
+
+    #[test]
+    fn linked() {
+        let Some(mut uring) = uring(Native) else {
+            return;
+        };
+
+        let mut block = [0; 64];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_eventfd2, [0; 6]), &[])
+            .unwrap();
+        writer.push_link(&Link { back: 1, arg: 0 }).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_close, [0; 6]), &[])
+            .unwrap();
+        writer
+            .push_syscall(&syscall(SYS_close, [-1 as c_int as _, 0, 0, 0, 0, 0]), &[])
+            .unwrap();
+        writer.push_link(&Link { back: 1, arg: 0 }).unwrap();
+        writer
+            .push_syscall(&syscall(SYS_close, [0; 6]), &[])
+            .unwrap();
+        assert_eq!(uring.execute(&mut block), Ok(()));
+        let [fd, close, ebadf, canceled] = rets(&mut block);
+        assert!(fd as isize > 2);
+        assert_eq!([close, ebadf, canceled], [0, -EBADF as _, -ECANCELED as _]);
+    }
+}
@@ -1,9 +1,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{enarxcall, gdbcall, syscall, Item, Kind, LARGEST_ITEM_SIZE};
+use super::{
+    enarxcall, gdbcall, hello, link, notification, syscall, Header, Item, Kind, LARGEST_ITEM_SIZE,
+};
+use crate::libc::EINVAL;
+use crate::Error;
 
-use core::convert::TryInto;
-use core::mem::{align_of, size_of};
+use core::convert::{TryFrom, TryInto};
+use core::mem::{size_of, size_of_val};
+use core::slice;
+
+const HEADER_USIZE_COUNT: usize = size_of::<Header>() / size_of::<usize>();
 
 /// Untrusted `sallyport` block.
 #[derive(Debug, PartialEq)]
@@ -48,66 +55,160 @@ impl<'a> From<&'a mut [usize]> for Block<'a> {
 impl<'a> From<Block<'a>> for Option<(Option<Item<'a>>, Block<'a>)> {
     #[inline]
     fn from(block: Block<'a>) -> Self {
-        match block.0 {
-            [size, kind, tail @ ..] => {
-                #[inline]
-                fn decode_item<'a, const USIZE_COUNT: usize, T>(
-                    size: usize,
-                    tail: &'a mut [usize],
-                ) -> Option<((&'a mut T, &'a mut [u8]), Block<'a>)>
-                where
-                    &'a mut T: From<&'a mut [usize; USIZE_COUNT]>,
-                {
-                    let (payload, tail) = tail.split_at_mut(size / size_of::<usize>());
-
-                    debug_assert!(size >= size_of::<T>());
-                    let data_size = size.checked_sub(size_of::<T>())?;
-
-                    debug_assert_eq!(size_of::<T>(), USIZE_COUNT * size_of::<usize>());
-                    let (item_payload, data) = payload.split_at_mut(USIZE_COUNT);
-
-                    let item_payload: &mut [usize; USIZE_COUNT] = item_payload.try_into().ok()?;
-                    let (prefix, data, suffix) = unsafe { data.align_to_mut::<u8>() };
-                    if !prefix.is_empty() || !suffix.is_empty() || data.len() != data_size {
-                        debug_assert!(prefix.is_empty());
-                        debug_assert!(suffix.is_empty());
-                        debug_assert_eq!(data.len(), data_size);
-                        return None;
-                    }
-                    Some(((item_payload.into(), data), tail.into()))
-                }
+        decode(block.0)
+            .ok()
+            .flatten()
+            .map(|(item, tail)| (item, tail.into()))
+    }
+}
 
-                if *size % align_of::<usize>() != 0 {
-                    debug_assert_eq!(*size % align_of::<usize>(), 0);
-                    return None;
-                }
-                match (*kind).try_into() {
-                    Ok(Kind::End) => {
-                        debug_assert_eq!(*size, 0);
-                        None
-                    }
+/// Reason of a [`DecodeError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    /// The remainder of the block is too short to contain an item [header](super::Header).
+    TruncatedHeader,
 
-                    Ok(Kind::Syscall) => {
-                        decode_item::<{ syscall::USIZE_COUNT }, syscall::Payload>(*size, tail)
-                            .map(|((call, data), tail)| (Some(Item::Syscall(call, data)), tail))
-                    }
+    /// The item `size` is not a multiple of the size of `usize`.
+    MisalignedSize { size: usize },
 
-                    Ok(Kind::Gdbcall) => {
-                        decode_item::<{ gdbcall::USIZE_COUNT }, gdbcall::Payload>(*size, tail)
-                            .map(|((call, data), tail)| (Some(Item::Gdbcall(call, data)), tail))
-                    }
+    /// The item `size` exceeds the remainder of the block of `available` bytes.
+    Oversized { size: usize, available: usize },
 
-                    Ok(Kind::Enarxcall) => {
-                        decode_item::<{ enarxcall::USIZE_COUNT }, enarxcall::Payload>(*size, tail)
-                            .map(|((call, data), tail)| (Some(Item::Enarxcall(call, data)), tail))
-                    }
+    /// The item `size` is less than the `expected` size of the payload of its kind.
+    ShortPayload { size: usize, expected: usize },
 
-                    Err(_) => Some((None, tail.split_at_mut(*size / size_of::<usize>()).1.into())),
-                }
-            }
-            _ => None,
+    /// The payload contains an unknown call number.
+    InvalidNumber { num: usize },
+
+    /// An [`End`](Kind::End) item has a non-zero `size`.
+    InvalidEnd { size: usize },
+}
+
+/// Error returned when decoding a malformed [`Block`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    /// Offset of the offending item header from the beginning of the block in `usize` elements.
+    pub offset: usize,
+
+    /// Reason of the error.
+    pub kind: DecodeErrorKind,
+}
+
+impl From<DecodeError> for Error {
+    #[inline]
+    fn from(_: DecodeError) -> Self {
+        EINVAL
+    }
+}
+
+/// Decoded item, if the kind is known, and the remainder of the block.
+type Decoded<'a> = Option<(Option<Item<'a>>, &'a mut [usize])>;
+
+/// Decodes the first item in `block` and returns it along with the remainder of the `block`.
+///
+/// Items of unknown kind are skipped by returning `None` in place of the item.
+/// Returns `Ok(None)` if the end of the block is reached.
+fn decode(block: &mut [usize]) -> core::result::Result<Decoded<'_>, DecodeErrorKind> {
+    #[inline]
+    fn decode_item<'a, const USIZE_COUNT: usize, T>(
+        size: usize,
+        payload: &'a mut [usize],
+    ) -> core::result::Result<(&'a mut T, &'a mut [u8]), DecodeErrorKind>
+    where
+        &'a mut T: From<&'a mut [usize; USIZE_COUNT]>,
+    {
+        let short = DecodeErrorKind::ShortPayload {
+            size,
+            expected: size_of::<T>(),
+        };
+        if payload.len() < USIZE_COUNT {
+            return Err(short);
         }
+        let (item_payload, data) = payload.split_at_mut(USIZE_COUNT);
+        let item_payload: &mut [usize; USIZE_COUNT] = item_payload.try_into().map_err(|_| short)?;
+
+        let data_len = size_of_val(data);
+        let data = unsafe { slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, data_len) };
+        Ok((item_payload.into(), data))
+    }
+
+    #[inline]
+    fn validate_number<N: TryFrom<usize>>(
+        payload: &[usize],
+    ) -> core::result::Result<(), DecodeErrorKind> {
+        match payload.first() {
+            Some(&num) if N::try_from(num).is_err() => Err(DecodeErrorKind::InvalidNumber { num }),
+            _ => Ok(()),
+        }
+    }
+
+    if block.is_empty() {
+        return Ok(None);
+    }
+    if block.len() < HEADER_USIZE_COUNT {
+        return Err(DecodeErrorKind::TruncatedHeader);
+    }
+    let (header, tail) = block.split_at_mut(HEADER_USIZE_COUNT);
+    let (size, kind) = (header[0], header[1]);
+
+    if size % size_of::<usize>() != 0 {
+        return Err(DecodeErrorKind::MisalignedSize { size });
     }
+    let kind = match kind.try_into() {
+        Ok(Kind::End) if size == 0 => return Ok(None),
+        Ok(Kind::End) => return Err(DecodeErrorKind::InvalidEnd { size }),
+        kind => kind,
+    };
+    if size / size_of::<usize>() > tail.len() {
+        return Err(DecodeErrorKind::Oversized {
+            size,
+            available: size_of_val(tail),
+        });
+    }
+    let (payload, tail) = tail.split_at_mut(size / size_of::<usize>());
+
+    let item = match kind {
+        Ok(Kind::Syscall) => {
+            let (call, data) =
+                decode_item::<{ syscall::USIZE_COUNT }, syscall::Payload>(size, payload)?;
+            Some(Item::Syscall(call, data))
+        }
+
+        Ok(Kind::Gdbcall) => {
+            validate_number::<gdbcall::Number>(payload)?;
+            let (call, data) =
+                decode_item::<{ gdbcall::USIZE_COUNT }, gdbcall::Payload>(size, payload)?;
+            Some(Item::Gdbcall(call, data))
+        }
+
+        Ok(Kind::Enarxcall) => {
+            validate_number::<enarxcall::Number>(payload)?;
+            let (call, data) =
+                decode_item::<{ enarxcall::USIZE_COUNT }, enarxcall::Payload>(size, payload)?;
+            Some(Item::Enarxcall(call, data))
+        }
+
+        Ok(Kind::Hello) => {
+            let (call, data) =
+                decode_item::<{ hello::USIZE_COUNT }, hello::Payload>(size, payload)?;
+            Some(Item::Hello(call, data))
+        }
+
+        Ok(Kind::Notification) => {
+            validate_number::<notification::Number>(payload)?;
+            let (call, data) =
+                decode_item::<{ notification::USIZE_COUNT }, notification::Payload>(size, payload)?;
+            Some(Item::Notification(call, data))
+        }
+
+        Ok(Kind::Link) => {
+            let (call, data) = decode_item::<{ link::USIZE_COUNT }, link::Payload>(size, payload)?;
+            Some(Item::Link(call, data))
+        }
+
+        Ok(Kind::End) | Err(_) => None,
+    };
+    Ok(Some((item, tail)))
 }
 
 impl<'a> IntoIterator for Block<'a> {
@@ -119,6 +220,21 @@ impl<'a> IntoIterator for Block<'a> {
     }
 }
 
+impl<'a> Block<'a> {
+    /// Returns an iterator over the items in the block, which reports malformed contents
+    /// instead of silently stopping.
+    ///
+    /// The iterator yields a [`DecodeError`] at most once and stops afterwards.
+    /// Items of unknown kind are skipped.
+    #[inline]
+    pub fn try_iter(self) -> TryBlockIterator<'a> {
+        TryBlockIterator {
+            block: Some(self.0),
+            offset: 0,
+        }
+    }
+}
+
 /// An iterator for `Item` over a `Block`
 pub struct BlockIterator<'a>(Option<Block<'a>>);
 
@@ -144,15 +260,57 @@ impl<'a> Iterator for BlockIterator<'a> {
     }
 }
 
+/// A fallible iterator for `Item` over a `Block`, which never panics on malformed input.
+pub struct TryBlockIterator<'a> {
+    block: Option<&'a mut [usize]>,
+    offset: usize,
+}
+
+impl TryBlockIterator<'_> {
+    /// Returns the offset of the next item header from the beginning of the block
+    /// in `usize` elements.
+    ///
+    /// Once the iterator is exhausted without an error, this is the offset of the
+    /// [`End`](Kind::End) item, if the block contains one.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for TryBlockIterator<'a> {
+    type Item = core::result::Result<Item<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let block = self.block.take()?;
+            let len = block.len();
+            match decode(block) {
+                Ok(Some((item, tail))) => {
+                    self.offset += len - tail.len();
+                    self.block = Some(tail);
+                    if let Some(item) = item {
+                        return Some(Ok(item));
+                    }
+                }
+                Ok(None) => return None,
+                Err(kind) => {
+                    return Some(Err(DecodeError {
+                        offset: self.offset,
+                        kind,
+                    }))
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::item::Header;
 
     use libc::{SYS_exit, SYS_read, ENOSYS};
 
-    const HEADER_USIZE_COUNT: usize = size_of::<Header>() / size_of::<usize>();
-
     #[test]
     fn block_size_hint() {
         const LARGEST_ITEM_USIZE_COUNT: usize = syscall::USIZE_COUNT;
@@ -224,4 +382,124 @@ mod tests {
         );
         assert!(block_iter.next().is_none());
     }
+
+    #[test]
+    fn try_iter() {
+        let mut block: [usize; 3 * HEADER_USIZE_COUNT + syscall::USIZE_COUNT + 2] = [
+            2 * size_of::<usize>(), // size
+            0xff,                   // kind
+            0xdead,                 // -
+            0xbeef,                 // -
+            /* --------------------- */
+            syscall::USIZE_COUNT * size_of::<usize>(), // size
+            Kind::Syscall as _,                        // kind
+            SYS_exit as _,                             // num
+            5,                                         // status
+            0,                                         // -
+            0,                                         // -
+            0,                                         // -
+            0,                                         // -
+            0,                                         // -
+            -ENOSYS as _,                              // ret
+            0,                                         // -
+            /* --------------------- */
+            0,              // size
+            Kind::End as _, // kind
+        ];
+
+        let mut block_iter = Block::from(&mut block[..]).try_iter();
+        assert!(matches!(
+            block_iter.next(),
+            Some(Ok(Item::Syscall(syscall::Payload { num, .. }, _))) if *num == SYS_exit as _
+        ));
+        assert!(block_iter.next().is_none());
+    }
+
+    #[test]
+    fn try_iter_errors() {
+        fn first_error(block: &mut [usize]) -> Option<DecodeError> {
+            Block::from(block).try_iter().find_map(|item| item.err())
+        }
+
+        let mut exit = [0; HEADER_USIZE_COUNT + syscall::USIZE_COUNT];
+        exit[0] = syscall::USIZE_COUNT * size_of::<usize>();
+        exit[1] = Kind::Syscall as _;
+        exit[2] = SYS_exit as _;
+
+        let mut block = [exit, exit].concat();
+        block.push(0);
+        assert_eq!(
+            first_error(&mut block),
+            Some(DecodeError {
+                offset: 2 * exit.len(),
+                kind: DecodeErrorKind::TruncatedHeader,
+            })
+        );
+
+        let mut block = [exit, exit].concat();
+        block[exit.len()] = 3;
+        assert_eq!(
+            first_error(&mut block),
+            Some(DecodeError {
+                offset: exit.len(),
+                kind: DecodeErrorKind::MisalignedSize { size: 3 },
+            })
+        );
+
+        let mut block = exit.to_vec();
+        block[0] = usize::MAX - 7;
+        assert_eq!(
+            first_error(&mut block),
+            Some(DecodeError {
+                offset: 0,
+                kind: DecodeErrorKind::Oversized {
+                    size: usize::MAX - 7,
+                    available: syscall::USIZE_COUNT * size_of::<usize>(),
+                },
+            })
+        );
+
+        let mut block = exit.to_vec();
+        block[0] = size_of::<usize>();
+        assert_eq!(
+            first_error(&mut block),
+            Some(DecodeError {
+                offset: 0,
+                kind: DecodeErrorKind::ShortPayload {
+                    size: size_of::<usize>(),
+                    expected: size_of::<syscall::Payload>(),
+                },
+            })
+        );
+
+        let mut block = exit.to_vec();
+        block[1] = Kind::Enarxcall as _;
+        block[2] = 0xff;
+        assert_eq!(
+            first_error(&mut block),
+            Some(DecodeError {
+                offset: 0,
+                kind: DecodeErrorKind::InvalidNumber { num: 0xff },
+            })
+        );
+
+        let mut block = exit.to_vec();
+        block[1] = Kind::End as _;
+        assert_eq!(
+            first_error(&mut block),
+            Some(DecodeError {
+                offset: 0,
+                kind: DecodeErrorKind::InvalidEnd {
+                    size: syscall::USIZE_COUNT * size_of::<usize>()
+                },
+            })
+        );
+
+        let mut block = [exit, exit].concat();
+        block[0] = usize::MAX - 7;
+        let mut block_iter = Block::from(&mut block[..]).try_iter();
+        assert!(matches!(block_iter.next(), Some(Err(_))));
+        assert!(block_iter.next().is_none());
+        assert!(Block::from(&mut block[..]).into_iter().next().is_none());
+    }
 }
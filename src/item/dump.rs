@@ -0,0 +1,484 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Human-readable representation of `sallyport` items akin to `strace`.
+//!
+//! Syscall arguments referencing the data section are resolved, where the layout of the
+//! referenced data is known, for example, paths, buffers, socket addresses and timespecs.
+
+use super::{Block, Item};
+use crate::libc::{self, AF_INET};
+use crate::NULL;
+
+use core::ffi::c_long;
+use core::fmt::{Result, Write};
+use core::mem::size_of;
+
+/// Maximum count of bytes of a buffer argument written.
+pub const BUF_MAX: usize = 32;
+
+/// Syscall argument representation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Arg {
+    /// Signed integer.
+    Int,
+    /// Unsigned integer in hexadecimal notation.
+    Hex,
+    /// Offset of unresolved data within the data section.
+    Ptr,
+    /// Offset of a NUL-terminated string within the data section.
+    Str,
+    /// Offset of a buffer within the data section with length passed in the argument at index.
+    Buf(usize),
+    /// Same as [`Arg::Buf`], but the length is limited by the first return value.
+    OutBuf(usize),
+    /// Offset of a socket address within the data section with length passed in the argument
+    /// at index.
+    Sockaddr(usize),
+    /// Offset of a [`timespec`](libc::timespec) within the data section.
+    Timespec,
+}
+
+use Arg::*;
+
+/// Syscalls with known names and argument representation.
+const SYSCALLS: &[(c_long, &str, &[Arg])] = &[
+    (libc::SYS_accept, "accept", &[Int, Ptr, Ptr]),
+    (libc::SYS_accept4, "accept4", &[Int, Ptr, Ptr, Hex]),
+    (libc::SYS_arch_prctl, "arch_prctl", &[Hex, Hex]),
+    (libc::SYS_bind, "bind", &[Int, Sockaddr(2), Int]),
+    (libc::SYS_brk, "brk", &[Hex]),
+    (libc::SYS_clock_getres, "clock_getres", &[Int, Timespec]),
+    (libc::SYS_clock_gettime, "clock_gettime", &[Int, Timespec]),
+    (libc::SYS_close, "close", &[Int]),
+    (libc::SYS_connect, "connect", &[Int, Sockaddr(2), Int]),
+    (libc::SYS_dup, "dup", &[Int]),
+    (libc::SYS_dup2, "dup2", &[Int, Int]),
+    (libc::SYS_dup3, "dup3", &[Int, Int, Hex]),
+    (libc::SYS_epoll_create1, "epoll_create1", &[Hex]),
+    (libc::SYS_epoll_ctl, "epoll_ctl", &[Int, Int, Int, Ptr]),
+    (
+        libc::SYS_epoll_pwait,
+        "epoll_pwait",
+        &[Int, Ptr, Int, Int, Ptr, Int],
+    ),
+    (libc::SYS_epoll_wait, "epoll_wait", &[Int, Ptr, Int, Int]),
+    (libc::SYS_eventfd2, "eventfd2", &[Int, Hex]),
+    (libc::SYS_exit, "exit", &[Int]),
+    (libc::SYS_exit_group, "exit_group", &[Int]),
+    (libc::SYS_fchmod, "fchmod", &[Int, Hex]),
+    (libc::SYS_fcntl, "fcntl", &[Int, Int, Hex]),
+    (libc::SYS_fstat, "fstat", &[Int, Ptr]),
+    (libc::SYS_ftruncate, "ftruncate", &[Int, Int]),
+    (libc::SYS_getdents64, "getdents64", &[Int, OutBuf(2), Int]),
+    (libc::SYS_getegid, "getegid", &[]),
+    (libc::SYS_geteuid, "geteuid", &[]),
+    (libc::SYS_getgid, "getgid", &[]),
+    (libc::SYS_getpid, "getpid", &[]),
+    (libc::SYS_getrandom, "getrandom", &[OutBuf(1), Int, Hex]),
+    (libc::SYS_getsockname, "getsockname", &[Int, Ptr, Ptr]),
+    (libc::SYS_getuid, "getuid", &[]),
+    (libc::SYS_ioctl, "ioctl", &[Int, Hex, Hex]),
+    (libc::SYS_listen, "listen", &[Int, Int]),
+    (libc::SYS_lseek, "lseek", &[Int, Int, Int]),
+    (libc::SYS_madvise, "madvise", &[Hex, Int, Int]),
+    (libc::SYS_mkdirat, "mkdirat", &[Int, Str, Int, Hex]),
+    (libc::SYS_mmap, "mmap", &[Hex, Int, Hex, Hex, Int, Int]),
+    (libc::SYS_mprotect, "mprotect", &[Hex, Int, Hex]),
+    (libc::SYS_mremap, "mremap", &[Hex, Int, Int, Hex, Hex]),
+    (libc::SYS_munmap, "munmap", &[Hex, Int]),
+    (libc::SYS_nanosleep, "nanosleep", &[Timespec, Timespec]),
+    (
+        libc::SYS_newfstatat,
+        "newfstatat",
+        &[Int, Str, Int, Ptr, Hex],
+    ),
+    (libc::SYS_open, "open", &[Str, Hex, Hex]),
+    (libc::SYS_openat, "openat", &[Int, Str, Int, Hex, Hex]),
+    (libc::SYS_poll, "poll", &[Ptr, Int, Int]),
+    (libc::SYS_pread64, "pread64", &[Int, OutBuf(2), Int, Int]),
+    (libc::SYS_preadv, "preadv", &[Int, Ptr, Int, Int]),
+    (libc::SYS_pwrite64, "pwrite64", &[Int, Buf(2), Int, Int]),
+    (libc::SYS_pwritev, "pwritev", &[Int, Ptr, Int, Int]),
+    (libc::SYS_read, "read", &[Int, OutBuf(2), Int]),
+    (libc::SYS_readlink, "readlink", &[Str, OutBuf(2), Int]),
+    (libc::SYS_readv, "readv", &[Int, Ptr, Int]),
+    (
+        libc::SYS_recvfrom,
+        "recvfrom",
+        &[Int, OutBuf(2), Int, Hex, Ptr, Ptr],
+    ),
+    (libc::SYS_recvmsg, "recvmsg", &[Int, Ptr, Hex]),
+    (libc::SYS_renameat2, "renameat2", &[Int, Str, Int, Str, Hex]),
+    (
+        libc::SYS_rt_sigaction,
+        "rt_sigaction",
+        &[Int, Ptr, Ptr, Int],
+    ),
+    (
+        libc::SYS_rt_sigprocmask,
+        "rt_sigprocmask",
+        &[Int, Ptr, Ptr, Int],
+    ),
+    (libc::SYS_sendmsg, "sendmsg", &[Int, Ptr, Hex]),
+    (
+        libc::SYS_sendto,
+        "sendto",
+        &[Int, Buf(2), Int, Hex, Sockaddr(5), Int],
+    ),
+    (libc::SYS_set_tid_address, "set_tid_address", &[Hex]),
+    (
+        libc::SYS_setsockopt,
+        "setsockopt",
+        &[Int, Int, Int, Ptr, Int],
+    ),
+    (libc::SYS_sigaltstack, "sigaltstack", &[Ptr, Ptr]),
+    (libc::SYS_socket, "socket", &[Int, Int, Int]),
+    (libc::SYS_statx, "statx", &[Int, Str, Int, Hex, Hex, Ptr]),
+    (libc::SYS_sync, "sync", &[]),
+    (libc::SYS_uname, "uname", &[Ptr]),
+    (libc::SYS_unlinkat, "unlinkat", &[Int, Str, Int, Hex]),
+    (libc::SYS_write, "write", &[Int, Buf(2), Int]),
+    (libc::SYS_writev, "writev", &[Int, Ptr, Int]),
+];
+
+/// Returns the name of syscall `num`, if known.
+#[inline]
+pub fn syscall_name(num: usize) -> Option<&'static str> {
+    SYSCALLS
+        .iter()
+        .find(|(n, ..)| *n as usize == num)
+        .map(|(_, name, _)| *name)
+}
+
+/// Returns `len` bytes of `data` at `offset`, if available.
+fn resolve(data: &[u8], offset: usize, len: usize) -> Option<&[u8]> {
+    data.get(offset..)?.get(..len)
+}
+
+/// Writes `bytes` as an escaped string, truncated to [`BUF_MAX`] bytes.
+fn write_bytes(w: &mut impl Write, bytes: &[u8]) -> Result {
+    w.write_char('"')?;
+    for b in bytes.iter().take(BUF_MAX) {
+        match b {
+            b'"' => w.write_str("\\\"")?,
+            b'\\' => w.write_str("\\\\")?,
+            b'\n' => w.write_str("\\n")?,
+            b'\t' => w.write_str("\\t")?,
+            0x20..=0x7e => w.write_char(*b as char)?,
+            _ => write!(w, "\\x{:02x}", b)?,
+        }
+    }
+    w.write_char('"')?;
+    if bytes.len() > BUF_MAX {
+        w.write_str("...")?;
+    }
+    Ok(())
+}
+
+fn write_sockaddr(w: &mut impl Write, addr: &[u8]) -> Result {
+    let family = match addr {
+        [a, b, ..] => u16::from_ne_bytes([*a, *b]),
+        _ => return w.write_str("{...}"),
+    };
+    match addr {
+        [_, _, p0, p1, a0, a1, a2, a3, ..] if family == AF_INET as u16 => write!(
+            w,
+            "{{sa_family=AF_INET, sin_port={}, sin_addr={}.{}.{}.{}}}",
+            u16::from_be_bytes([*p0, *p1]),
+            a0,
+            a1,
+            a2,
+            a3
+        ),
+        _ => write!(w, "{{sa_family={}, ...}}", family),
+    }
+}
+
+fn write_timespec(w: &mut impl Write, ts: &[u8]) -> Result {
+    let (sec, nsec) = ts.split_at(size_of::<i64>());
+    write!(
+        w,
+        "{{tv_sec={}, tv_nsec={}}}",
+        i64::from_ne_bytes(sec.try_into().unwrap()),
+        i64::from_ne_bytes(nsec.try_into().unwrap())
+    )
+}
+
+/// Writes the argument at index `i` of `argv` represented as `arg`.
+fn write_arg(
+    w: &mut impl Write,
+    arg: Arg,
+    i: usize,
+    argv: &[usize; 6],
+    ret: usize,
+    data: &[u8],
+) -> Result {
+    let val = argv[i];
+    let resolved = match arg {
+        Int => return write!(w, "{}", val as isize),
+        Hex => return write!(w, "{:#x}", val),
+        _ if val == NULL => return w.write_str("NULL"),
+        Ptr => None,
+        Str => data.get(val..).and_then(|tail| {
+            let len = tail.iter().position(|&b| b == 0)?;
+            Some(&tail[..len])
+        }),
+        Buf(len) => resolve(data, val, argv[len]),
+        OutBuf(_) if (ret as isize) < 0 => resolve(data, val, 0),
+        OutBuf(len) => resolve(data, val, argv[len].min(ret)),
+        Sockaddr(len) => match resolve(data, val, argv[len]) {
+            Some(addr) => return write_sockaddr(w, addr),
+            None => None,
+        },
+        Timespec => match resolve(data, val, size_of::<libc::timespec>()) {
+            Some(ts) => return write_timespec(w, ts),
+            None => None,
+        },
+    };
+    match resolved {
+        Some(bytes) => write_bytes(w, bytes),
+        None => write!(w, "&{:#x}", val),
+    }
+}
+
+/// Writes the return value `ret`, or the transition from `before` to `ret` if they differ.
+fn write_ret(w: &mut impl Write, ret: usize, before: Option<usize>) -> Result {
+    match before {
+        Some(before) if before != ret => {
+            write!(w, " = {} -> {}", before as isize, ret as isize)
+        }
+        _ => write!(w, " = {}", ret as isize),
+    }
+}
+
+fn write_item_ret(w: &mut impl Write, item: &Item<'_>, before: Option<usize>) -> Result {
+    match item {
+        Item::Syscall(call, data) => {
+            let args = match SYSCALLS.iter().find(|(n, ..)| *n as usize == call.num) {
+                Some((_, name, args)) => {
+                    w.write_str(name)?;
+                    *args
+                }
+                None => {
+                    write!(w, "syscall_{}", call.num)?;
+                    &[Hex; 6]
+                }
+            };
+            w.write_char('(')?;
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    w.write_str(", ")?;
+                }
+                write_arg(w, *arg, i, &call.argv, call.ret[0], data)?;
+            }
+            w.write_char(')')?;
+            write_ret(w, call.ret[0], before)
+        }
+        Item::Gdbcall(call, _) => {
+            let [a0, a1, a2, a3] = call.argv;
+            write!(
+                w,
+                "gdbcall::{:?}({:#x}, {:#x}, {:#x}, {:#x})",
+                call.num, a0, a1, a2, a3
+            )?;
+            write_ret(w, call.ret, before)
+        }
+        Item::Enarxcall(call, _) => {
+            let [a0, a1, a2, a3] = call.argv;
+            write!(
+                w,
+                "enarxcall::{:?}({:#x}, {:#x}, {:#x}, {:#x})",
+                call.num, a0, a1, a2, a3
+            )?;
+            write_ret(w, call.ret, before)
+        }
+        Item::Hello(call, data) => {
+            w.write_str("hello(")?;
+            match resolve(data, call.version, call.version_len) {
+                Some(version) => write_bytes(w, version)?,
+                None => write!(w, "&{:#x}", call.version)?,
+            }
+            w.write_str(", ")?;
+            match resolve(data, call.requires, call.requires_len) {
+                Some(requires) => write_bytes(w, requires)?,
+                None => write!(w, "&{:#x}", call.requires)?,
+            }
+            write!(w, ", &{:#x})", call.reply)?;
+            write_ret(w, call.ret, before)
+        }
+        Item::Notification(notification, _) => {
+            let [a0, a1] = notification.argv;
+            write!(w, "notification::{:?}({}, {})", notification.num, a0, a1)
+        }
+        Item::Link(link, _) => write!(w, "link({}, {})", link.back, link.arg),
+    }
+}
+
+/// Writes `item` in human-readable form.
+#[inline]
+pub fn write_item(w: &mut impl Write, item: &Item<'_>) -> Result {
+    write_item_ret(w, item, None)
+}
+
+/// Writes each item contained in `block` on a separate line.
+///
+/// Writing stops at the first malformed item, which is described on the last line.
+pub fn write_block(w: &mut impl Write, block: &mut [usize]) -> Result {
+    for item in Block::from(block).try_iter() {
+        match item {
+            Ok(item) => write_item(w, &item)?,
+            Err(e) => write!(w, "<{:?} at offset {}>", e.kind, e.offset)?,
+        }
+        w.write_char('\n')?;
+    }
+    Ok(())
+}
+
+/// Writes each item contained in `after` on a separate line, along with the transition of
+/// its first return value from the corresponding item in `before`.
+///
+/// Writing stops at the first malformed item, which is described on the last line.
+pub fn write_diff(w: &mut impl Write, before: &mut [usize], after: &mut [usize]) -> Result {
+    let mut before = Block::from(before).try_iter();
+    for item in Block::from(after).try_iter() {
+        let before = before.next().and_then(|item| item.ok());
+        match item {
+            Ok(item) => write_item_ret(w, &item, before.as_ref().and_then(Item::ret0))?,
+            Err(e) => write!(w, "<{:?} at offset {}>", e.kind, e.offset)?,
+        }
+        w.write_char('\n')?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{enarxcall, notification, BlockWriter, Enarxcall, Notification, Syscall};
+
+    use crate::libc::{
+        in_addr, sockaddr_in, timespec, SYS_close, SYS_connect, SYS_nanosleep, SYS_write, ENOSYS,
+    };
+
+    #[test]
+    fn syscall_name() {
+        assert_eq!(super::syscall_name(SYS_write as _), Some("write"));
+        assert_eq!(super::syscall_name(0xffff), None);
+    }
+
+    #[test]
+    fn block() {
+        let mut block = [0; 128];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        writer
+            .push_syscall(
+                &Syscall {
+                    num: SYS_write as _,
+                    argv: [1, 0, 6, 0, 0, 0],
+                    ret: [6, 0],
+                },
+                b"hello\n",
+            )
+            .unwrap();
+
+        let addr = sockaddr_in {
+            sin_family: AF_INET as _,
+            sin_port: 80u16.to_be(),
+            sin_addr: in_addr {
+                s_addr: u32::from_ne_bytes([127, 0, 0, 1]),
+            },
+            sin_zero: [0; 8],
+        };
+        let addr: [u8; size_of::<sockaddr_in>()] = unsafe { core::mem::transmute(addr) };
+        writer
+            .push_syscall(
+                &Syscall {
+                    num: SYS_connect as _,
+                    argv: [3, 0, addr.len(), 0, 0, 0],
+                    ret: [-ENOSYS as _, 0],
+                },
+                &addr,
+            )
+            .unwrap();
+
+        let ts: [u8; size_of::<timespec>()] = unsafe {
+            core::mem::transmute(timespec {
+                tv_sec: 1,
+                tv_nsec: 2,
+            })
+        };
+        writer
+            .push_syscall(
+                &Syscall {
+                    num: SYS_nanosleep as _,
+                    argv: [0, NULL, 0, 0, 0, 0],
+                    ret: [0, 0],
+                },
+                &ts,
+            )
+            .unwrap();
+        writer
+            .push_syscall(
+                &Syscall {
+                    num: 0xffff,
+                    argv: [1, 2, 3, 4, 5, 6],
+                    ret: [-ENOSYS as _, 0],
+                },
+                &[],
+            )
+            .unwrap();
+        writer
+            .push_enarxcall(
+                &Enarxcall {
+                    num: enarxcall::Number::Cpuid,
+                    argv: [1, 0, 0, 0],
+                    ret: 0,
+                },
+                &[],
+            )
+            .unwrap();
+        writer
+            .push_notification(&Notification {
+                num: notification::Number::Signal,
+                argv: [2, 0],
+            })
+            .unwrap();
+
+        let mut out = String::new();
+        write_block(&mut out, &mut block).unwrap();
+        assert_eq!(
+            out,
+            r#"write(1, "hello\n", 6) = 6
+connect(3, {sa_family=AF_INET, sin_port=80, sin_addr=127.0.0.1}, 16) = -38
+nanosleep({tv_sec=1, tv_nsec=2}, NULL) = 0
+syscall_65535(0x1, 0x2, 0x3, 0x4, 0x5, 0x6) = -38
+enarxcall::Cpuid(0x1, 0x0, 0x0, 0x0) = 0
+notification::Signal(2, 0)
+"#
+        );
+    }
+
+    #[test]
+    fn diff() {
+        let mut before = [0; 32];
+        let call = Syscall {
+            num: SYS_close as _,
+            argv: [3, 0, 0, 0, 0, 0],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut writer = BlockWriter::new(&mut before).unwrap();
+        writer.push_syscall(&call, &[]).unwrap();
+        writer.push_syscall(&call, &[]).unwrap();
+
+        let mut after = before;
+        after[2 + 7] = 0;
+        after[2 * 2 + 2 * 9] = 1;
+
+        let mut out = String::new();
+        write_diff(&mut out, &mut before, &mut after).unwrap();
+        assert_eq!(
+            out,
+            "close(3) = -38 -> 0\nclose(3) = -38\n<MisalignedSize { size: 1 } at offset 22>\n"
+        );
+    }
+}
@@ -5,6 +5,10 @@
 pub mod sev;
 pub mod sgx;
 
+use crate::libc::EINVAL;
+use crate::Error;
+
+use core::convert::TryFrom;
 use core::mem::size_of;
 
 /// `get_attestation` syscall number used by the shim.
@@ -67,6 +71,27 @@ pub enum Number {
     TrimSgxPages = 0x09,
 }
 
+impl TryFrom<usize> for Number {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(num: usize) -> Result<Self, Self::Error> {
+        match num {
+            num if num == Number::MemInfo as _ => Ok(Number::MemInfo),
+            num if num == Number::BalloonMemory as _ => Ok(Number::BalloonMemory),
+            num if num == Number::Cpuid as _ => Ok(Number::Cpuid),
+            num if num == Number::GetSgxQuote as _ => Ok(Number::GetSgxQuote),
+            num if num == Number::GetSgxTargetInfo as _ => Ok(Number::GetSgxTargetInfo),
+            num if num == Number::GetSgxQuoteSize as _ => Ok(Number::GetSgxQuoteSize),
+            num if num == Number::GetSnpVcek as _ => Ok(Number::GetSnpVcek),
+            num if num == Number::RemoveSgxPages as _ => Ok(Number::RemoveSgxPages),
+            num if num == Number::ResetSgxPermissions as _ => Ok(Number::ResetSgxPermissions),
+            num if num == Number::TrimSgxPages as _ => Ok(Number::TrimSgxPages),
+            _ => Err(EINVAL),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,6 +101,19 @@ mod tests {
         assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>())
     }
 
+    #[test]
+    fn number_try_from() {
+        for (v, expected) in [
+            (0x00, Ok(Number::MemInfo)),
+            (0x02, Ok(Number::Cpuid)),
+            (0x09, Ok(Number::TrimSgxPages)),
+            (0x0a, Err(EINVAL)),
+            (0xff, Err(EINVAL)),
+        ] {
+            assert_eq!(v.try_into(), expected, "Invalid mapping for {}", v);
+        }
+    }
+
     #[test]
     fn tech_assignments() {
         assert_ne!(sev::TECH, sgx::TECH);
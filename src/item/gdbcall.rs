@@ -2,6 +2,10 @@
 
 //! GDB call item definitions
 
+use crate::libc::EINVAL;
+use crate::Error;
+
+use core::convert::TryFrom;
 use core::mem::size_of;
 
 /// Payload of an [`Item`](super::Item) of [`Kind::Gdbcall`](super::Kind::Gdbcall).
@@ -60,6 +64,23 @@ pub enum Number {
     Peek = 0x05,
 }
 
+impl TryFrom<usize> for Number {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(num: usize) -> Result<Self, Self::Error> {
+        match num {
+            num if num == Number::Write as _ => Ok(Number::Write),
+            num if num == Number::WriteAll as _ => Ok(Number::WriteAll),
+            num if num == Number::Flush as _ => Ok(Number::Flush),
+            num if num == Number::OnSessionStart as _ => Ok(Number::OnSessionStart),
+            num if num == Number::Read as _ => Ok(Number::Read),
+            num if num == Number::Peek as _ => Ok(Number::Peek),
+            _ => Err(EINVAL),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +89,20 @@ mod tests {
     fn payload_size() {
         assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>())
     }
+
+    #[test]
+    fn number_try_from() {
+        for (v, expected) in [
+            (0x00, Ok(Number::Write)),
+            (0x01, Ok(Number::WriteAll)),
+            (0x02, Ok(Number::Flush)),
+            (0x03, Ok(Number::OnSessionStart)),
+            (0x04, Ok(Number::Read)),
+            (0x05, Ok(Number::Peek)),
+            (0x06, Err(EINVAL)),
+            (0xff, Err(EINVAL)),
+        ] {
+            assert_eq!(v.try_into(), expected, "Invalid mapping for {}", v);
+        }
+    }
 }
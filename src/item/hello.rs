@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protocol version handshake item definitions
+
+use super::enarxcall::Number;
+use super::Kind;
+
+use core::ffi::c_long;
+use core::mem::size_of;
+
+/// Maximum length of a version string contained in a [`Reply`].
+pub const VERSION_MAX: usize = 32;
+
+/// Syscall numbers below this value can be advertised as supported in a [`Reply`].
+pub const SYSCALL_MAX: usize = 512;
+
+/// Payload of an [`Item`](super::Item) of [`Kind::Hello`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, align(8))]
+pub struct Payload {
+    /// Offset of the [`VERSION`](crate::VERSION) of the guest within the data section.
+    pub version: usize,
+
+    /// Length of the [`VERSION`](crate::VERSION) of the guest.
+    pub version_len: usize,
+
+    /// Offset of the [`REQUIRES`](crate::REQUIRES) of the guest within the data section.
+    pub requires: usize,
+
+    /// Length of the [`REQUIRES`](crate::REQUIRES) of the guest.
+    pub requires_len: usize,
+
+    /// Offset of the [`Reply`] of the host within the data section.
+    pub reply: usize,
+
+    /// The return value.
+    pub ret: usize,
+}
+
+pub(crate) const USIZE_COUNT: usize = size_of::<Payload>() / size_of::<usize>();
+
+impl From<&mut [usize; USIZE_COUNT]> for &mut Payload {
+    #[inline]
+    fn from(buf: &mut [usize; USIZE_COUNT]) -> Self {
+        debug_assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>());
+        unsafe { &mut *(buf as *mut _ as *mut _) }
+    }
+}
+
+/// Reply of the host to a [`Kind::Hello`] item.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, align(8))]
+pub struct Reply {
+    /// The [`VERSION`](crate::VERSION) of the host padded with zero bytes.
+    pub version: [u8; VERSION_MAX],
+
+    /// Bitmap of supported [item kinds](Kind).
+    pub kinds: u64,
+
+    /// Bitmap of supported [Enarx call numbers](Number).
+    pub enarxcalls: u64,
+
+    /// Bitmap of supported syscall numbers.
+    pub syscalls: [u64; SYSCALL_MAX / 64],
+}
+
+impl Reply {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            version: [0; VERSION_MAX],
+            kinds: 0,
+            enarxcalls: 0,
+            syscalls: [0; SYSCALL_MAX / 64],
+        }
+    }
+
+    /// Returns the version contained in the reply, if it is valid UTF-8.
+    #[inline]
+    pub fn version(&self) -> Option<&str> {
+        let len = self
+            .version
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(VERSION_MAX);
+        core::str::from_utf8(&self.version[..len]).ok()
+    }
+
+    /// Sets the version contained in the reply.
+    ///
+    /// Returns `false` if `version` is longer than [`VERSION_MAX`].
+    #[inline]
+    pub fn set_version(&mut self, version: &str) -> bool {
+        if version.len() > VERSION_MAX {
+            return false;
+        }
+        self.version = [0; VERSION_MAX];
+        self.version[..version.len()].copy_from_slice(version.as_bytes());
+        true
+    }
+
+    /// Returns `true` if the item `kind` is supported.
+    #[inline]
+    pub fn supports_kind(&self, kind: Kind) -> bool {
+        self.kinds & (1 << kind as usize) != 0
+    }
+
+    /// Marks the item `kind` as supported.
+    #[inline]
+    pub fn add_kind(&mut self, kind: Kind) {
+        self.kinds |= 1 << kind as usize;
+    }
+
+    /// Returns `true` if the Enarx call `num` is supported.
+    #[inline]
+    pub fn supports_enarxcall(&self, num: Number) -> bool {
+        self.enarxcalls & (1 << num as usize) != 0
+    }
+
+    /// Marks the Enarx call `num` as supported.
+    #[inline]
+    pub fn add_enarxcall(&mut self, num: Number) {
+        self.enarxcalls |= 1 << num as usize;
+    }
+
+    /// Returns `true` if the syscall `num` is supported.
+    #[inline]
+    pub fn supports_syscall(&self, num: c_long) -> bool {
+        match usize::try_from(num) {
+            Ok(num) if num < SYSCALL_MAX => self.syscalls[num / 64] & (1 << (num % 64)) != 0,
+            _ => false,
+        }
+    }
+
+    /// Marks the syscall `num` as supported.
+    ///
+    /// Returns `false` if `num` cannot be represented in the reply.
+    #[inline]
+    pub fn add_syscall(&mut self, num: c_long) -> bool {
+        match usize::try_from(num) {
+            Ok(num) if num < SYSCALL_MAX => {
+                self.syscalls[num / 64] |= 1 << (num % 64);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for Reply {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_size() {
+        assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>())
+    }
+
+    #[test]
+    fn reply() {
+        let mut reply = Reply::new();
+        assert_eq!(reply.version(), Some(""));
+        assert!(reply.set_version("0.4.0"));
+        assert_eq!(reply.version(), Some("0.4.0"));
+        assert!(!reply.set_version(core::str::from_utf8(&[b'1'; VERSION_MAX + 1]).unwrap()));
+
+        assert!(!reply.supports_kind(Kind::Syscall));
+        reply.add_kind(Kind::Syscall);
+        assert!(reply.supports_kind(Kind::Syscall));
+        assert!(!reply.supports_kind(Kind::Gdbcall));
+
+        assert!(!reply.supports_enarxcall(Number::Cpuid));
+        reply.add_enarxcall(Number::Cpuid);
+        assert!(reply.supports_enarxcall(Number::Cpuid));
+        assert!(!reply.supports_enarxcall(Number::MemInfo));
+
+        assert!(reply.add_syscall(0));
+        assert!(reply.add_syscall(SYSCALL_MAX as c_long - 1));
+        assert!(!reply.add_syscall(SYSCALL_MAX as _));
+        assert!(!reply.add_syscall(-1));
+        assert!(reply.supports_syscall(0));
+        assert!(reply.supports_syscall(SYSCALL_MAX as c_long - 1));
+        assert!(!reply.supports_syscall(1));
+        assert!(!reply.supports_syscall(-1));
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Linked item definitions
+
+use core::mem::size_of;
+
+/// Maximum distance of an item referenced by a [`Link`](super::Link).
+pub const BACK_MAX: usize = 16;
+
+/// Payload of an [`Item`](super::Item) of [`Kind::Link`](super::Kind::Link).
+///
+/// Instructs the host to replace the argument at index `arg` of the next item, which is not
+/// a link, by the first return value of the item `back` items before it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, align(8))]
+pub struct Payload {
+    /// Distance of the referenced item from the linked item counting only items,
+    /// which have a return value.
+    pub back: usize,
+
+    /// Index of the argument of the linked item to replace.
+    pub arg: usize,
+}
+
+pub(crate) const USIZE_COUNT: usize = size_of::<Payload>() / size_of::<usize>();
+
+impl From<&mut [usize; USIZE_COUNT]> for &mut Payload {
+    #[inline]
+    fn from(buf: &mut [usize; USIZE_COUNT]) -> Self {
+        debug_assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>());
+        unsafe { &mut *(buf as *mut _ as *mut _) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_size() {
+        assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>())
+    }
+}
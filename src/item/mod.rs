@@ -3,14 +3,25 @@
 //! Shared `sallyport` item definitions.
 
 mod block;
+pub mod dump;
 pub mod enarxcall;
 pub mod gdbcall;
+pub mod hello;
+pub mod link;
+pub mod notification;
+pub mod record;
+pub mod ring;
 pub mod syscall;
+mod writer;
 
 pub use block::*;
 pub use enarxcall::Payload as Enarxcall;
 pub use gdbcall::Payload as Gdbcall;
+pub use hello::Payload as Hello;
+pub use link::Payload as Link;
+pub use notification::Payload as Notification;
 pub use syscall::Payload as Syscall;
+pub use writer::*;
 
 use crate::libc::EINVAL;
 use crate::Error;
@@ -32,6 +43,15 @@ pub(super) const LARGEST_PAYLOAD_SIZE: usize = {
     if size_of::<Enarxcall>() > max {
         max = size_of::<Enarxcall>();
     }
+    if size_of::<Hello>() > max {
+        max = size_of::<Hello>();
+    }
+    if size_of::<Notification>() > max {
+        max = size_of::<Notification>();
+    }
+    if size_of::<Link>() > max {
+        max = size_of::<Link>();
+    }
     max
 };
 
@@ -46,6 +66,9 @@ pub enum Kind {
     Syscall = 0x01,
     Gdbcall = 0x02,
     Enarxcall = 0x03,
+    Hello = 0x04,
+    Notification = 0x05,
+    Link = 0x06,
 }
 
 impl TryFrom<usize> for Kind {
@@ -58,6 +81,9 @@ impl TryFrom<usize> for Kind {
             kind if kind == Kind::Syscall as _ => Ok(Kind::Syscall),
             kind if kind == Kind::Gdbcall as _ => Ok(Kind::Gdbcall),
             kind if kind == Kind::Enarxcall as _ => Ok(Kind::Enarxcall),
+            kind if kind == Kind::Hello as _ => Ok(Kind::Hello),
+            kind if kind == Kind::Notification as _ => Ok(Kind::Notification),
+            kind if kind == Kind::Link as _ => Ok(Kind::Link),
             _ => Err(EINVAL),
         }
     }
@@ -77,6 +103,46 @@ pub enum Item<'a> {
     Syscall(&'a mut Syscall, &'a mut [u8]),
     Gdbcall(&'a mut Gdbcall, &'a mut [u8]),
     Enarxcall(&'a mut Enarxcall, &'a mut [u8]),
+    Hello(&'a mut Hello, &'a mut [u8]),
+    Notification(&'a mut Notification, &'a mut [u8]),
+    Link(&'a mut Link, &'a mut [u8]),
+}
+
+impl Item<'_> {
+    /// Returns the first return value of the item, if it has one.
+    #[inline]
+    pub fn ret0(&self) -> Option<usize> {
+        match self {
+            Item::Syscall(call, _) => Some(call.ret[0]),
+            Item::Gdbcall(call, _) => Some(call.ret),
+            Item::Enarxcall(call, _) => Some(call.ret),
+            Item::Hello(call, _) => Some(call.ret),
+            Item::Notification(..) | Item::Link(..) => None,
+        }
+    }
+
+    /// Returns a mutable borrow of the first return value of the item, if it has one.
+    #[inline]
+    pub(crate) fn ret0_mut(&mut self) -> Option<&mut usize> {
+        match self {
+            Item::Syscall(call, _) => Some(&mut call.ret[0]),
+            Item::Gdbcall(call, _) => Some(&mut call.ret),
+            Item::Enarxcall(call, _) => Some(&mut call.ret),
+            Item::Hello(call, _) => Some(&mut call.ret),
+            Item::Notification(..) | Item::Link(..) => None,
+        }
+    }
+
+    /// Returns a mutable borrow of the arguments of the item, if it has any.
+    #[inline]
+    pub(crate) fn argv_mut(&mut self) -> Option<&mut [usize]> {
+        match self {
+            Item::Syscall(call, _) => Some(&mut call.argv),
+            Item::Gdbcall(call, _) => Some(&mut call.argv),
+            Item::Enarxcall(call, _) => Some(&mut call.argv),
+            Item::Hello(..) | Item::Notification(..) | Item::Link(..) => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -90,7 +156,10 @@ mod tests {
             (0x01, Ok(Kind::Syscall)),
             (0x02, Ok(Kind::Gdbcall)),
             (0x03, Ok(Kind::Enarxcall)),
-            (0x04, Err(EINVAL)),
+            (0x04, Ok(Kind::Hello)),
+            (0x05, Ok(Kind::Notification)),
+            (0x06, Ok(Kind::Link)),
+            (0x07, Err(EINVAL)),
             (0xff, Err(EINVAL)),
         ] {
             assert_eq!(v.try_into(), expected, "Invalid mapping for {}", v);
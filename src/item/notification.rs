@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Host-to-guest notification item definitions
+
+use crate::libc::EINVAL;
+use crate::Error;
+
+use core::convert::TryFrom;
+use core::mem::size_of;
+
+/// Payload of an [`Item`](super::Item) of [`Kind::Notification`](super::Kind::Notification).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, align(8))]
+pub struct Payload {
+    pub num: Number,
+    pub argv: [usize; 2],
+}
+
+pub(crate) const USIZE_COUNT: usize = size_of::<Payload>() / size_of::<usize>();
+
+impl From<&mut [usize; USIZE_COUNT]> for &mut Payload {
+    #[inline]
+    fn from(buf: &mut [usize; USIZE_COUNT]) -> Self {
+        debug_assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>());
+        unsafe { &mut *(buf as *mut _ as *mut _) }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(usize)]
+/// Number of an [`Item`](super::Item) of [`Kind::Notification`](super::Kind::Notification).
+pub enum Number {
+    /// A signal is pending, the first argument contains the signal number.
+    Signal = 0x00,
+
+    /// A file descriptor became readable, the first argument contains the file descriptor.
+    Readable = 0x01,
+
+    /// Memory should be returned to the host, the first argument contains the size in bytes.
+    ReclaimMemory = 0x02,
+}
+
+impl TryFrom<usize> for Number {
+    type Error = Error;
+
+    #[inline]
+    fn try_from(num: usize) -> Result<Self, Self::Error> {
+        match num {
+            num if num == Number::Signal as _ => Ok(Number::Signal),
+            num if num == Number::Readable as _ => Ok(Number::Readable),
+            num if num == Number::ReclaimMemory as _ => Ok(Number::ReclaimMemory),
+            _ => Err(EINVAL),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_size() {
+        assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>())
+    }
+
+    #[test]
+    fn number_try_from() {
+        for (v, expected) in [
+            (0x00, Ok(Number::Signal)),
+            (0x01, Ok(Number::Readable)),
+            (0x02, Ok(Number::ReclaimMemory)),
+            (0x03, Err(EINVAL)),
+        ] {
+            assert_eq!(v.try_into(), expected, "Invalid mapping for {}", v);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording format of `sallyport` traffic.
+//!
+//! A recording starts with the [`MAGIC`] bytes followed by the [`FORMAT_VERSION`] and contains
+//! a sequence of exchanges. Each exchange consists of the contents of a block before execution
+//! by the host followed by the contents of the same block after execution.
+//!
+//! The contents of a block are stored as a count of `usize` elements followed by the elements.
+//! Only the elements up to and including the [`End`](super::Kind::End) item are stored.
+//!
+//! All numbers are stored as little-endian `u64`.
+
+use super::Block;
+use crate::libc::{EINVAL, EPROTO};
+use crate::Result;
+
+use core::mem::size_of;
+use core::slice::ChunksExact;
+
+/// Bytes a recording starts with.
+pub const MAGIC: [u8; 8] = *b"SALLYREC";
+
+/// Version of the recording format.
+pub const FORMAT_VERSION: u64 = 1;
+
+const HEADER_USIZE_COUNT: usize = size_of::<super::Header>() / size_of::<usize>();
+
+/// Count of elements encoded at once when writing a block.
+const CHUNK_COUNT: usize = 64;
+
+/// Returns the count of `usize` elements in `block` up to and including the
+/// [`End`](super::Kind::End) item or the first malformed item.
+fn used_len(block: &mut [usize]) -> usize {
+    let len = block.len();
+    let mut items = Block::from(block).try_iter();
+    for _ in &mut items {}
+    items.offset().saturating_add(HEADER_USIZE_COUNT).min(len)
+}
+
+/// Writes the recording header into `sink`.
+pub(crate) fn write_header(sink: &mut impl FnMut(&[u8]) -> Result<()>) -> Result<()> {
+    sink(&MAGIC)?;
+    sink(&FORMAT_VERSION.to_le_bytes())
+}
+
+/// Writes the used contents of `block` into `sink`.
+pub(crate) fn write_block(
+    sink: &mut impl FnMut(&[u8]) -> Result<()>,
+    block: &mut [usize],
+) -> Result<()> {
+    let len = used_len(block);
+    sink(&(len as u64).to_le_bytes())?;
+
+    let mut buf = [0u8; CHUNK_COUNT * size_of::<u64>()];
+    for chunk in block[..len].chunks(CHUNK_COUNT) {
+        for (bytes, word) in buf.chunks_exact_mut(size_of::<u64>()).zip(chunk) {
+            bytes.copy_from_slice(&(*word as u64).to_le_bytes());
+        }
+        sink(&buf[..chunk.len() * size_of::<u64>()])?;
+    }
+    Ok(())
+}
+
+/// Contents of a block stored in a recording.
+#[derive(Clone, Debug)]
+pub struct Words<'a>(ChunksExact<'a, u8>);
+
+impl Iterator for Words<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as _)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Words<'_> {}
+
+/// Contents of a block before and after execution by the host.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exchange<'a> {
+    before: &'a [u8],
+    after: &'a [u8],
+}
+
+impl<'a> Exchange<'a> {
+    /// Returns the contents of the block passed by the guest.
+    #[inline]
+    pub fn before(&self) -> Words<'a> {
+        Words(self.before.chunks_exact(size_of::<u64>()))
+    }
+
+    /// Returns the contents of the block returned by the host.
+    #[inline]
+    pub fn after(&self) -> Words<'a> {
+        Words(self.after.chunks_exact(size_of::<u64>()))
+    }
+}
+
+/// Reader of the exchanges contained in a recording.
+///
+/// The iterator yields [`EINVAL`] once if the recording is truncated.
+#[derive(Clone, Debug)]
+pub struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Creates a new [`Reader`] over `recording`.
+    ///
+    /// Returns [`EINVAL`] if `recording` does not start with [`MAGIC`] and [`EPROTO`]
+    /// if it uses a format version other than [`FORMAT_VERSION`].
+    pub fn new(recording: &'a [u8]) -> Result<Self> {
+        let mut reader = Self(recording.strip_prefix(&MAGIC).ok_or(EINVAL)?);
+        if reader.read_u64()? != FORMAT_VERSION {
+            return Err(EPROTO);
+        }
+        Ok(reader)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.0.len() {
+            return Err(EINVAL);
+        }
+        let (bytes, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(bytes)
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        let bytes = self.read_bytes(size_of::<u64>())?;
+        Ok(u64::from_le_bytes(bytes.try_into().or(Err(EINVAL))?))
+    }
+
+    fn read_block(&mut self) -> Result<&'a [u8]> {
+        let len = usize::try_from(self.read_u64()?)
+            .ok()
+            .and_then(|len| len.checked_mul(size_of::<u64>()))
+            .ok_or(EINVAL)?;
+        self.read_bytes(len)
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Exchange<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let exchange = self.read_block().and_then(|before| {
+            let after = self.read_block()?;
+            Ok(Exchange { before, after })
+        });
+        if exchange.is_err() {
+            self.0 = &[];
+        }
+        Some(exchange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{BlockWriter, Syscall};
+
+    use libc::{SYS_getpid, ENOSYS};
+
+    #[test]
+    fn round_trip() {
+        let mut recording = Vec::new();
+        let mut sink = |bytes: &[u8]| {
+            recording.extend_from_slice(bytes);
+            Ok(())
+        };
+        write_header(&mut sink).unwrap();
+
+        let mut block = [0xff; 256];
+        let call = Syscall {
+            num: SYS_getpid as _,
+            argv: [0; 6],
+            ret: [-ENOSYS as _, 0],
+        };
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        for _ in 0..CHUNK_COUNT / 8 {
+            writer.push_syscall(&call, &[]).unwrap();
+        }
+        let len = writer.len() + HEADER_USIZE_COUNT;
+        let before = block;
+        write_block(&mut sink, &mut block).unwrap();
+        block[HEADER_USIZE_COUNT + 7] = 42;
+        write_block(&mut sink, &mut block).unwrap();
+        write_block(&mut sink, &mut [0xff; 1]).unwrap();
+        write_block(&mut sink, &mut []).unwrap();
+
+        let mut reader = Reader::new(&recording).unwrap();
+        let exchange = reader.next().unwrap().unwrap();
+        assert_eq!(exchange.before().len(), len);
+        assert!(exchange.before().eq(before[..len].iter().copied()));
+        assert!(exchange.after().eq(block[..len].iter().copied()));
+
+        let exchange = reader.next().unwrap().unwrap();
+        assert!(exchange.before().eq([0xff]));
+        assert_eq!(exchange.after().len(), 0);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Reader::new(b"SALLY").unwrap_err(), EINVAL);
+        assert_eq!(Reader::new(&MAGIC).unwrap_err(), EINVAL);
+
+        let mut recording = MAGIC.to_vec();
+        recording.extend_from_slice(&2u64.to_le_bytes());
+        assert_eq!(Reader::new(&recording).unwrap_err(), EPROTO);
+
+        let mut recording = MAGIC.to_vec();
+        recording.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        recording.extend_from_slice(&1u64.to_le_bytes());
+        recording.extend_from_slice(&0u64.to_le_bytes());
+        let mut reader = Reader::new(&recording).unwrap();
+        assert_eq!(reader.next(), Some(Err(EINVAL)));
+        assert_eq!(reader.next(), None);
+    }
+}
@@ -0,0 +1,439 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ring of `sallyport` blocks shared between the guest and the host.
+//!
+//! The ring consists of a [`Header`] and a number of blocks of equal size, as defined by the
+//! [`NUM_BLOCKS`](crate::elf::note::NUM_BLOCKS) and [`BLOCK_SIZE`](crate::elf::note::BLOCK_SIZE)
+//! notes.
+//!
+//! The guest [submits](Producer::submit) blocks at the producer index using a [`Producer`],
+//! the host [consumes](Consumer::consume) them at the consumer index using a [`Consumer`] and
+//! the guest [collects](Producer::collect) the results of consumed blocks afterwards. Blocks are
+//! submitted and consumed in order, hence the guest only needs to pass control to the host if
+//! the ring is [full](Producer::is_full) or the host is [idle](Producer::is_idle).
+//!
+//! Both views only synchronize through the atomic indices of the [`Header`] and only access the
+//! blocks they own at the time, so they can be used by two parties sharing the memory. The
+//! [`Producer`] keeps its own count of submitted blocks and never trusts the producer index of
+//! the [`Header`], which the host can write.
+
+use crate::libc::{EAGAIN, EINVAL};
+use crate::Result;
+
+use core::marker::PhantomData;
+use core::mem::{size_of, size_of_val};
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Header of a ring placed in shared memory.
+#[derive(Debug, Default)]
+#[repr(C, align(64))]
+pub struct Header {
+    /// Count of blocks submitted by the guest.
+    producer: AtomicUsize,
+
+    /// Count of blocks consumed by the host.
+    consumer: AtomicUsize,
+}
+
+impl Header {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            producer: AtomicUsize::new(0),
+            consumer: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Returns the count of `usize` elements required for `num_blocks` blocks of `block_size` bytes.
+#[inline]
+pub fn blocks_len(block_size: u64, num_blocks: u64) -> Option<usize> {
+    let len = block_size.checked_mul(num_blocks)? / size_of::<usize>() as u64;
+    len.try_into().ok()
+}
+
+/// Blocks of a ring in shared memory.
+#[derive(Debug)]
+struct Blocks<'a> {
+    ptr: NonNull<usize>,
+    capacity: usize,
+    block_len: usize,
+    blocks: PhantomData<&'a mut [usize]>,
+}
+
+impl<'a> Blocks<'a> {
+    /// Returns [`EINVAL`] if `block_size` is not a non-zero multiple of `usize` size or
+    /// `blocks` do not consist of a non-zero number of blocks.
+    fn new(blocks: NonNull<[usize]>, block_size: usize) -> Result<Self> {
+        let block_len = block_size / size_of::<usize>();
+        if block_len * size_of::<usize>() != block_size
+            || blocks.is_empty()
+            || blocks.len().checked_rem(block_len) != Some(0)
+        {
+            return Err(EINVAL);
+        }
+        Ok(Self {
+            ptr: blocks.cast(),
+            capacity: blocks.len() / block_len,
+            block_len,
+            blocks: PhantomData,
+        })
+    }
+
+    /// Returns the block at ring `index`.
+    fn get(&self, index: usize) -> NonNull<[usize]> {
+        let start = index % self.capacity * self.block_len;
+        let ptr = unsafe { NonNull::new_unchecked(self.ptr.as_ptr().add(start)) };
+        NonNull::slice_from_raw_parts(ptr, self.block_len)
+    }
+}
+
+/// Owner of a ring of blocks, which can be [split](Ring::split) into a [`Producer`] and a
+/// [`Consumer`] sharing it within a single address space.
+#[derive(Debug)]
+pub struct Ring<'a> {
+    header: &'a Header,
+    blocks: &'a mut [usize],
+    block_size: usize,
+}
+
+impl<'a> Ring<'a> {
+    /// Creates a new [`Ring`] over `header` and `blocks` of `block_size` bytes each.
+    ///
+    /// Returns [`EINVAL`] if `block_size` is not a non-zero multiple of `usize` size or
+    /// `blocks` do not consist of a non-zero number of blocks.
+    pub fn new(header: &'a Header, blocks: &'a mut [usize], block_size: usize) -> Result<Self> {
+        Blocks::new(NonNull::from(&mut *blocks), block_size)?;
+        Ok(Self {
+            header,
+            blocks,
+            block_size,
+        })
+    }
+
+    /// Returns the number of blocks in the ring.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        size_of_val(self.blocks) / self.block_size
+    }
+
+    /// Splits the ring into its [`Producer`] and [`Consumer`] views.
+    pub fn split(&mut self) -> (Producer<'_>, Consumer<'_>) {
+        let blocks = NonNull::from(&mut *self.blocks);
+        // The views only access blocks owned by them according to the header indices.
+        unsafe {
+            (
+                Producer::new(self.header, blocks, self.block_size).unwrap(),
+                Consumer::new(self.header, blocks, self.block_size).unwrap(),
+            )
+        }
+    }
+}
+
+/// View of a ring used by the guest to submit blocks and collect their results.
+#[derive(Debug)]
+pub struct Producer<'a> {
+    header: &'a Header,
+    blocks: Blocks<'a>,
+
+    /// Count of blocks submitted by the guest.
+    submitted: usize,
+
+    /// Count of blocks collected by the guest.
+    collected: usize,
+}
+
+impl<'a> Producer<'a> {
+    /// Creates a new [`Producer`] over `header` and `blocks` of `block_size` bytes each.
+    ///
+    /// Returns [`EINVAL`] if `block_size` is not a non-zero multiple of `usize` size,
+    /// `blocks` do not consist of a non-zero number of blocks or the indices of `header`
+    /// are inconsistent.
+    ///
+    /// # Safety
+    ///
+    /// `blocks` must be valid for reads and writes for `'a` and only accessed by a single
+    /// [`Consumer`] sharing `header` apart from the returned [`Producer`].
+    pub unsafe fn new(
+        header: &'a Header,
+        blocks: NonNull<[usize]>,
+        block_size: usize,
+    ) -> Result<Self> {
+        let blocks = Blocks::new(blocks, block_size)?;
+        let submitted = header.producer.load(Ordering::Relaxed);
+        let collected = header.consumer.load(Ordering::Acquire);
+        if submitted.wrapping_sub(collected) > blocks.capacity {
+            return Err(EINVAL);
+        }
+        Ok(Self {
+            header,
+            blocks,
+            submitted,
+            collected,
+        })
+    }
+
+    /// Returns the number of blocks in the ring.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.blocks.capacity
+    }
+
+    /// Returns `true` if the guest cannot submit a block before collecting one.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.submitted.wrapping_sub(self.collected) >= self.capacity()
+    }
+
+    /// Returns `true` if the host consumed all submitted blocks.
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        self.header.consumer.load(Ordering::Acquire) == self.submitted
+    }
+
+    /// Returns `true` if the next submitted block was consumed by the host and can be collected.
+    ///
+    /// Returns [`EINVAL`] if the host consumed more blocks than submitted.
+    pub fn is_collectable(&self) -> Result<bool> {
+        let consumer = self.header.consumer.load(Ordering::Acquire);
+        if consumer.wrapping_sub(self.collected) > self.submitted.wrapping_sub(self.collected) {
+            return Err(EINVAL);
+        }
+        Ok(consumer != self.collected)
+    }
+
+    /// Returns the ring index of the next block to be collected.
+    #[inline]
+    pub(crate) fn collected(&self) -> usize {
+        self.collected
+    }
+
+    /// Passes the next free block and its ring index to `fill` and submits it to the host.
+    ///
+    /// Returns [`EAGAIN`] if the ring is [full](Self::is_full). The block is not submitted
+    /// if `fill` returns an error.
+    pub(crate) fn submit_raw<T>(
+        &mut self,
+        fill: impl FnOnce(NonNull<[usize]>, usize) -> Result<T>,
+    ) -> Result<T> {
+        if self.is_full() {
+            return Err(EAGAIN);
+        }
+        let ret = fill(self.blocks.get(self.submitted), self.submitted)?;
+        self.submitted = self.submitted.wrapping_add(1);
+        self.header
+            .producer
+            .store(self.submitted, Ordering::Release);
+        Ok(ret)
+    }
+
+    /// Fills the next free block using `fill` and submits it to the host.
+    ///
+    /// Returns [`EAGAIN`] if the ring is [full](Self::is_full). The block is not submitted
+    /// if `fill` returns an error.
+    pub fn submit<T>(&mut self, fill: impl FnOnce(&mut [usize]) -> Result<T>) -> Result<T> {
+        // The free block is owned by the guest until submitted.
+        self.submit_raw(|mut block, _| fill(unsafe { block.as_mut() }))
+    }
+
+    /// Passes the next block consumed by the host to `collect` and makes it available
+    /// for submission again.
+    ///
+    /// Returns `None` if no consumed block is pending collection and [`EINVAL`] if the host
+    /// consumed more blocks than submitted.
+    pub fn collect<T>(&mut self, collect: impl FnOnce(&mut [usize]) -> T) -> Result<Option<T>> {
+        if !self.is_collectable()? {
+            return Ok(None);
+        }
+        // The consumed block is owned by the guest until collected.
+        let ret = collect(unsafe { self.blocks.get(self.collected).as_mut() });
+        self.collected = self.collected.wrapping_add(1);
+        Ok(Some(ret))
+    }
+}
+
+/// View of a ring used by the host to consume the blocks submitted by the guest.
+#[derive(Debug)]
+pub struct Consumer<'a> {
+    header: &'a Header,
+    blocks: Blocks<'a>,
+}
+
+impl<'a> Consumer<'a> {
+    /// Creates a new [`Consumer`] over `header` and `blocks` of `block_size` bytes each.
+    ///
+    /// Returns [`EINVAL`] if `block_size` is not a non-zero multiple of `usize` size or
+    /// `blocks` do not consist of a non-zero number of blocks.
+    ///
+    /// # Safety
+    ///
+    /// `blocks` must be valid for reads and writes for `'a` and only accessed by a single
+    /// [`Producer`] sharing `header` apart from the returned [`Consumer`].
+    pub unsafe fn new(
+        header: &'a Header,
+        blocks: NonNull<[usize]>,
+        block_size: usize,
+    ) -> Result<Self> {
+        Ok(Self {
+            header,
+            blocks: Blocks::new(blocks, block_size)?,
+        })
+    }
+
+    /// Returns the number of blocks in the ring.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.blocks.capacity
+    }
+
+    /// Passes the next block submitted by the guest to `consume` and marks it consumed.
+    ///
+    /// Returns `None` if no submitted block is pending and [`EINVAL`] if the guest submitted
+    /// more blocks than fit in the ring.
+    pub fn consume<T>(&mut self, consume: impl FnOnce(&mut [usize]) -> T) -> Result<Option<T>> {
+        let producer = self.header.producer.load(Ordering::Acquire);
+        let consumer = self.header.consumer.load(Ordering::Relaxed);
+        let pending = producer.wrapping_sub(consumer);
+        if pending > self.capacity() {
+            return Err(EINVAL);
+        }
+        if pending == 0 {
+            return Ok(None);
+        }
+        // The submitted block is owned by the host until consumed.
+        let ret = consume(unsafe { self.blocks.get(consumer).as_mut() });
+        self.header
+            .consumer
+            .store(consumer.wrapping_add(1), Ordering::Release);
+        Ok(Some(ret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new() {
+        let header = Header::new();
+        assert_eq!(Ring::new(&header, &mut [0; 8], 0).unwrap_err(), EINVAL);
+        assert_eq!(Ring::new(&header, &mut [0; 8], 12).unwrap_err(), EINVAL);
+        assert_eq!(Ring::new(&header, &mut [0; 8], 24).unwrap_err(), EINVAL);
+        assert_eq!(Ring::new(&header, &mut [], 16).unwrap_err(), EINVAL);
+        assert_eq!(Ring::new(&header, &mut [0; 8], 16).unwrap().capacity(), 4);
+        let mut blocks = [0; 8];
+        let mut ring = Ring::new(&header, &mut blocks, 16).unwrap();
+        let (producer, consumer) = ring.split();
+        assert_eq!((producer.capacity(), consumer.capacity()), (4, 4));
+        assert_eq!(blocks_len(16, 4), Some(8));
+        assert_eq!(blocks_len(u64::MAX, 2), None);
+    }
+
+    #[test]
+    fn ring() {
+        let header = Header::new();
+        let mut blocks = [0; 6];
+        let mut ring = Ring::new(&header, &mut blocks, 2 * size_of::<usize>()).unwrap();
+        let (mut producer, mut consumer) = ring.split();
+        assert!(producer.is_idle());
+        assert_eq!(consumer.consume(|_| ()), Ok(None));
+        assert_eq!(producer.collect(|_| ()), Ok(None));
+
+        for i in 0..3 {
+            assert!(!producer.is_full());
+            assert_eq!(
+                producer.submit(|block| {
+                    block.fill(i);
+                    Ok(())
+                }),
+                Ok(())
+            );
+        }
+        assert!(producer.is_full());
+        assert!(!producer.is_idle());
+        assert_eq!(producer.submit(|_| Ok(())), Err(EAGAIN));
+
+        assert_eq!(consumer.consume(|block| block[0]), Ok(Some(0)));
+        assert_eq!(consumer.consume(|block| block[1]), Ok(Some(1)));
+        assert!(producer.is_full());
+
+        assert_eq!(producer.collect(|block| block[0]), Ok(Some(0)));
+        assert!(!producer.is_full());
+        assert_eq!(
+            producer.submit(|block| {
+                block.fill(3);
+                Ok(())
+            }),
+            Ok(())
+        );
+        assert_eq!(producer.submit(|_| Ok(())), Err(EAGAIN));
+        assert_eq!(producer.submit::<()>(|_| Err(EINVAL)), Err(EAGAIN));
+
+        assert_eq!(consumer.consume(|block| block[0]), Ok(Some(2)));
+        assert_eq!(consumer.consume(|block| block[0]), Ok(Some(3)));
+        assert_eq!(consumer.consume(|_| ()), Ok(None));
+        assert!(producer.is_idle());
+
+        for i in 1..4 {
+            assert_eq!(producer.collect(|block| block[0]), Ok(Some(i)));
+        }
+        assert_eq!(producer.collect(|_| ()), Ok(None));
+        assert_eq!(producer.submit::<()>(|_| Err(EINVAL)), Err(EINVAL));
+        assert_eq!(consumer.consume(|_| ()), Ok(None));
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let header = Header::new();
+        let mut blocks = [0; 4];
+        let mut ring = Ring::new(&header, &mut blocks, size_of::<usize>()).unwrap();
+        let (mut producer, mut consumer) = ring.split();
+
+        header.producer.store(5, Ordering::Relaxed);
+        assert_eq!(consumer.consume(|_| ()), Err(EINVAL));
+
+        header.producer.store(1, Ordering::Relaxed);
+        header.consumer.store(2, Ordering::Relaxed);
+        assert_eq!(producer.collect(|_| ()), Err(EINVAL));
+        assert_eq!(producer.is_collectable(), Err(EINVAL));
+
+        header.producer.store(2, Ordering::Relaxed);
+        header.consumer.store(0, Ordering::Relaxed);
+        assert_eq!(
+            unsafe { Producer::new(&header, NonNull::from(&mut [0; 1][..]), size_of::<usize>()) }
+                .unwrap_err(),
+            EINVAL
+        );
+    }
+
+    #[test]
+    fn rewound_producer() {
+        let header = Header::new();
+        let mut blocks = [0; 2];
+        let mut ring = Ring::new(&header, &mut blocks, size_of::<usize>()).unwrap();
+        let (mut producer, mut consumer) = ring.split();
+
+        assert_eq!(producer.submit_raw(|_, index| Ok(index)), Ok(0));
+        assert_eq!(producer.submit_raw(|_, index| Ok(index)), Ok(1));
+        assert!(producer.is_full());
+
+        // The host rewinds the producer index while both blocks are outstanding.
+        header.producer.store(0, Ordering::Relaxed);
+        assert!(producer.is_full());
+        assert!(!producer.is_idle());
+        assert_eq!(producer.submit_raw(|_, index| Ok(index)), Err(EAGAIN));
+        assert_eq!(producer.is_collectable(), Ok(false));
+
+        header.consumer.store(1, Ordering::Relaxed);
+        assert_eq!(producer.collect(|_| ()), Ok(Some(())));
+        assert_eq!(producer.submit_raw(|_, index| Ok(index)), Ok(2));
+        assert_eq!(header.producer.load(Ordering::Relaxed), 3);
+
+        header.consumer.store(4, Ordering::Relaxed);
+        assert_eq!(producer.is_collectable(), Err(EINVAL));
+        header.consumer.store(3, Ordering::Relaxed);
+        assert!(producer.is_idle());
+        assert_eq!(consumer.consume(|_| ()), Ok(None));
+    }
+}
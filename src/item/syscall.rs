@@ -2,8 +2,12 @@
 
 //! System call item definitions
 
+use crate::libc::{cmsghdr, CMSG_ALIGN, CMSG_LEN, EINVAL};
+use crate::Result;
+
 use core::ffi::c_int;
 use core::mem::size_of;
+use core::ops::Range;
 
 /// Payload of an [`Item`](super::Item) of [`Kind::Syscall`](super::Kind::Syscall).
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -39,6 +43,55 @@ pub const ARCH_GET_GS: c_int = 0x1004;
 #[allow(non_camel_case_types)] // follow `libc` conventions
 pub type sigaction = [u64; 4];
 
+/// Iterator over the control messages in the control buffer of a
+/// [`msghdr`](crate::libc::msghdr) laid out like Linux does.
+///
+/// Yields the header of each message along with the range of its data within the buffer or
+/// [`EINVAL`] once, if the buffer is malformed.
+#[derive(Clone, Debug)]
+pub(crate) struct Cmsgs<T> {
+    control: T,
+    offset: usize,
+}
+
+impl<T: AsRef<[u8]>> Cmsgs<T> {
+    #[inline]
+    pub(crate) fn new(control: T) -> Self {
+        Self { control, offset: 0 }
+    }
+}
+
+impl<T: AsMut<[u8]>> Cmsgs<T> {
+    /// Returns the control buffer, for example, to modify the data of the last message yielded.
+    #[inline]
+    pub(crate) fn control_mut(&mut self) -> &mut [u8] {
+        self.control.as_mut()
+    }
+}
+
+impl<T: AsRef<[u8]>> Iterator for Cmsgs<T> {
+    type Item = Result<(cmsghdr, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let control = self.control.as_ref();
+        let tail = control.get(self.offset..).filter(|tail| !tail.is_empty())?;
+        let cmsg = match tail
+            .get(..size_of::<cmsghdr>())
+            .map(|header| unsafe { header.as_ptr().cast::<cmsghdr>().read_unaligned() })
+        {
+            Some(cmsg) if (CMSG_LEN(0)..=tail.len()).contains(&cmsg.cmsg_len) => cmsg,
+            _ => {
+                self.offset = control.len();
+                return Some(Err(EINVAL));
+            }
+        };
+        let data = self.offset + CMSG_LEN(0)..self.offset + cmsg.cmsg_len;
+        // The padding of the last message is truncated to the end of the buffer.
+        self.offset += CMSG_ALIGN(cmsg.cmsg_len).min(tail.len());
+        Some(Ok((cmsg, data)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,4 +100,42 @@ mod tests {
     fn payload_size() {
         assert_eq!(size_of::<Payload>(), USIZE_COUNT * size_of::<usize>())
     }
+
+    #[test]
+    fn cmsgs() {
+        use crate::libc::{SCM_CREDENTIALS, SCM_RIGHTS, SOL_SOCKET};
+
+        fn header(control: &mut [u8], offset: usize, len: usize, kind: c_int) {
+            let cmsg = cmsghdr {
+                cmsg_len: len,
+                cmsg_level: SOL_SOCKET,
+                cmsg_type: kind,
+            };
+            let cmsg: [u8; size_of::<cmsghdr>()] = unsafe { core::mem::transmute(cmsg) };
+            control[offset..][..cmsg.len()].copy_from_slice(&cmsg);
+        }
+
+        let mut control = [0u8; 44];
+        header(&mut control, 0, CMSG_LEN(8), SCM_RIGHTS);
+        header(&mut control, 24, CMSG_LEN(4), SCM_CREDENTIALS);
+        let cmsgs: [_; 2] = Cmsgs::new(&control[..])
+            .map(|cmsg| cmsg.map(|(cmsg, data)| (cmsg.cmsg_type, data)))
+            .collect::<Result<std::vec::Vec<_>>>()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(cmsgs, [(SCM_RIGHTS, 16..24), (SCM_CREDENTIALS, 40..44)]);
+
+        assert_eq!(Cmsgs::new(&control[..0]).next(), None);
+        let mut cmsgs = Cmsgs::new(&control[..30]);
+        assert!(matches!(cmsgs.next(), Some(Ok(_))));
+        assert_eq!(cmsgs.next().map(|cmsg| cmsg.map(|_| ())), Some(Err(EINVAL)));
+        assert!(cmsgs.next().is_none());
+
+        header(&mut control, 0, CMSG_LEN(0) - 1, SCM_RIGHTS);
+        assert_eq!(
+            Cmsgs::new(control).next().map(|cmsg| cmsg.map(|_| ())),
+            Some(Err(EINVAL))
+        );
+    }
 }
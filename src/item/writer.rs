@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    enarxcall, gdbcall, hello, link, notification, syscall, Block, Enarxcall, Gdbcall, Header,
+    Hello, Kind, Link, Notification, Syscall,
+};
+use crate::libc::{ENOMEM, EOVERFLOW};
+use crate::Result;
+
+use core::mem::size_of;
+
+const HEADER_USIZE_COUNT: usize = size_of::<Header>() / size_of::<usize>();
+
+/// Writer of items into an untrusted `sallyport` [`Block`].
+///
+/// The written items are always followed by an [`End`](Kind::End) item, so that the block
+/// can be passed to the other side at any point in time.
+#[derive(Debug, PartialEq)]
+pub struct BlockWriter<'a> {
+    block: &'a mut [usize],
+    len: usize,
+}
+
+impl<'a> BlockWriter<'a> {
+    /// Creates a new [`BlockWriter`] over `block` and terminates it.
+    ///
+    /// Returns [`ENOMEM`] if `block` is too small to contain an [`End`](Kind::End) item.
+    pub fn new(block: &'a mut [usize]) -> Result<Self> {
+        let mut writer = Self { block, len: 0 };
+        writer.terminate(0)?;
+        Ok(writer)
+    }
+
+    /// Creates a new [`BlockWriter`] over `block`, which appends items after the items
+    /// already contained in `block`.
+    ///
+    /// Returns [`EINVAL`](crate::libc::EINVAL) if `block` is malformed and [`ENOMEM`] if `block` is too small
+    /// to contain an [`End`](Kind::End) item after the existing items.
+    pub fn append(block: &'a mut [usize]) -> Result<Self> {
+        let mut items = Block::from(&mut *block).try_iter();
+        for item in &mut items {
+            item?;
+        }
+        let len = items.offset();
+
+        let mut writer = Self { block, len };
+        writer.terminate(len)?;
+        Ok(writer)
+    }
+
+    /// Returns the count of `usize` elements written so far, excluding the [`End`](Kind::End) item.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no items were written.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a [`Syscall`] item with `data` as the data section.
+    #[inline]
+    pub fn push_syscall(&mut self, call: &Syscall, data: &[u8]) -> Result<()> {
+        self.push::<{ syscall::USIZE_COUNT }, _>(Kind::Syscall, call, data)
+    }
+
+    /// Appends a [`Gdbcall`] item with `data` as the data section.
+    #[inline]
+    pub fn push_gdbcall(&mut self, call: &Gdbcall, data: &[u8]) -> Result<()> {
+        self.push::<{ gdbcall::USIZE_COUNT }, _>(Kind::Gdbcall, call, data)
+    }
+
+    /// Appends an [`Enarxcall`] item with `data` as the data section.
+    #[inline]
+    pub fn push_enarxcall(&mut self, call: &Enarxcall, data: &[u8]) -> Result<()> {
+        self.push::<{ enarxcall::USIZE_COUNT }, _>(Kind::Enarxcall, call, data)
+    }
+
+    /// Appends a [`Hello`] item with `data` as the data section.
+    #[inline]
+    pub fn push_hello(&mut self, call: &Hello, data: &[u8]) -> Result<()> {
+        self.push::<{ hello::USIZE_COUNT }, _>(Kind::Hello, call, data)
+    }
+
+    /// Appends a [`Notification`] item.
+    #[inline]
+    pub fn push_notification(&mut self, notification: &Notification) -> Result<()> {
+        self.push::<{ notification::USIZE_COUNT }, _>(Kind::Notification, notification, &[])
+    }
+
+    /// Appends a [`Link`] item.
+    #[inline]
+    pub fn push_link(&mut self, link: &Link) -> Result<()> {
+        self.push::<{ link::USIZE_COUNT }, _>(Kind::Link, link, &[])
+    }
+
+    /// Writes an [`End`](Kind::End) item at `offset`.
+    fn terminate(&mut self, offset: usize) -> Result<()> {
+        let end = self
+            .block
+            .get_mut(offset..)
+            .and_then(|tail| tail.get_mut(..HEADER_USIZE_COUNT))
+            .ok_or(ENOMEM)?;
+        end.copy_from_slice(&[0, Kind::End as _]);
+        Ok(())
+    }
+
+    /// Appends an item of `kind` consisting of `payload` followed by `data`,
+    /// which is padded to a multiple of `usize` size.
+    ///
+    /// The block is left unmodified on error.
+    fn push<const USIZE_COUNT: usize, T: Copy>(
+        &mut self,
+        kind: Kind,
+        payload: &T,
+        data: &[u8],
+    ) -> Result<()>
+    where
+        for<'b> &'b mut T: From<&'b mut [usize; USIZE_COUNT]>,
+    {
+        let data_count = data
+            .len()
+            .checked_add(size_of::<usize>() - 1)
+            .ok_or(EOVERFLOW)?
+            / size_of::<usize>();
+        let item_count = USIZE_COUNT.checked_add(data_count).ok_or(EOVERFLOW)?;
+        let size = item_count
+            .checked_mul(size_of::<usize>())
+            .ok_or(EOVERFLOW)?;
+
+        let offset = self.len;
+        let end = offset
+            .checked_add(HEADER_USIZE_COUNT)
+            .and_then(|end| end.checked_add(item_count))
+            .ok_or(EOVERFLOW)?;
+        if end.checked_add(HEADER_USIZE_COUNT).ok_or(EOVERFLOW)? > self.block.len() {
+            return Err(ENOMEM);
+        }
+
+        let (header, item) = self.block[offset..end].split_at_mut(HEADER_USIZE_COUNT);
+        header.copy_from_slice(&[size, kind as _]);
+
+        let (item_payload, item_data) = item.split_at_mut(USIZE_COUNT);
+        let item_payload: &mut [usize; USIZE_COUNT] = item_payload.try_into().or(Err(EOVERFLOW))?;
+        *<&mut T>::from(item_payload) = *payload;
+
+        item_data.fill(0);
+        for (word, chunk) in item_data.iter_mut().zip(data.chunks(size_of::<usize>())) {
+            let mut bytes = [0; size_of::<usize>()];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *word = usize::from_ne_bytes(bytes);
+        }
+
+        self.len = end;
+        self.terminate(end)
+    }
+}
+
+impl<'a> From<BlockWriter<'a>> for Block<'a> {
+    #[inline]
+    fn from(writer: BlockWriter<'a>) -> Self {
+        writer.block.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::Item;
+    use crate::libc::EINVAL;
+
+    use libc::{SYS_exit, SYS_read, ENOSYS};
+
+    #[test]
+    fn round_trip() {
+        let mut block = [0xff; 32];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        assert!(writer.is_empty());
+
+        let read = Syscall {
+            num: SYS_read as _,
+            argv: [1, 0, 5, 0, 0, 0],
+            ret: [-ENOSYS as _, 0],
+        };
+        writer.push_syscall(&read, b"hello").unwrap();
+        assert_eq!(writer.len(), HEADER_USIZE_COUNT + syscall::USIZE_COUNT + 1);
+
+        let cpuid = Enarxcall {
+            num: enarxcall::Number::Cpuid,
+            argv: [0, 0, 0, 0],
+            ret: -ENOSYS as _,
+        };
+        writer.push_enarxcall(&cpuid, &[]).unwrap();
+
+        let exit = Syscall {
+            num: SYS_exit as _,
+            argv: [0; 6],
+            ret: [-ENOSYS as _, 0],
+        };
+        assert_eq!(writer.push_syscall(&exit, &[0; 64]), Err(ENOMEM));
+
+        let mut items = Block::from(writer).into_iter();
+        assert!(matches!(
+            items.next(),
+            Some(Item::Syscall(call, data)) if *call == read && data == b"hello\0\0\0"
+        ));
+        assert!(matches!(
+            items.next(),
+            Some(Item::Enarxcall(call, data)) if *call == cpuid && data.is_empty()
+        ));
+        assert!(items.next().is_none());
+    }
+
+    #[test]
+    fn append() {
+        let mut block = [0; 32];
+        let exit = Syscall {
+            num: SYS_exit as _,
+            argv: [0; 6],
+            ret: [-ENOSYS as _, 0],
+        };
+        BlockWriter::new(&mut block)
+            .unwrap()
+            .push_syscall(&exit, &[])
+            .unwrap();
+
+        let signal = Notification {
+            num: notification::Number::Signal,
+            argv: [2, 0],
+        };
+        let mut writer = BlockWriter::append(&mut block).unwrap();
+        assert_eq!(writer.len(), HEADER_USIZE_COUNT + syscall::USIZE_COUNT);
+        writer.push_notification(&signal).unwrap();
+
+        let mut items = Block::from(writer).into_iter();
+        assert!(matches!(items.next(), Some(Item::Syscall(call, _)) if *call == exit));
+        assert!(matches!(items.next(), Some(Item::Notification(call, _)) if *call == signal));
+        assert!(items.next().is_none());
+
+        block[1] = Kind::End as _;
+        assert_eq!(BlockWriter::append(&mut block), Err(EINVAL));
+    }
+
+    #[test]
+    fn too_small() {
+        assert_eq!(BlockWriter::new(&mut [0; 1]), Err(ENOMEM));
+
+        let mut block = [0; HEADER_USIZE_COUNT];
+        let mut writer = BlockWriter::new(&mut block).unwrap();
+        let call = Gdbcall {
+            num: gdbcall::Number::Flush,
+            argv: [0; 4],
+            ret: -ENOSYS as _,
+        };
+        assert_eq!(writer.push_gdbcall(&call, &[]), Err(ENOMEM));
+        assert!(Block::from(writer).try_iter().next().is_none());
+    }
+}
@@ -33,6 +33,9 @@
 //! * `SYSCALL`: `1`
 //! * `GDBCALL`: `2`
 //! * `ENARXCALL`: `3`
+//! * `HELLO`: `4`
+//! * `NOTIFICATION`: `5`
+//! * `LINK`: `6`
 //!
 //! ### End
 //!
@@ -77,9 +80,46 @@
 //! * `ret`: `usize` - the return value
 //! * `data`: `...` - data that can be referenced (optional)
 //!
+//! ### Hello
+//!
+//! A `HELLO` item performs the protocol version handshake and has the following contents:
+//!
+//! * `vers`: `usize` - the offset of the guest [`VERSION`] within the data section
+//! * `vlen`: `usize` - the length of the guest [`VERSION`]
+//! * `reqs`: `usize` - the offset of the guest [`REQUIRES`] within the data section
+//! * `rlen`: `usize` - the length of the guest [`REQUIRES`]
+//! * `rply`: `usize` - the offset of the host [reply](item::hello::Reply) within the data section
+//! * `ret`: `usize` - the return value
+//! * `data`: `...` - data that can be referenced
+//!
+//! The host writes its version and the item kinds, syscalls and Enarx calls it supports into the reply. The return value is `0` if the host version satisfies the guest [`REQUIRES`] and [`-EPROTO`](libc::EPROTO) otherwise.
+//!
+//! The guest [`Handler`](guest::Handler) performs the handshake on its first exit to the host and fails all calls with [`EPROTO`](libc::EPROTO) if the host is incompatible.
+//!
+//! ### Notification
+//!
+//! A `NOTIFICATION` item is passed from the host to the guest and has the following contents:
+//!
+//! * `nmbr`: `usize` - the [notification number](item::notification::Number)
+//! * `arg0`: `usize` - the first argument
+//! * `arg1`: `usize` - the second argument
+//!
+//! The host MAY write notification items after the last item passed by the guest in place of the `END` item, as long as the block is terminated by an `END` item afterwards. The guest MUST ignore notifications with invalid arguments.
+//!
+//! ### Link
+//!
+//! A `LINK` item feeds the first return value of a preceding item into an argument of the item following it and has the following contents:
+//!
+//! * `back`: `usize` - the count of items preceding the next item to go back to reach the source item, at most [`BACK_MAX`](item::link::BACK_MAX)
+//! * `arg`: `usize` - the index of the argument of the next item to replace
+//!
+//! `LINK` items are not counted when going back. If the source item failed, the host MUST NOT execute the next item and MUST write [`-ECANCELED`](libc::ECANCELED) into its first return value. If the reference is invalid, the host MUST write [`-EINVAL`](libc::EINVAL) instead.
+//!
 //!
 //! The argument values may contain numeric values. However, all pointers MUST be translated to an offset from the beginning of the data section.
 //!
+//! If the host cannot execute an item, for example, because an argument references memory outside of the data section, it MUST write the negated error number into the first return value of the item and proceed with the next item.
+//!
 //! ## Example
 //!
 //! Here's an example of how the `sallyport` protocol might be used to proxy a syscall between
@@ -16,6 +16,7 @@
 #![allow(non_upper_case_globals)]
 
 use core::ffi::{c_char, c_int, c_long, c_short, c_size_t, c_uint, c_ulong, c_void};
+use core::mem::size_of;
 
 pub type blkcnt_t = i64;
 pub type blksize_t = i64;
@@ -36,6 +37,14 @@ pub type time_t = i64;
 pub type uid_t = u32;
 pub type Ioctl = i32;
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct cmsghdr {
+    pub cmsg_len: c_size_t,
+    pub cmsg_level: c_int,
+    pub cmsg_type: c_int,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct epoll_event {
@@ -63,6 +72,18 @@ pub struct in6_addr {
     pub s6_addr: [u8; 16],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct msghdr {
+    pub msg_name: *mut c_void,
+    pub msg_namelen: socklen_t,
+    pub msg_iov: *mut iovec,
+    pub msg_iovlen: c_size_t,
+    pub msg_control: *mut c_void,
+    pub msg_controllen: c_size_t,
+    pub msg_flags: c_int,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct pollfd {
@@ -149,6 +170,40 @@ pub struct stat {
     __unused: [c_long; 3],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct statx {
+    pub stx_mask: u32,
+    pub stx_blksize: u32,
+    pub stx_attributes: u64,
+    pub stx_nlink: u32,
+    pub stx_uid: u32,
+    pub stx_gid: u32,
+    pub stx_mode: u16,
+    __statx_pad1: [u16; 1],
+    pub stx_ino: u64,
+    pub stx_size: u64,
+    pub stx_blocks: u64,
+    pub stx_attributes_mask: u64,
+    pub stx_atime: statx_timestamp,
+    pub stx_btime: statx_timestamp,
+    pub stx_ctime: statx_timestamp,
+    pub stx_mtime: statx_timestamp,
+    pub stx_rdev_major: u32,
+    pub stx_rdev_minor: u32,
+    pub stx_dev_major: u32,
+    pub stx_dev_minor: u32,
+    __statx_pad2: [u64; 14],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct statx_timestamp {
+    pub tv_sec: i64,
+    pub tv_nsec: u32,
+    __statx_timestamp_pad1: [i32; 1],
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct timespec {
@@ -163,6 +218,14 @@ pub struct timeval {
     pub tv_usec: suseconds_t,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ucred {
+    pub pid: pid_t,
+    pub uid: uid_t,
+    pub gid: gid_t,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct utsname {
@@ -175,22 +238,58 @@ pub struct utsname {
 }
 
 pub const AF_INET: c_int = 2;
+pub const AF_INET6: c_int = 10;
+pub const AF_PACKET: c_int = 17;
+pub const AF_UNIX: c_int = 1;
+pub const AT_EMPTY_PATH: c_int = 0x1000;
+pub const AT_FDCWD: c_int = -100;
+pub const AT_REMOVEDIR: c_int = 0x200;
+pub const AT_SYMLINK_NOFOLLOW: c_int = 0x100;
 pub const EACCES: c_int = 13;
+pub const EADDRINUSE: c_int = 98;
+pub const EADDRNOTAVAIL: c_int = 99;
+pub const EAFNOSUPPORT: c_int = 97;
 pub const EAGAIN: c_int = 11;
 pub const EBADF: c_int = 9;
 pub const EBADFD: c_int = 77;
+pub const EBUSY: c_int = 16;
+pub const ECANCELED: c_int = 125;
+pub const ECONNREFUSED: c_int = 111;
+pub const ECONNRESET: c_int = 104;
+pub const EDESTADDRREQ: c_int = 89;
+pub const EEXIST: c_int = 17;
 pub const EFAULT: c_int = 14;
+pub const EFD_CLOEXEC: c_int = O_CLOEXEC;
+pub const EFD_NONBLOCK: c_int = O_NONBLOCK;
+pub const EFD_SEMAPHORE: c_int = 1;
 pub const EINTR: c_int = 4;
 pub const EINVAL: c_int = 22;
 pub const EIO: c_int = 5;
+pub const EISCONN: c_int = 106;
+pub const EMFILE: c_int = 24;
 pub const EMSGSIZE: c_int = 90;
+pub const ENETUNREACH: c_int = 101;
+pub const ENFILE: c_int = 23;
+pub const ENODATA: c_int = 61;
 pub const ENOENT: c_int = 2;
 pub const ENOMEM: c_int = 12;
+pub const ENOSPC: c_int = 28;
 pub const ENOSYS: c_int = 38;
+pub const ENOTCONN: c_int = 107;
+pub const ENOTSOCK: c_int = 88;
 pub const ENOTSUP: c_int = 95;
 pub const ENOTTY: c_int = 25;
 pub const EOVERFLOW: c_int = 75;
 pub const EPERM: c_int = 1;
+pub const EPIPE: c_int = 32;
+pub const EPOLL_CLOEXEC: c_int = O_CLOEXEC;
+pub const EPOLL_CTL_ADD: c_int = 1;
+pub const EPOLL_CTL_DEL: c_int = 2;
+pub const EPOLL_CTL_MOD: c_int = 3;
+pub const EPROTO: c_int = 71;
+pub const FD_CLOEXEC: c_int = 1;
+pub const F_DUPFD: c_int = 0;
+pub const F_DUPFD_CLOEXEC: c_int = 1030;
 pub const F_GETFD: c_int = 1;
 pub const F_GETFL: c_int = 3;
 pub const F_SETFD: c_int = 2;
@@ -199,27 +298,62 @@ pub const FIONBIO: Ioctl = 0x5421;
 pub const FIONREAD: Ioctl = 0x541B;
 pub const GRND_NONBLOCK: c_uint = 1;
 pub const GRND_RANDOM: c_uint = 2;
+pub const IPPROTO_TCP: c_int = 6;
 pub const MAP_ANONYMOUS: c_int = 32;
+pub const MAP_POPULATE: c_int = 0x8000;
 pub const MAP_PRIVATE: c_int = 2;
+pub const MAP_SHARED: c_int = 1;
 pub const MREMAP_DONTUNMAP: c_int = 4;
 pub const MREMAP_FIXED: c_int = 2;
 pub const MREMAP_MAYMOVE: c_int = 1;
+pub const MSG_CMSG_CLOEXEC: c_int = 0x40000000;
+pub const MSG_CTRUNC: c_int = 8;
+pub const MSG_DONTWAIT: c_int = 0x40;
 pub const MSG_NOSIGNAL: c_int = 16384;
+pub const MSG_PEEK: c_int = 2;
+pub const MSG_TRUNC: c_int = 0x20;
+pub const O_ACCMODE: c_int = 3;
 pub const O_APPEND: c_int = 1024;
 pub const O_CLOEXEC: c_int = 0x80000;
 pub const O_CREAT: c_int = 64;
+pub const O_NONBLOCK: c_int = 2048;
 pub const O_RDONLY: c_int = 0;
 pub const O_RDWR: c_int = 2;
+pub const O_TRUNC: c_int = 512;
 pub const O_WRONLY: c_int = 1;
+pub const POLLERR: c_short = 0x8;
+pub const POLLHUP: c_short = 0x10;
+pub const POLLIN: c_short = 0x1;
+pub const POLLNVAL: c_short = 0x20;
+pub const POLLOUT: c_short = 0x4;
 pub const PROT_EXEC: c_int = 4;
 pub const PROT_READ: c_int = 1;
 pub const PROT_WRITE: c_int = 2;
+pub const RENAME_EXCHANGE: c_uint = 2;
+pub const RENAME_NOREPLACE: c_uint = 1;
+pub const SCM_CREDENTIALS: c_int = 2;
+pub const SCM_RIGHTS: c_int = 1;
+pub const SEEK_CUR: c_int = 1;
+pub const SEEK_END: c_int = 2;
+pub const SEEK_SET: c_int = 0;
+pub const S_IFBLK: mode_t = 0o060000;
+pub const S_IFCHR: mode_t = 0o020000;
+pub const S_IFDIR: mode_t = 0o040000;
 pub const S_IFIFO: mode_t = 4096;
+pub const S_IFLNK: mode_t = 0o120000;
+pub const S_IFMT: mode_t = 0o170000;
+pub const S_IFREG: mode_t = 0o100000;
+pub const S_IFSOCK: mode_t = 0o140000;
 pub const SOCK_CLOEXEC: c_int = O_CLOEXEC;
+pub const SOCK_DGRAM: c_int = 2;
+pub const SOCK_NONBLOCK: c_int = O_NONBLOCK;
 pub const SOCK_STREAM: c_int = 1;
 pub const SOL_SOCKET: c_int = 1;
+pub const SO_PASSCRED: c_int = 16;
 pub const SO_RCVTIMEO: c_int = 20;
 pub const SO_REUSEADDR: c_int = 2;
+pub const STATX_BASIC_STATS: c_uint = 0x7ff;
+pub const STATX_TYPE: c_uint = 0x1;
 pub const STDERR_FILENO: c_int = 2;
 pub const STDIN_FILENO: c_int = 0;
 pub const STDOUT_FILENO: c_int = 1;
@@ -242,8 +376,11 @@ pub const SYS_epoll_wait: c_long = 232;
 pub const SYS_eventfd2: c_long = 290;
 pub const SYS_exit: c_long = 60;
 pub const SYS_exit_group: c_long = 231;
+pub const SYS_fchmod: c_long = 91;
 pub const SYS_fcntl: c_long = 72;
 pub const SYS_fstat: c_long = 5;
+pub const SYS_ftruncate: c_long = 77;
+pub const SYS_getdents64: c_long = 217;
 pub const SYS_getegid: c_long = 108;
 pub const SYS_geteuid: c_long = 107;
 pub const SYS_getgid: c_long = 104;
@@ -253,27 +390,57 @@ pub const SYS_getrandom: c_long = 318;
 pub const SYS_getsockname: c_long = 51;
 pub const SYS_ioctl: c_long = 16;
 pub const SYS_listen: c_long = 50;
+pub const SYS_lseek: c_long = 8;
 pub const SYS_madvise: c_long = 28;
+pub const SYS_mkdirat: c_long = 258;
 pub const SYS_mmap: c_long = 9;
 pub const SYS_mprotect: c_long = 10;
 pub const SYS_mremap: c_long = 25;
 pub const SYS_munmap: c_long = 11;
 pub const SYS_nanosleep: c_long = 35;
+pub const SYS_newfstatat: c_long = 262;
 pub const SYS_open: c_long = 2;
+pub const SYS_openat: c_long = 257;
 pub const SYS_poll: c_long = 7;
+pub const SYS_pread64: c_long = 17;
+pub const SYS_preadv: c_long = 295;
+pub const SYS_pwrite64: c_long = 18;
+pub const SYS_pwritev: c_long = 296;
 pub const SYS_read: c_long = 0;
 pub const SYS_readlink: c_long = 89;
 pub const SYS_readv: c_long = 19;
 pub const SYS_recvfrom: c_long = 45;
+pub const SYS_recvmsg: c_long = 47;
+pub const SYS_renameat2: c_long = 316;
 pub const SYS_rt_sigaction: c_long = 13;
 pub const SYS_rt_sigprocmask: c_long = 14;
 pub const SYS_set_tid_address: c_long = 218;
+pub const SYS_sendmsg: c_long = 46;
 pub const SYS_sendto: c_long = 44;
 pub const SYS_setsockopt: c_long = 54;
 pub const SYS_sigaltstack: c_long = 131;
 pub const SYS_socket: c_long = 41;
+pub const SYS_statx: c_long = 332;
 pub const SYS_sync: c_long = 162;
 pub const SYS_uname: c_long = 63;
+pub const SYS_unlinkat: c_long = 263;
 pub const SYS_write: c_long = 1;
 pub const SYS_writev: c_long = 20;
+pub const TCP_NODELAY: c_int = 1;
 pub const TIOCGWINSZ: Ioctl = 0x5413;
+pub const UIO_MAXIOV: c_int = 1024;
+
+#[allow(non_snake_case)]
+pub const fn CMSG_ALIGN(len: usize) -> usize {
+    (len + size_of::<usize>() - 1) & !(size_of::<usize>() - 1)
+}
+
+#[allow(non_snake_case)]
+pub const fn CMSG_LEN(len: usize) -> usize {
+    CMSG_ALIGN(size_of::<cmsghdr>()) + len
+}
+
+#[allow(non_snake_case)]
+pub const fn CMSG_SPACE(len: usize) -> usize {
+    CMSG_ALIGN(size_of::<cmsghdr>()) + CMSG_ALIGN(len)
+}
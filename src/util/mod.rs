@@ -3,3 +3,4 @@
 //! Utilities
 
 pub mod ptr;
+pub mod version;
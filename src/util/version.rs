@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Utility functions for semantic versions
+
+use core::cmp::Ordering;
+
+/// Parses the `major.minor.patch` triple and the pre-release, if any, of a semantic `version`,
+/// ignoring the build metadata, if any.
+fn parse(version: &str) -> Option<([u64; 3], Option<&str>)> {
+    let version = version.split('+').next()?;
+    let (version, pre) = match version.split_once('-') {
+        Some((_, "")) => return None,
+        Some((version, pre)) => (version, Some(pre)),
+        None => (version, None),
+    };
+    let mut parts = version.split('.');
+    let mut triple = [0; 3];
+    for v in triple.iter_mut() {
+        *v = parts.next()?.parse().ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((triple, pre))
+}
+
+/// Compares the pre-releases `a` and `b` by semantic version precedence.
+fn cmp_pre(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.split('.'), b.split('.'));
+    loop {
+        let ord = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+/// Returns `true` if `version` satisfies the caret version requirement `requires`,
+/// for example, `^1.2.3`, and `false` otherwise.
+///
+/// A requirement without an operator is interpreted as a caret requirement.
+/// A pre-release `version` only satisfies a requirement on a pre-release of the same
+/// `major.minor.patch` triple.
+///
+/// See [this link](https://docs.rs/semver/1.0.0/semver/enum.Op.html#opcaretcompatible-updates)
+/// for more details.
+pub fn satisfies(version: &str, requires: &str) -> bool {
+    let requires = requires.strip_prefix('^').unwrap_or(requires);
+    match (parse(version), parse(requires)) {
+        (Some((version, pre)), Some((requires, requires_pre))) => {
+            let significant = match requires {
+                [0, 0, _] => 3,
+                [0, _, _] => 2,
+                _ => 1,
+            };
+            match (pre, requires_pre) {
+                (Some(pre), Some(requires_pre)) => {
+                    version == requires && cmp_pre(pre, requires_pre) != Ordering::Less
+                }
+                (Some(_), None) => false,
+                (None, _) => {
+                    version[..significant] == requires[..significant] && version >= requires
+                }
+            }
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn satisfies() {
+        for (version, requires, expected) in [
+            ("1.2.3", "^1.2.3", true),
+            ("1.3.0", "^1.2.3", true),
+            ("1.2.2", "^1.2.3", false),
+            ("2.0.0", "^1.2.3", false),
+            ("0.4.1", "^0.4.0", true),
+            ("0.5.0", "^0.4.0", false),
+            ("0.0.3", "^0.0.3", true),
+            ("0.0.4", "^0.0.3", false),
+            ("0.4.0-rc.1", "0.4.0", false),
+            ("0.4.0-rc.2", "^0.4.0-rc.1", true),
+            ("0.4.0-rc.10", "^0.4.0-rc.2", true),
+            ("0.4.0-rc.1", "^0.4.0-rc.2", false),
+            ("0.4.0-rc.1", "^0.4.0-rc.1.1", false),
+            ("0.4.0-1", "^0.4.0-rc", false),
+            ("0.4.1-rc.1", "^0.4.0-rc.1", false),
+            ("0.4.0", "^0.4.0-rc.1", true),
+            ("0.4.0+build", "0.4.0", true),
+            ("0.4.0-", "0.4.0", false),
+            ("0.4", "^0.4.0", false),
+            ("0.4.0", "^0.4.0.1", false),
+            ("", "", false),
+        ] {
+            assert_eq!(
+                super::satisfies(version, requires),
+                expected,
+                "`{}` against `{}`",
+                version,
+                requires
+            );
+        }
+    }
+}
@@ -3,10 +3,14 @@
 use super::run_test;
 
 use core::ptr::NonNull;
-use libc::ENOSYS;
-use std::arch::x86_64::{CpuidResult, __cpuid_count};
+use libc::{EMSGSIZE, ENOSYS, ENOTSUP};
+use std::arch::x86_64::{__cpuid_count, CpuidResult};
 
-use sallyport::guest::Handler;
+use sallyport::guest::{CpuidFeatures, Handler};
+#[cfg(not(miri))]
+use sallyport::host::{AnonMemory, Balloon};
+use sallyport::host::{Attestation, CpuidFilter, CpuidPolicy, CpuidRule, MockQuote, Native};
+use sallyport::item::enarxcall::sgx::Report;
 
 #[test]
 fn balloon_memory() {
@@ -15,6 +19,25 @@ fn balloon_memory() {
     })
 }
 
+#[test]
+#[cfg(not(miri))]
+fn balloon_memory_anon() {
+    run_test(1, [0xff; 16], move |_, _, handler| {
+        handler.executor = Some(Box::new(Balloon::new(
+            AnonMemory::<2>::new(1 << 21),
+            Native,
+        )));
+        assert_eq!(handler.mem_info(), Ok(2));
+        let addr = handler.balloon_memory(12, 16, 0x100000 as _).unwrap();
+        unsafe { (addr as *mut u8).write_bytes(0xff, 16 << 12) };
+        assert_eq!(handler.mem_info(), Ok(1));
+        assert_eq!(
+            handler.balloon_memory(21, 1, 0x200000 as _),
+            Err(libc::ENOMEM)
+        );
+    })
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn cpuid() {
@@ -30,6 +53,59 @@ fn cpuid() {
     })
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn cpuid_filtered() {
+    const RULES: &[CpuidRule] = &[
+        CpuidRule::HideHypervisor,
+        CpuidRule::Force {
+            leaf: 0,
+            sub_leaf: None,
+            value: CpuidResult {
+                eax: 0xd,
+                ebx: 0,
+                ecx: 0,
+                edx: 0,
+            },
+        },
+    ];
+
+    run_test(1, [0xff; 16], move |_, _, handler| {
+        handler.executor = Some(Box::new(CpuidFilter::new(CpuidPolicy::new(RULES), Native)));
+        let mut result = CpuidResult {
+            eax: 0,
+            ebx: 0,
+            ecx: 0,
+            edx: 0,
+        };
+        let hypervisor = CpuidFeatures {
+            leaf: 1,
+            sub_leaf: 0,
+            required: CpuidResult { ecx: 0, ..result },
+            forbidden: CpuidResult {
+                ecx: 1 << 31,
+                ..result
+            },
+        };
+        let max_leaf = CpuidFeatures {
+            leaf: 0,
+            sub_leaf: 0,
+            required: CpuidResult { eax: 0xe, ..result },
+            forbidden: result,
+        };
+        let features = [hypervisor, max_leaf];
+        assert_eq!(
+            handler.cpuid_validated(1, 0, &mut result, &features),
+            Ok(())
+        );
+        assert_eq!(
+            handler.cpuid_validated(0, 0, &mut result, &features),
+            Err(ENOTSUP)
+        );
+        assert_eq!(result.eax, 0xd);
+    })
+}
+
 #[test]
 fn get_sgx_quote() {
     run_test(1, [0xff; 1024], move |_, _, handler| {
@@ -39,6 +115,29 @@ fn get_sgx_quote() {
     })
 }
 
+#[test]
+fn get_sgx_quote_mock() {
+    run_test(1, [0xff; 1024], move |_, _, handler| {
+        handler.executor = Some(Box::new(Attestation::new(MockQuote, Native)));
+
+        let mut info = Default::default();
+        assert_eq!(handler.get_sgx_target_info(&mut info), Ok(()));
+        assert_eq!(info.mrenclave, MockQuote::MRENCLAVE);
+
+        let mut report = Report::default();
+        report.payload.reportdata = [0x42; 64];
+        let size = handler.get_sgx_quote_size().unwrap();
+        assert_eq!(size, MockQuote::QUOTE_SIZE);
+        let mut quote = vec![0; size];
+        assert_eq!(handler.get_sgx_quote(&report, &mut quote), Ok(size));
+        assert_eq!(quote[48 + 320..48 + 384], [0x42; 64]);
+        assert_eq!(
+            handler.get_sgx_quote(&report, &mut quote[..size - 1]),
+            Err(EMSGSIZE)
+        );
+    })
+}
+
 #[test]
 fn get_snp_vcek() {
     run_test(1, [0xff; 512], move |_, _, handler| {
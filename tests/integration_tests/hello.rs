@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::run_test;
+
+use libc::{SYS_read, SYS_write, ENOSYS, EPROTO};
+use std::cell::Cell;
+use std::rc::Rc;
+
+use sallyport::guest::Handler;
+use sallyport::host::{self, Executor};
+use sallyport::item::hello::Reply;
+use sallyport::item::{enarxcall, Block, BlockWriter, Hello, Item, Kind};
+use sallyport::Result;
+use sallyport::VERSION;
+
+#[test]
+fn hello() {
+    run_test(1, [0xff; 32], move |_, _, handler| {
+        let mut reply = Reply::default();
+        assert_eq!(handler.hello(&mut reply), Ok(()));
+        assert_eq!(reply.version(), Some(VERSION));
+        assert!(reply.supports_kind(Kind::Hello));
+        assert!(!reply.supports_kind(Kind::Gdbcall));
+        #[cfg(not(miri))]
+        {
+            assert!(reply.supports_kind(Kind::Syscall));
+            assert!(reply.supports_syscall(SYS_read));
+            assert!(reply.supports_syscall(SYS_write));
+            assert!(reply.supports_enarxcall(enarxcall::Number::Cpuid));
+        }
+    })
+}
+
+#[test]
+fn hello_incompatible() {
+    const REQUIRES: &[u8] = b"^99.0.0";
+
+    let mut data = [0; REQUIRES.len() + 1 + core::mem::size_of::<Reply>()];
+    data[..REQUIRES.len()].copy_from_slice(REQUIRES);
+    let reply = REQUIRES.len() + 1;
+
+    let mut block = [0; 64];
+    let mut writer = BlockWriter::new(&mut block).unwrap();
+    writer
+        .push_hello(
+            &Hello {
+                version: 1,
+                version_len: REQUIRES.len() - 1,
+                requires: 0,
+                requires_len: REQUIRES.len(),
+                reply,
+                ret: 0,
+            },
+            &data,
+        )
+        .unwrap();
+
+    assert_eq!(host::execute(Block::from(writer)), Ok(()));
+    match Block::from(&mut block[..]).into_iter().next() {
+        Some(Item::Hello(call, _)) => assert_eq!(call.ret, -EPROTO as usize),
+        item => panic!("unexpected item {:?}", item),
+    }
+}
+
+/// [`Executor`] answering every handshake with `errno` and counting them.
+struct Handshake {
+    errno: i32,
+    count: Rc<Cell<usize>>,
+}
+
+impl Executor for Handshake {
+    fn hello(&mut self, _: &mut Hello, _: &mut [u8]) -> Result<()> {
+        self.count.set(self.count.get() + 1);
+        Err(self.errno)
+    }
+}
+
+#[test]
+fn hello_automatic() {
+    run_test(1, [0xff; 32], move |_, _, handler| {
+        let count = Rc::new(Cell::new(0));
+        handler.executor = Some(Box::new(Handshake {
+            errno: EPROTO,
+            count: count.clone(),
+        }));
+        for _ in 0..2 {
+            assert_eq!(handler.write(1, b""), Err(EPROTO));
+        }
+        assert_eq!(count.get(), 1);
+    });
+
+    run_test(1, [0xff; 32], move |_, _, handler| {
+        let count = Rc::new(Cell::new(0));
+        handler.executor = Some(Box::new(Handshake {
+            errno: ENOSYS,
+            count: count.clone(),
+        }));
+        for _ in 0..2 {
+            assert_eq!(
+                handler.write(1, b""),
+                if cfg!(not(miri)) { Ok(0) } else { Err(ENOSYS) }
+            );
+        }
+        assert_eq!(count.get(), 1);
+    });
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::run_test;
+
+use libc::ECANCELED;
+
+use sallyport::guest::call::Link;
+use sallyport::guest::{syscall, Handler};
+
+#[test]
+fn eventfd2_read_close() {
+    run_test(1, [0xff; 64], move |_, _, handler| {
+        let mut buf = [0u8; 8];
+        let ret = handler.execute((
+            (
+                syscall::Eventfd2 {
+                    initval: 3,
+                    flags: 0,
+                },
+                Link { back: 1, arg: 0 },
+                syscall::Read {
+                    fd: -1,
+                    buf: &mut buf,
+                },
+            ),
+            Link { back: 2, arg: 0 },
+            syscall::Close { fd: -1 },
+        ));
+
+        if cfg!(not(miri)) {
+            let ((fd, (), read), (), close) = ret.unwrap();
+            assert!(fd.unwrap() > 2);
+            assert_eq!(read, Some(Ok(buf.len())));
+            assert_eq!(close, Ok(()));
+            assert_eq!(buf, 3u64.to_ne_bytes());
+        } else {
+            let ((_, (), read), (), close) = ret.unwrap();
+            assert_eq!(read, Some(Err(ECANCELED)));
+            assert_eq!(close, Err(ECANCELED));
+        }
+    });
+}
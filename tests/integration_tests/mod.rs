@@ -2,6 +2,12 @@
 
 pub mod enarxcall;
 pub mod gdbcall;
+pub mod hello;
+pub mod link;
+pub mod notification;
+pub mod replay;
+pub mod ring;
+pub mod sim;
 pub mod syscall;
 
 use core::ffi::{c_int, c_size_t, c_ulong, c_void};
@@ -13,14 +19,37 @@ use std::ptr::NonNull;
 use std::thread;
 
 use sallyport::guest::{Handler, Platform, ThreadLocalStorage};
-use sallyport::item::Block;
+use sallyport::host::{Executor, Recorder};
+use sallyport::item::ring::Consumer;
+use sallyport::item::{Block, Notification};
 use sallyport::libc::off_t;
 use sallyport::util::ptr;
 use sallyport::{host, Result};
 
+pub type Sink = Box<dyn FnMut(&[u8]) -> Result<()>>;
+
 pub struct TestHandler<const N: usize> {
     block: [usize; N],
     tls: ThreadLocalStorage,
+    notifications: Vec<Notification>,
+    recorder: Option<Recorder<Sink>>,
+    executor: Option<Box<dyn Executor>>,
+    ring: Option<Consumer<'static>>,
+    exits: usize,
+}
+
+impl<const N: usize> TestHandler<N> {
+    pub fn new(block: [usize; N]) -> Self {
+        Self {
+            block,
+            tls: Default::default(),
+            notifications: Vec::new(),
+            recorder: None,
+            executor: None,
+            ring: None,
+            exits: 0,
+        }
+    }
 }
 
 pub struct TestPlatform;
@@ -57,7 +86,19 @@ impl Platform for TestPlatform {
 
 impl<const N: usize> Handler for TestHandler<N> {
     fn sally(&mut self) -> Result<()> {
-        host::execute(Block::from(self.block_mut()))
+        self.exits += 1;
+        match (&mut self.recorder, &mut self.executor) {
+            (Some(recorder), _) => recorder.execute(&mut self.block)?,
+            (None, Some(executor)) => {
+                host::execute_with(executor.as_mut(), Block::from(&mut self.block[..]))?
+            }
+            (None, None) => host::execute(Block::from(self.block_mut()))?,
+        }
+        if let Some(ring) = &mut self.ring {
+            host::drain(ring)?;
+        }
+        let notifications = std::mem::take(&mut self.notifications);
+        host::notify(self.block_mut(), notifications).map(|_| ())
     }
 
     fn block(&self) -> &[usize] {
@@ -141,10 +182,7 @@ where
             .name(format!("iteration {}", i))
             .spawn(move || {
                 let mut platform = TestPlatform;
-                let mut handler = TestHandler {
-                    block: block.clone(),
-                    tls: Default::default(),
-                };
+                let mut handler = TestHandler::new(block.clone());
                 f(i, &mut platform, &mut handler);
             })
             .expect(&format!("couldn't spawn test iteration {} thread", i))
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::run_test;
+
+use libc::{SIGINT, SIGUSR1};
+
+use sallyport::guest::{Handler, Notification};
+use sallyport::item::notification::Number;
+use sallyport::item::Notification as Item;
+
+#[test]
+fn notification() {
+    run_test(1, [0xff; 64], move |_, _, handler| {
+        let action = [1, 2, 3, 4];
+        assert_eq!(
+            handler.rt_sigaction(SIGUSR1, Some(&action), None, 8),
+            Ok(())
+        );
+
+        handler.notifications = vec![
+            Item {
+                num: Number::Readable,
+                argv: [3, 0],
+            },
+            Item {
+                num: Number::Signal,
+                argv: [SIGINT as _, 0],
+            },
+            Item {
+                num: Number::Signal,
+                argv: [SIGUSR1 as _, 0],
+            },
+            Item {
+                num: Number::ReclaimMemory,
+                argv: [4096, 0],
+            },
+        ];
+        assert_eq!(handler.take_notification(), None);
+        assert_eq!(handler.sync(), Ok(()));
+        assert!(handler.notifications.is_empty());
+
+        assert_eq!(
+            handler.take_notification(),
+            Some(Notification::Signal {
+                signum: SIGUSR1,
+                action
+            })
+        );
+        assert_eq!(
+            handler.take_notification(),
+            Some(Notification::Readable { fd: 3 })
+        );
+        assert_eq!(
+            handler.take_notification(),
+            Some(Notification::ReclaimMemory { size: 4096 })
+        );
+        assert_eq!(handler.take_notification(), None);
+    })
+}
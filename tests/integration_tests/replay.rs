@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{run_test, Sink, TestHandler};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use libc::{CLOCK_MONOTONIC, CLOCK_REALTIME, EIO, ENODATA};
+
+use sallyport::guest::{Handler, ReplayHandler};
+use sallyport::host::Recorder;
+use sallyport::libc::timespec;
+
+#[test]
+fn replay() {
+    run_test(1, [0xff; 32], move |_, _, handler| {
+        let recording = Rc::new(RefCell::new(Vec::new()));
+        let sink = recording.clone();
+        handler.recorder = Some(
+            Recorder::new(Box::new(move |bytes: &[u8]| {
+                sink.borrow_mut().extend_from_slice(bytes);
+                Ok(())
+            }) as Sink)
+            .unwrap(),
+        );
+
+        let mut recorded = [timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        }; 2];
+        for tp in &mut recorded {
+            assert_eq!(handler.clock_gettime(CLOCK_MONOTONIC, tp), Ok(()));
+        }
+        handler.recorder = None;
+        let recording = recording.borrow();
+
+        let mut replay = ReplayHandler::new(TestHandler::new([0xff; 32]), &recording).unwrap();
+        for expected in recorded {
+            let mut tp = timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            };
+            assert_eq!(replay.clock_gettime(CLOCK_MONOTONIC, &mut tp), Ok(()));
+            assert_eq!(tp, expected);
+        }
+        let mut tp = timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        assert_eq!(replay.clock_gettime(CLOCK_MONOTONIC, &mut tp), Err(ENODATA));
+
+        let mut replay = ReplayHandler::new(TestHandler::new([0xff; 32]), &recording).unwrap();
+        assert_eq!(replay.clock_gettime(CLOCK_REALTIME, &mut tp), Err(EIO));
+    })
+}
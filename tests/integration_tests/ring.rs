@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::run_test;
+
+use libc::{SYS_sync, ENOSYS};
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::prelude::{AsRawFd, FromRawFd};
+use std::ptr::NonNull;
+
+use sallyport::guest::{syscall, Handler};
+use sallyport::host;
+use sallyport::item::ring::{blocks_len, Consumer, Header, Producer, Ring};
+use sallyport::item::{Block, BlockWriter, Item, Syscall};
+use sallyport::libc::EAGAIN;
+
+#[test]
+fn ring() {
+    const BLOCK_SIZE: u64 = 32 * 8;
+    const NUM_BLOCKS: u64 = 4;
+
+    let header = Header::new();
+    let mut blocks = vec![0; blocks_len(BLOCK_SIZE, NUM_BLOCKS).unwrap()];
+    let mut ring = Ring::new(&header, &mut blocks, BLOCK_SIZE as _).unwrap();
+    let (mut ring, mut consumer) = ring.split();
+
+    let sync = Syscall {
+        num: SYS_sync as _,
+        argv: [0; 6],
+        ret: [-ENOSYS as _, 0],
+    };
+    while !ring.is_full() {
+        ring.submit(|block| {
+            let mut writer = BlockWriter::new(block)?;
+            writer.push_syscall(&sync, &[])?;
+            writer.push_syscall(&sync, &[])
+        })
+        .unwrap();
+    }
+    assert_eq!(ring.submit(|_| Ok(())), Err(EAGAIN));
+    assert_eq!(ring.collect(|_| ()), Ok(None));
+
+    assert_eq!(host::drain(&mut consumer), Ok(NUM_BLOCKS as _));
+    assert!(ring.is_idle());
+
+    let mut collected = 0;
+    while let Some(rets) = ring
+        .collect(|block| {
+            Block::from(block)
+                .into_iter()
+                .map(|item| match item {
+                    Item::Syscall(call, _) => call.ret[0],
+                    _ => panic!("unexpected item"),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap()
+    {
+        #[cfg(not(miri))]
+        assert_eq!(rets, [0; 2]);
+        #[cfg(miri)]
+        assert_eq!(rets.len(), 2);
+        collected += 1;
+    }
+    assert_eq!(collected, NUM_BLOCKS);
+    assert!(!ring.is_full());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn handler() {
+    const BLOCK_SIZE: u64 = 32 * 8;
+    const NUM_BLOCKS: u64 = 2;
+
+    run_test(1, [0xff; 32], move |_, _, handler| {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let mut reader = unsafe { File::from_raw_fd(fds[0]) };
+        let writer = unsafe { File::from_raw_fd(fds[1]) };
+
+        // The host and the guest share the ring for the rest of the process.
+        let header = Box::leak(Box::new(Header::new()));
+        let blocks = Box::leak(vec![0; blocks_len(BLOCK_SIZE, NUM_BLOCKS).unwrap()].into());
+        let blocks = NonNull::from(blocks);
+        let mut ring = unsafe { Producer::new(header, blocks, BLOCK_SIZE as _) }.unwrap();
+        handler.ring = Some(unsafe { Consumer::new(header, blocks, BLOCK_SIZE as _) }.unwrap());
+        // Perform the handshake in advance.
+        assert_eq!(handler.sync(), Ok(()));
+        let exits = handler.exits;
+
+        let write = |buf| syscall::Write {
+            fd: writer.as_raw_fd(),
+            buf,
+        };
+        let first = handler.enqueue(&mut ring, write(b"ring")).unwrap();
+        let second = handler.enqueue(&mut ring, write(b"s")).unwrap();
+        assert!(ring.is_full());
+        assert_eq!(
+            handler.enqueue(&mut ring, write(b"full")).err(),
+            Some(EAGAIN)
+        );
+        assert_eq!(handler.exits, exits);
+
+        assert_eq!(handler.dequeue(&mut ring, first), Ok(Some(Ok(4))));
+        assert_eq!(handler.exits, exits + 1);
+        assert!(ring.is_idle());
+        assert_eq!(handler.dequeue(&mut ring, second), Ok(Some(Ok(1))));
+        assert_eq!(handler.exits, exits + 1);
+        drop(writer);
+
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "rings");
+    });
+}
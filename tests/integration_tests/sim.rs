@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::run_test;
+
+use libc::CLOCK_MONOTONIC;
+use std::mem::size_of;
+
+use sallyport::guest::Handler;
+use sallyport::host::SimKernel;
+use sallyport::libc::{
+    epoll_event, in_addr, pollfd, sockaddr_in, timespec, AF_INET, EAGAIN, EPOLL_CTL_ADD, POLLIN,
+    POLLOUT, SOCK_CLOEXEC, SOCK_DGRAM, SOCK_STREAM,
+};
+
+fn loopback(port: u16) -> sockaddr_in {
+    sockaddr_in {
+        sin_family: AF_INET as _,
+        sin_port: port.to_be(),
+        sin_addr: in_addr {
+            s_addr: u32::from_ne_bytes([127, 0, 0, 1]),
+        },
+        sin_zero: [0; 8],
+    }
+}
+
+#[test]
+fn pipe() {
+    run_test(1, [0xff; 64], move |_, _, handler| {
+        let mut sim = SimKernel::<8>::new();
+        let [reader, writer] = sim.pipe(0).unwrap();
+        handler.executor = Some(Box::new(sim));
+
+        let mut buf = [0; 8];
+        assert_eq!(handler.read(reader, &mut buf), Err(EAGAIN));
+        assert_eq!(handler.write(writer, b"hello"), Ok(5));
+        let mut fds = [pollfd {
+            fd: reader,
+            events: POLLIN | POLLOUT,
+            revents: 0,
+        }];
+        assert_eq!(handler.poll(&mut fds, -1), Ok(1));
+        assert_eq!(fds[0].revents, POLLIN);
+        assert_eq!(handler.read(reader, &mut buf), Ok(5));
+        assert_eq!(&buf[..5], b"hello");
+        assert_eq!(handler.close(writer), Ok(()));
+        assert_eq!(handler.read(reader, &mut buf), Ok(0));
+    })
+}
+
+#[test]
+fn tcp() {
+    run_test(1, [0xff; 64], move |_, _, handler| {
+        handler.executor = Some(Box::new(SimKernel::<8>::new()));
+
+        let listener = handler.socket(AF_INET, SOCK_STREAM, 0).unwrap();
+        assert_eq!(handler.bind(listener, &loopback(0)), Ok(()));
+        assert_eq!(handler.listen(listener, 1), Ok(()));
+        let mut addr = loopback(0);
+        let mut addrlen = size_of::<sockaddr_in>() as _;
+        assert_eq!(
+            handler.getsockname(listener, (&mut addr, &mut addrlen)),
+            Ok(())
+        );
+        assert_ne!(addr.sin_port, 0);
+
+        let client = handler.socket(AF_INET, SOCK_STREAM, 0).unwrap();
+        assert_eq!(handler.connect(client, &addr), Ok(()));
+        let server = handler
+            .accept4(listener, None::<(&mut sockaddr_in, _)>, SOCK_CLOEXEC)
+            .unwrap();
+
+        let epfd = handler.epoll_create1(0).unwrap();
+        let event = epoll_event {
+            events: POLLIN as _,
+            u64: server as _,
+        };
+        assert_eq!(
+            handler.epoll_ctl(epfd, EPOLL_CTL_ADD, server, &event),
+            Ok(())
+        );
+        let mut events = [epoll_event { events: 0, u64: 0 }; 2];
+        assert_eq!(handler.epoll_wait(epfd, &mut events, 1000), Ok(0));
+
+        assert_eq!(handler.write(client, b"ping"), Ok(4));
+        assert_eq!(handler.epoll_wait(epfd, &mut events, 1000), Ok(1));
+        assert_eq!(events[0], event);
+        let mut buf = [0; 4];
+        assert_eq!(handler.read(server, &mut buf), Ok(4));
+        assert_eq!(&buf, b"ping");
+
+        let mut tp = timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        assert_eq!(handler.clock_gettime(CLOCK_MONOTONIC, &mut tp), Ok(()));
+        assert_eq!(tp.tv_sec, 1);
+    })
+}
+
+#[test]
+fn udp() {
+    run_test(1, [0xff; 64], move |_, _, handler| {
+        handler.executor = Some(Box::new(SimKernel::<8>::new()));
+
+        let server = handler.socket(AF_INET, SOCK_DGRAM, 0).unwrap();
+        assert_eq!(handler.bind(server, &loopback(5353)), Ok(()));
+        let client = handler.socket(AF_INET, SOCK_DGRAM, 0).unwrap();
+        assert_eq!(handler.sendto(client, b"query", 0, &loopback(5353)), Ok(5));
+
+        let mut buf = [0; 8];
+        let mut src = loopback(0);
+        let mut srclen = size_of::<sockaddr_in>() as _;
+        assert_eq!(
+            handler.recvfrom(server, &mut buf, 0, (&mut src, &mut srclen)),
+            Ok(5)
+        );
+        assert_eq!(&buf[..5], b"query");
+        assert_ne!(src.sin_port, 0);
+    })
+}
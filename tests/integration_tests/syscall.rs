@@ -4,32 +4,40 @@ use super::{run_test, write_tcp};
 use crate::integration_tests::recv_udp;
 
 use libc::{
-    self, in_addr, iovec, pollfd, sockaddr, sockaddr_in, timespec, timeval, utsname, SYS_accept,
-    SYS_accept4, SYS_bind, SYS_clock_getres, SYS_clock_gettime, SYS_close, SYS_fcntl, SYS_fstat,
-    SYS_getegid, SYS_geteuid, SYS_getgid, SYS_getpid, SYS_getrandom, SYS_getsockname, SYS_listen,
-    SYS_mremap, SYS_nanosleep, SYS_open, SYS_poll, SYS_read, SYS_readlink, SYS_readv, SYS_recvfrom,
-    SYS_rt_sigaction, SYS_rt_sigprocmask, SYS_sendto, SYS_set_tid_address, SYS_setsockopt,
-    SYS_sigaltstack, SYS_socket, SYS_uname, SYS_write, SYS_writev, AF_INET, CLOCK_MONOTONIC,
-    CLOCK_REALTIME, EACCES, EBADF, EBADFD, EINVAL, ENOENT, ENOSYS, ENOTSUP, F_GETFD, F_GETFL,
-    F_SETFD, F_SETFL, GRND_RANDOM, MREMAP_DONTUNMAP, MREMAP_FIXED, MREMAP_MAYMOVE, MSG_NOSIGNAL,
-    O_APPEND, O_CREAT, O_RDONLY, O_RDWR, O_WRONLY, SIGCHLD, SIG_BLOCK, SOCK_CLOEXEC, SOCK_STREAM,
-    SOL_SOCKET, SO_RCVTIMEO, SO_REUSEADDR, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO,
+    self, cmsghdr, in_addr, iovec, mode_t, msghdr, pollfd, sockaddr, sockaddr_in, timespec,
+    timeval, utsname, SYS_accept, SYS_accept4, SYS_bind, SYS_clock_getres, SYS_clock_gettime,
+    SYS_close, SYS_fchmod, SYS_fcntl, SYS_fstat, SYS_ftruncate, SYS_getdents64, SYS_getegid,
+    SYS_geteuid, SYS_getgid, SYS_getpid, SYS_getrandom, SYS_getsockname, SYS_listen, SYS_lseek,
+    SYS_mkdirat, SYS_mremap, SYS_nanosleep, SYS_newfstatat, SYS_open, SYS_openat, SYS_poll,
+    SYS_pread64, SYS_preadv, SYS_pwrite64, SYS_pwritev, SYS_read, SYS_readlink, SYS_readv,
+    SYS_recvfrom, SYS_recvmsg, SYS_renameat2, SYS_rt_sigaction, SYS_rt_sigprocmask, SYS_sendmsg,
+    SYS_sendto, SYS_set_tid_address, SYS_setsockopt, SYS_sigaltstack, SYS_socket, SYS_statx,
+    SYS_uname, SYS_unlinkat, SYS_write, SYS_writev, AF_INET, AT_EMPTY_PATH, AT_FDCWD, AT_REMOVEDIR,
+    CLOCK_MONOTONIC, CLOCK_REALTIME, EACCES, EBADF, EEXIST, EINVAL, ENODATA, ENOENT, ENOSYS,
+    ENOTSUP, F_GETFD, F_GETFL, F_SETFD, F_SETFL, GRND_RANDOM, MREMAP_DONTUNMAP, MREMAP_FIXED,
+    MREMAP_MAYMOVE, MSG_NOSIGNAL, O_APPEND, O_CREAT, O_RDONLY, O_RDWR, O_WRONLY, RENAME_NOREPLACE,
+    SCM_RIGHTS, SEEK_END, SEEK_SET, SIGCHLD, SIG_BLOCK, SOCK_CLOEXEC, SOCK_STREAM, SOL_SOCKET,
+    SO_RCVTIMEO, SO_REUSEADDR, STATX_BASIC_STATS, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO,
+    S_IFMT, S_IFREG,
 };
 use std::env::temp_dir;
-use std::ffi::{c_char, c_int, CString};
-use std::fs::{File, OpenOptions};
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Seek, Write};
 use std::mem::{size_of, transmute};
 use std::net::{TcpListener, UdpSocket};
-use std::os::unix::prelude::AsRawFd;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::os::unix::prelude::{AsRawFd, FromRawFd};
 use std::ptr::{null_mut, NonNull};
 use std::slice;
 use std::{mem, thread};
 
-use sallyport::guest::syscall::types::SockaddrOutput;
+use sallyport::guest::syscall::types::{MsghdrInput, MsghdrOutput, SockaddrOutput};
 use sallyport::guest::syscall::{FAKE_GID, FAKE_PID, FAKE_TID, FAKE_UID};
 use sallyport::guest::{syscall, Handler, Platform};
 use sallyport::item::syscall::sigaction;
+use sallyport::libc::{CMSG_LEN, CMSG_SPACE};
 use serial_test::serial;
 
 fn syscall_socket<'a, 'b>(
@@ -86,6 +94,53 @@ fn syscall_recv<'a, 'b>(
     }
 }
 
+/// Control buffer holding a single `SCM_RIGHTS` message with one file descriptor.
+type Rights = [u64; CMSG_SPACE(size_of::<c_int>()) / size_of::<u64>()];
+
+/// Returns a control buffer passing `fd`.
+fn scm_rights(fd: c_int) -> Rights {
+    let mut control: Rights = Default::default();
+    let cmsg = control.as_mut_ptr() as *mut cmsghdr;
+    unsafe {
+        (*cmsg).cmsg_len = CMSG_LEN(size_of::<c_int>());
+        (*cmsg).cmsg_level = SOL_SOCKET;
+        (*cmsg).cmsg_type = SCM_RIGHTS;
+        libc::CMSG_DATA(cmsg).cast::<c_int>().write_unaligned(fd);
+    }
+    control
+}
+
+/// Returns the file descriptor passed in `control` as a stream.
+fn passed(control: &Rights) -> UnixStream {
+    let cmsg = control.as_ptr() as *const cmsghdr;
+    unsafe {
+        assert_eq!((*cmsg).cmsg_len, CMSG_LEN(size_of::<c_int>()));
+        assert_eq!(
+            ((*cmsg).cmsg_level, (*cmsg).cmsg_type),
+            (SOL_SOCKET, SCM_RIGHTS)
+        );
+        UnixStream::from_raw_fd(libc::CMSG_DATA(cmsg).cast::<c_int>().read_unaligned())
+    }
+}
+
+/// Returns a [`msghdr`] referencing `iov` and `control`.
+fn msghdr(iov: &mut [iovec], control: &mut Rights) -> msghdr {
+    let mut msg: msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = iov.as_mut_ptr();
+    msg.msg_iovlen = iov.len();
+    msg.msg_control = control.as_mut_ptr() as _;
+    msg.msg_controllen = size_of::<Rights>();
+    msg
+}
+
+/// Asserts that `stream` passed over a socket refers to the same socket as `peer`.
+fn assert_passed(stream: UnixStream, mut peer: UnixStream) {
+    (&stream).write_all(b"x").unwrap();
+    let mut buf = [0u8];
+    peer.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"x");
+}
+
 #[test]
 fn clock_getres() {
     run_test(2, [0xff; 16], move |i, platform, handler| {
@@ -246,6 +301,41 @@ fn close() {
     })
 }
 
+#[test]
+fn fchmod() {
+    run_test(2, [0xff; 16], move |i, platform, handler| {
+        let path = temp_dir().join(format!("sallyport-test-fchmod-{}", i));
+        let file = File::create(&path).unwrap();
+
+        let mode = 0o600 | (i as mode_t * 0o040);
+        let ret = if i % 2 == 0 {
+            handler.fchmod(file.as_raw_fd(), mode).map(|_| [0, 0])
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_fchmod as _,
+                        file.as_raw_fd() as _,
+                        mode as _,
+                        0,
+                        0,
+                        0,
+                        0,
+                    ],
+                )
+            }
+        };
+        if cfg!(not(miri)) {
+            assert_eq!(ret, Ok([0, 0]));
+            let perm = file.metadata().unwrap().permissions();
+            assert_eq!(perm.mode() & 0o777, mode);
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+    });
+}
+
 #[test]
 #[serial]
 fn fcntl() {
@@ -357,13 +447,16 @@ fn fcntl() {
 #[test]
 #[serial]
 fn fstat() {
-    let file = File::create(temp_dir().join("sallyport-test-fstat")).unwrap();
+    const EXPECTED: &str = "fstat";
+    let mut file = File::create(temp_dir().join("sallyport-test-fstat")).unwrap();
+    write!(&mut file, "{}", EXPECTED).unwrap();
     let fd = file.as_raw_fd();
 
-    run_test(2, [0xff; 16], move |i, platform, handler| {
-        let mut fd_stat = unsafe { mem::zeroed() };
+    run_test(2, [0xff; 32], move |i, platform, handler| {
+        let mut fd_stat: sallyport::libc::stat = unsafe { mem::zeroed() };
+        let expected = if cfg!(not(miri)) { Ok(()) } else { Err(ENOSYS) };
         if i % 2 == 0 {
-            assert_eq!(handler.fstat(fd, &mut fd_stat), Err(EBADFD));
+            assert_eq!(handler.fstat(fd, &mut fd_stat), expected);
         } else {
             assert_eq!(
                 unsafe {
@@ -380,9 +473,13 @@ fn fstat() {
                         ],
                     )
                 },
-                Err(EBADFD)
+                expected.map(|_| [0, 0])
             );
         }
+        if cfg!(not(miri)) {
+            assert_eq!(fd_stat.st_size, EXPECTED.len() as _);
+            assert_eq!(fd_stat.st_mode & S_IFMT, S_IFREG);
+        }
 
         for fd in [STDIN_FILENO, STDOUT_FILENO, STDERR_FILENO] {
             let mut stat = unsafe { mem::zeroed() };
@@ -412,6 +509,80 @@ fn fstat() {
     let _ = file;
 }
 
+#[test]
+fn ftruncate() {
+    run_test(2, [0xff; 16], move |i, platform, handler| {
+        let path = temp_dir().join(format!("sallyport-test-ftruncate-{}", i));
+        let mut file = File::create(&path).unwrap();
+        write!(&mut file, "ftruncate").unwrap();
+
+        let ret = if i % 2 == 0 {
+            handler.ftruncate(file.as_raw_fd(), 2).map(|_| [0, 0])
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [SYS_ftruncate as _, file.as_raw_fd() as _, 2, 0, 0, 0, 0],
+                )
+            }
+        };
+        if cfg!(not(miri)) {
+            assert_eq!(ret, Ok([0, 0]));
+            assert_eq!(file.metadata().unwrap().len(), 2);
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+    });
+}
+
+#[test]
+fn getdents64() {
+    run_test(2, [0xff; 64], move |i, platform, handler| {
+        let path = temp_dir().join(format!("sallyport-test-getdents64-{}", i));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir(&path).unwrap();
+        File::create(path.join("entry")).unwrap();
+        let dir = File::open(&path).unwrap();
+
+        let mut dirp = [0u8; 256];
+        let ret = if i % 2 == 0 {
+            handler.getdents64(dir.as_raw_fd(), &mut dirp)
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_getdents64 as _,
+                        dir.as_raw_fd() as _,
+                        dirp.as_mut_ptr() as _,
+                        dirp.len(),
+                        0,
+                        0,
+                        0,
+                    ],
+                )
+            }
+            .map(|[ret, _]| ret)
+        };
+        if cfg!(not(miri)) {
+            let len = ret.unwrap();
+            let mut names = vec![];
+            let mut dirents = &dirp[..len];
+            while !dirents.is_empty() {
+                let reclen = u16::from_ne_bytes([dirents[16], dirents[17]]) as usize;
+                let name = &dirents[19..reclen];
+                names.push(&name[..name.iter().position(|&b| b == 0).unwrap()]);
+                dirents = &dirents[reclen..];
+            }
+            names.sort();
+            assert_eq!(names, [&b"."[..], b"..", b"entry"]);
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+        fs::remove_dir_all(&path).unwrap();
+    });
+}
+
 #[test]
 fn getegid() {
     run_test(2, [0xff; 16], move |i, platform, handler| {
@@ -502,6 +673,93 @@ fn getrandom() {
     });
 }
 
+#[test]
+#[serial]
+fn lseek() {
+    run_test(2, [0xff; 16], move |i, platform, handler| {
+        const EXPECTED: &str = "lseek";
+        let path = temp_dir().join(format!("sallyport-test-lseek-{}", i));
+        write!(&mut File::create(&path).unwrap(), "{}", EXPECTED).unwrap();
+
+        let file = File::open(&path).unwrap();
+        for (offset, whence, expected) in [(2, SEEK_SET, 2), (-1, SEEK_END, EXPECTED.len() - 1)] {
+            if i % 2 == 0 {
+                assert_eq!(
+                    handler.lseek(file.as_raw_fd(), offset, whence),
+                    if cfg!(not(miri)) {
+                        Ok(expected as _)
+                    } else {
+                        Err(ENOSYS)
+                    }
+                );
+            } else {
+                assert_eq!(
+                    unsafe {
+                        handler.syscall(
+                            platform,
+                            [
+                                SYS_lseek as _,
+                                file.as_raw_fd() as _,
+                                offset as _,
+                                whence as _,
+                                0,
+                                0,
+                                0,
+                            ],
+                        )
+                    },
+                    if cfg!(not(miri)) {
+                        Ok([expected, 0])
+                    } else {
+                        Err(ENOSYS)
+                    }
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn mkdirat() {
+    run_test(2, [0xff; 32], move |i, platform, handler| {
+        let path = temp_dir().join(format!("sallyport-test-mkdirat-{}", i));
+        let _ = fs::remove_dir(&path);
+        let path_c = CString::new(path.to_str().unwrap()).unwrap();
+
+        let ret = if i % 2 == 0 {
+            handler
+                .mkdirat(AT_FDCWD, path_c.as_bytes_with_nul(), 0o755)
+                .map(|_| [0, 0])
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_mkdirat as _,
+                        AT_FDCWD as _,
+                        path_c.as_ptr() as _,
+                        0o755,
+                        0,
+                        0,
+                        0,
+                    ],
+                )
+            }
+        };
+        if cfg!(not(miri)) {
+            assert_eq!(ret, Ok([0, 0]));
+            assert!(path.is_dir());
+            assert_eq!(
+                handler.mkdirat(AT_FDCWD, path_c.as_bytes_with_nul(), 0o755),
+                Err(EEXIST)
+            );
+            fs::remove_dir(&path).unwrap();
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+    });
+}
+
 #[test]
 fn mremap() {
     let mem = [0u8; 4096];
@@ -646,14 +904,75 @@ fn nanosleep() {
     });
 }
 
+#[test]
+#[serial]
+fn newfstatat() {
+    run_test(2, [0xff; 48], move |i, platform, handler| {
+        const EXPECTED: &str = "newfstatat";
+        let path = temp_dir().join(format!("sallyport-test-newfstatat-{}", i));
+        write!(&mut File::create(&path).unwrap(), "{}", EXPECTED).unwrap();
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+
+        let mut stat: sallyport::libc::stat = unsafe { mem::zeroed() };
+        let ret = if i % 2 == 0 {
+            handler
+                .newfstatat(AT_FDCWD, path.as_bytes_with_nul(), &mut stat, 0)
+                .map(|_| [0, 0])
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_newfstatat as _,
+                        AT_FDCWD as _,
+                        path.as_ptr() as _,
+                        &mut stat as *mut _ as _,
+                        0,
+                        0,
+                        0,
+                    ],
+                )
+            }
+        };
+        if cfg!(not(miri)) {
+            assert_eq!(ret, Ok([0, 0]));
+            assert_eq!(stat.st_size, EXPECTED.len() as _);
+            assert_eq!(stat.st_mode & S_IFMT, S_IFREG);
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+
+        assert_eq!(
+            handler.newfstatat(
+                AT_FDCWD,
+                c"/sallyport-test-missing".to_bytes_with_nul(),
+                &mut stat,
+                0
+            ),
+            if cfg!(not(miri)) {
+                Err(ENOENT)
+            } else {
+                Err(ENOSYS)
+            }
+        );
+    });
+}
+
+// `#[serial]` cannot parse C string literals, so they are declared outside.
+const PASSWD: &CStr = c"/etc/passwd";
+const RESOLV_CONF: &CStr = c"/etc/resolv.conf";
+
 #[test]
 #[serial]
 fn open() {
     run_test(2, [0xff; 32], move |i, platform, handler| {
-        let libc_ret = unsafe { libc::open(b"/etc/resolv.conf\0".as_ptr() as _, O_RDONLY, 0o666) }; // NOTE: mode argument is ignored in this case, but specified to satisfy miri
+        let libc_ret = unsafe { libc::open(RESOLV_CONF.as_ptr() as _, O_RDONLY, 0o666) }; // NOTE: mode argument is ignored in this case, but specified to satisfy miri
 
         if i % 2 == 0 {
-            assert_eq!(handler.open(b"/etc/passwd\0", O_RDONLY, None), Err(EACCES));
+            assert_eq!(
+                handler.open(PASSWD.to_bytes_with_nul(), O_RDONLY, None),
+                Err(EACCES)
+            );
         } else {
             assert_eq!(
                 unsafe {
@@ -661,7 +980,7 @@ fn open() {
                         platform,
                         [
                             SYS_open as _,
-                            b"/etc/passwd\0".as_ptr() as _,
+                            PASSWD.as_ptr() as _,
                             O_RDONLY as _,
                             0,
                             0,
@@ -675,7 +994,7 @@ fn open() {
         }
 
         if i % 2 == 0 {
-            let ret = handler.open(b"/etc/resolv.conf\0", O_RDONLY, None);
+            let ret = handler.open(RESOLV_CONF.to_bytes_with_nul(), O_RDONLY, None);
             if cfg!(not(miri)) {
                 if libc_ret < 0 {
                     assert_eq!(ret, Err(-libc_ret));
@@ -692,7 +1011,7 @@ fn open() {
                     platform,
                     [
                         SYS_open as _,
-                        b"/etc/resolv.conf\0".as_ptr() as _,
+                        RESOLV_CONF.as_ptr() as _,
                         O_RDONLY as _,
                         0,
                         0,
@@ -705,59 +1024,314 @@ fn open() {
                 if libc_ret < 0 {
                     assert_eq!(ret, Err(-libc_ret));
                 } else {
-                    assert!(ret.is_ok());
-                    assert_ne!(ret.unwrap(), [libc_ret as _, 0]);
+                    assert!(ret.is_ok());
+                    assert_ne!(ret.unwrap(), [libc_ret as _, 0]);
+                }
+                assert!(ret.is_ok());
+            } else {
+                assert_eq!(ret, Err(ENOSYS));
+            }
+        }
+    });
+}
+
+#[test]
+#[serial]
+fn openat() {
+    run_test(2, [0xff; 32], move |i, platform, handler| {
+        const EXPECTED: &str = "openat";
+        let path = temp_dir().join(format!("sallyport-test-openat-{}", i));
+        write!(&mut File::create(&path).unwrap(), "{}", EXPECTED).unwrap();
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+
+        let ret = if i % 2 == 0 {
+            handler.openat(AT_FDCWD, path.as_bytes_with_nul(), O_RDONLY, None)
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_openat as _,
+                        AT_FDCWD as _,
+                        path.as_ptr() as _,
+                        O_RDONLY as _,
+                        0,
+                        0,
+                        0,
+                    ],
+                )
+            }
+            .map(|[ret, _]| ret as _)
+        };
+        if cfg!(not(miri)) {
+            let fd = ret.unwrap();
+            let mut buf = [0u8; EXPECTED.len()];
+            assert_eq!(handler.read(fd, &mut buf), Ok(EXPECTED.len()));
+            assert_eq!(buf, EXPECTED.as_bytes());
+            assert_eq!(handler.close(fd), Ok(()));
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+    });
+}
+
+#[test]
+#[serial]
+fn poll() {
+    run_test(2, [0xff; 16], move |i, platform, handler| {
+        let mut fds: [pollfd; 3] = [
+            pollfd {
+                fd: 0,
+                events: 0,
+                revents: 0,
+            },
+            pollfd {
+                fd: 1,
+                events: 0,
+                revents: 0,
+            },
+            pollfd {
+                fd: 2,
+                events: 0,
+                revents: 0,
+            },
+        ];
+
+        if i % 2 == 0 {
+            assert_eq!(
+                handler.poll(unsafe { transmute::<_, &mut [_; 3]>(&mut fds) }, 0),
+                if cfg!(not(miri)) { Ok(0) } else { Err(ENOSYS) }
+            );
+        } else {
+            assert_eq!(
+                unsafe {
+                    handler.syscall(
+                        platform,
+                        [SYS_poll as _, fds.as_mut_ptr() as _, fds.len(), 0, 0, 0, 0],
+                    )
+                },
+                if cfg!(not(miri)) {
+                    Ok([0, 0])
+                } else {
+                    Err(ENOSYS)
+                }
+            );
+        }
+    });
+}
+
+#[test]
+#[serial]
+fn pread64() {
+    run_test(2, [0xff; 16], move |i, platform, handler| {
+        let path = temp_dir().join(format!("sallyport-test-pread64-{}", i));
+        write!(&mut File::create(&path).unwrap(), "pread64").unwrap();
+
+        let mut buf = [0u8; 4];
+        let file = File::open(&path).unwrap();
+        if i % 2 == 0 {
+            assert_eq!(
+                handler.pread64(file.as_raw_fd(), &mut buf, 3),
+                if cfg!(not(miri)) {
+                    Ok(buf.len())
+                } else {
+                    Err(ENOSYS)
+                }
+            );
+        } else {
+            assert_eq!(
+                unsafe {
+                    handler.syscall(
+                        platform,
+                        [
+                            SYS_pread64 as _,
+                            file.as_raw_fd() as _,
+                            buf.as_mut_ptr() as _,
+                            buf.len(),
+                            3,
+                            0,
+                            0,
+                        ],
+                    )
+                },
+                if cfg!(not(miri)) {
+                    Ok([buf.len(), 0])
+                } else {
+                    Err(ENOSYS)
+                }
+            );
+        }
+        if cfg!(not(miri)) {
+            assert_eq!(&buf, b"ad64");
+            // The file position is not changed.
+            assert_eq!((&file).stream_position().unwrap(), 0);
+        }
+    });
+}
+
+#[test]
+#[serial]
+fn pwrite64() {
+    run_test(2, [0xff; 16], move |i, platform, handler| {
+        const EXPECTED: &[u8] = b"pwrite";
+        let path = temp_dir().join(format!("sallyport-test-pwrite64-{}", i));
+        write!(&mut File::create(&path).unwrap(), "xxxxxxxx").unwrap();
+
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        if i % 2 == 0 {
+            assert_eq!(
+                handler.pwrite64(file.as_raw_fd(), EXPECTED, 2),
+                if cfg!(not(miri)) {
+                    Ok(EXPECTED.len())
+                } else {
+                    Err(ENOSYS)
+                }
+            );
+        } else {
+            assert_eq!(
+                unsafe {
+                    handler.syscall(
+                        platform,
+                        [
+                            SYS_pwrite64 as _,
+                            file.as_raw_fd() as _,
+                            EXPECTED.as_ptr() as _,
+                            EXPECTED.len(),
+                            2,
+                            0,
+                            0,
+                        ],
+                    )
+                },
+                if cfg!(not(miri)) {
+                    Ok([EXPECTED.len(), 0])
+                } else {
+                    Err(ENOSYS)
+                }
+            );
+        }
+        if cfg!(not(miri)) {
+            let mut buf = Vec::new();
+            File::open(&path).unwrap().read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, b"xxpwrite");
+        }
+    });
+}
+
+#[test]
+fn preadv() {
+    // The block fits the item header, 2 `iovec` elements and a single word of data.
+    run_test(2, [0xff; 18], move |i, platform, handler| {
+        let path = temp_dir().join(format!("sallyport-test-preadv-{}", i));
+        write!(&mut File::create(&path).unwrap(), "preadv").unwrap();
+
+        let mut one = [0u8; 2];
+        let mut two = [0u8; 4];
+        let file = File::open(&path).unwrap();
+        if i % 2 == 0 {
+            assert_eq!(
+                handler.preadv(file.as_raw_fd(), &mut [&mut one[..], &mut two[..]], 1),
+                if cfg!(not(miri)) { Ok(5) } else { Err(ENOSYS) }
+            );
+        } else {
+            assert_eq!(
+                unsafe {
+                    handler.syscall(
+                        platform,
+                        [
+                            SYS_preadv as _,
+                            file.as_raw_fd() as _,
+                            [
+                                iovec {
+                                    iov_base: one.as_mut_ptr() as _,
+                                    iov_len: one.len(),
+                                },
+                                iovec {
+                                    iov_base: two.as_mut_ptr() as _,
+                                    iov_len: two.len(),
+                                },
+                            ]
+                            .as_mut_ptr() as _,
+                            2,
+                            1,
+                            0,
+                            0,
+                        ],
+                    )
+                },
+                if cfg!(not(miri)) {
+                    Ok([5, 0])
+                } else {
+                    Err(ENOSYS)
                 }
-                assert!(ret.is_ok());
-            } else {
-                assert_eq!(ret, Err(ENOSYS));
-            }
+            );
+        }
+        if cfg!(not(miri)) {
+            assert_eq!(&one, b"re");
+            assert_eq!(&two, b"adv\0");
+            // The file position is not changed.
+            assert_eq!((&file).stream_position().unwrap(), 0);
         }
     });
 }
 
 #[test]
-#[serial]
-fn poll() {
-    run_test(2, [0xff; 16], move |i, platform, handler| {
-        let mut fds: [pollfd; 3] = [
-            pollfd {
-                fd: 0,
-                events: 0,
-                revents: 0,
-            },
-            pollfd {
-                fd: 1,
-                events: 0,
-                revents: 0,
-            },
-            pollfd {
-                fd: 2,
-                events: 0,
-                revents: 0,
-            },
-        ];
-
+fn pwritev() {
+    // The block fits the item header, 2 `iovec` elements and a single word of data.
+    run_test(2, [0xff; 18], move |i, platform, handler| {
+        const ONE: &[u8] = b"pwr";
+        const TWO: &[u8] = b"itev";
+        let path = temp_dir().join(format!("sallyport-test-pwritev-{}", i));
+        write!(&mut File::create(&path).unwrap(), "xxxxxxxxx").unwrap();
+
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
         if i % 2 == 0 {
             assert_eq!(
-                handler.poll(unsafe { transmute::<_, &mut [_; 3]>(&mut fds) }, 0),
-                if cfg!(not(miri)) { Ok(0) } else { Err(ENOSYS) }
+                handler.pwritev(file.as_raw_fd(), &[ONE, TWO], 2),
+                if cfg!(not(miri)) {
+                    Ok(ONE.len() + TWO.len())
+                } else {
+                    Err(ENOSYS)
+                }
             );
         } else {
             assert_eq!(
                 unsafe {
                     handler.syscall(
                         platform,
-                        [SYS_poll as _, fds.as_mut_ptr() as _, fds.len(), 0, 0, 0, 0],
+                        [
+                            SYS_pwritev as _,
+                            file.as_raw_fd() as _,
+                            [
+                                iovec {
+                                    iov_base: ONE.as_ptr() as _,
+                                    iov_len: ONE.len(),
+                                },
+                                iovec {
+                                    iov_base: TWO.as_ptr() as _,
+                                    iov_len: TWO.len(),
+                                },
+                            ]
+                            .as_ptr() as _,
+                            2,
+                            2,
+                            0,
+                            0,
+                        ],
                     )
                 },
                 if cfg!(not(miri)) {
-                    Ok([0, 0])
+                    Ok([ONE.len() + TWO.len(), 0])
                 } else {
                     Err(ENOSYS)
                 }
             );
         }
+        if cfg!(not(miri)) {
+            let mut buf = Vec::new();
+            File::open(&path).unwrap().read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, b"xxpwritev");
+        }
     });
 }
 
@@ -810,6 +1384,26 @@ fn read() {
     });
 }
 
+#[test]
+#[serial]
+fn read_exact() {
+    run_test(1, [0xff; 16], move |_, _, handler| {
+        let expected: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let path = temp_dir().join("sallyport-test-read-exact");
+        File::create(&path).unwrap().write_all(&expected).unwrap();
+
+        let file = File::open(&path).unwrap();
+        let mut buf = [0u8; 1000];
+        if cfg!(not(miri)) {
+            assert_eq!(handler.read_exact(file.as_raw_fd(), &mut buf), Ok(()));
+            assert_eq!(buf[..], expected);
+            assert_eq!(handler.read_exact(file.as_raw_fd(), &mut buf), Err(ENODATA));
+        } else {
+            assert_eq!(handler.read_exact(file.as_raw_fd(), &mut buf), Err(ENOSYS));
+        }
+    });
+}
+
 #[test]
 fn readlink() {
     run_test(2, [0xff; 16], move |i, platform, handler| {
@@ -817,7 +1411,10 @@ fn readlink() {
         let mut buf = [0u8; EXPECTED.len()];
 
         if i % 2 == 0 {
-            assert_eq!(handler.readlink(b"/proc/self\0", &mut buf), Err(ENOENT));
+            assert_eq!(
+                handler.readlink(c"/proc/self".to_bytes_with_nul(), &mut buf),
+                Err(ENOENT)
+            );
         } else {
             assert_eq!(
                 unsafe {
@@ -825,7 +1422,7 @@ fn readlink() {
                         platform,
                         [
                             SYS_readlink as _,
-                            b"/proc/self\0".as_ptr() as _,
+                            c"/proc/self".as_ptr() as _,
                             buf.as_mut_ptr() as _,
                             EXPECTED.len(),
                             0,
@@ -841,7 +1438,7 @@ fn readlink() {
 
         if i % 2 == 0 {
             assert_eq!(
-                handler.readlink(b"/proc/self/exe\0", &mut buf),
+                handler.readlink(c"/proc/self/exe".to_bytes_with_nul(), &mut buf),
                 Ok(EXPECTED.len())
             );
         } else {
@@ -851,7 +1448,7 @@ fn readlink() {
                         platform,
                         [
                             SYS_readlink as _,
-                            b"/proc/self/exe\0".as_ptr() as _,
+                            c"/proc/self/exe".as_ptr() as _,
                             buf.as_mut_ptr() as _,
                             EXPECTED.len(),
                             0,
@@ -1298,6 +1895,150 @@ fn recvfrom() {
     });
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn recvmsg() {
+    const EXPECTED: &str = "recvmsg";
+
+    run_test(2, [0xff; 32], move |i, platform, handler| {
+        let (src, dest) = UnixStream::pair().unwrap();
+        let (stream, peer) = UnixStream::pair().unwrap();
+        let mut control = scm_rights(stream.as_raw_fd());
+        let mut iov = [iovec {
+            iov_base: EXPECTED.as_ptr() as _,
+            iov_len: EXPECTED.len(),
+        }];
+        let msg = msghdr(&mut iov, &mut control);
+        assert_eq!(
+            unsafe { libc::sendmsg(src.as_raw_fd(), &msg, 0) },
+            EXPECTED.len() as _
+        );
+        drop(stream);
+
+        let mut one = [0u8; 3];
+        let mut two = [0u8; 4];
+        let mut control: Rights = Default::default();
+        if i % 2 == 0 {
+            let control_bytes = unsafe {
+                slice::from_raw_parts_mut(control.as_mut_ptr() as _, size_of::<Rights>())
+            };
+            let mut controllen = 0;
+            let mut flags = -1;
+            assert_eq!(
+                handler.recvmsg(
+                    dest.as_raw_fd(),
+                    MsghdrOutput {
+                        name: None,
+                        iov: &mut [&mut one[..], &mut two[..]],
+                        control: control_bytes,
+                        controllen: &mut controllen,
+                        flags: &mut flags,
+                    },
+                    0,
+                ),
+                Ok(EXPECTED.len())
+            );
+            assert_eq!(controllen, CMSG_SPACE(size_of::<c_int>()));
+            assert_eq!(flags, 0);
+        } else {
+            let mut iov = [
+                iovec {
+                    iov_base: one.as_mut_ptr() as _,
+                    iov_len: one.len(),
+                },
+                iovec {
+                    iov_base: two.as_mut_ptr() as _,
+                    iov_len: two.len(),
+                },
+            ];
+            let mut msg = msghdr(&mut iov, &mut control);
+            assert_eq!(
+                unsafe {
+                    handler.syscall(
+                        platform,
+                        [
+                            SYS_recvmsg as _,
+                            dest.as_raw_fd() as _,
+                            &mut msg as *mut _ as _,
+                            0,
+                            0,
+                            0,
+                            0,
+                        ],
+                    )
+                },
+                Ok([EXPECTED.len(), 0])
+            );
+            assert_eq!(msg.msg_controllen, CMSG_SPACE(size_of::<c_int>()));
+            assert_eq!(msg.msg_flags, 0);
+        }
+        assert_eq!(&one, b"rec");
+        assert_eq!(&two, b"vmsg");
+        assert_passed(passed(&control), peer);
+    });
+}
+
+#[test]
+fn renameat2() {
+    run_test(2, [0xff; 32], move |i, platform, handler| {
+        const EXPECTED: &str = "renameat2";
+        let oldpath = temp_dir().join(format!("sallyport-test-renameat2-old-{}", i));
+        let newpath = temp_dir().join(format!("sallyport-test-renameat2-new-{}", i));
+        let _ = fs::remove_file(&newpath);
+        write!(&mut File::create(&oldpath).unwrap(), "{}", EXPECTED).unwrap();
+        let oldpath_c = CString::new(oldpath.to_str().unwrap()).unwrap();
+        let newpath_c = CString::new(newpath.to_str().unwrap()).unwrap();
+
+        let ret = if i % 2 == 0 {
+            handler
+                .renameat2(
+                    AT_FDCWD,
+                    oldpath_c.as_bytes_with_nul(),
+                    AT_FDCWD,
+                    newpath_c.as_bytes_with_nul(),
+                    0,
+                )
+                .map(|_| [0, 0])
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_renameat2 as _,
+                        AT_FDCWD as _,
+                        oldpath_c.as_ptr() as _,
+                        AT_FDCWD as _,
+                        newpath_c.as_ptr() as _,
+                        0,
+                        0,
+                    ],
+                )
+            }
+        };
+        if cfg!(not(miri)) {
+            assert_eq!(ret, Ok([0, 0]));
+            assert!(!oldpath.exists());
+            assert_eq!(fs::read_to_string(&newpath).unwrap(), EXPECTED);
+
+            File::create(&oldpath).unwrap();
+            assert_eq!(
+                handler.renameat2(
+                    AT_FDCWD,
+                    oldpath_c.as_bytes_with_nul(),
+                    AT_FDCWD,
+                    newpath_c.as_bytes_with_nul(),
+                    RENAME_NOREPLACE,
+                ),
+                Err(EEXIST)
+            );
+            fs::remove_file(&oldpath).unwrap();
+            fs::remove_file(&newpath).unwrap();
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+    });
+}
+
 #[test]
 fn rt_sigaction() {
     run_test(2, [0xff; 16], move |i, platform, handler| {
@@ -1448,6 +2189,103 @@ fn send() {
     });
 }
 
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn send_all_recv_exact() {
+    run_test(1, [0xff; 32], move |_, _, handler| {
+        let expected: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let (guest, mut peer) = UnixStream::pair().expect("couldn't create socket pair");
+
+        assert_eq!(
+            handler.send_all(guest.as_raw_fd(), &expected, MSG_NOSIGNAL),
+            Ok(())
+        );
+        let mut got = vec![0u8; expected.len()];
+        peer.read_exact(&mut got).unwrap();
+        assert_eq!(got, expected);
+
+        peer.write_all(&expected).unwrap();
+        drop(peer);
+        let mut buf = [0u8; 1024];
+        assert_eq!(
+            handler.recv_exact(guest.as_raw_fd(), &mut buf[..expected.len()], 0),
+            Ok(())
+        );
+        assert_eq!(buf[..expected.len()], expected);
+        assert_eq!(
+            handler.recv_exact(guest.as_raw_fd(), &mut buf, 0),
+            Err(ENODATA)
+        );
+    });
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn sendmsg() {
+    const EXPECTED: &str = "sendmsg";
+
+    run_test(2, [0xff; 32], move |i, platform, handler| {
+        let (src, dest) = UnixStream::pair().unwrap();
+        let (stream, peer) = UnixStream::pair().unwrap();
+        let mut control = scm_rights(stream.as_raw_fd());
+        if i % 2 == 0 {
+            let control_bytes =
+                unsafe { slice::from_raw_parts(control.as_ptr() as _, size_of::<Rights>()) };
+            assert_eq!(
+                handler.sendmsg(
+                    src.as_raw_fd(),
+                    MsghdrInput {
+                        name: None,
+                        iov: &[&EXPECTED.as_bytes()[..4], &EXPECTED.as_bytes()[4..]],
+                        control: control_bytes,
+                    },
+                    0,
+                ),
+                Ok(EXPECTED.len())
+            );
+        } else {
+            let mut iov = [iovec {
+                iov_base: EXPECTED.as_ptr() as _,
+                iov_len: EXPECTED.len(),
+            }];
+            let msg = msghdr(&mut iov, &mut control);
+            assert_eq!(
+                unsafe {
+                    handler.syscall(
+                        platform,
+                        [
+                            SYS_sendmsg as _,
+                            src.as_raw_fd() as _,
+                            &msg as *const _ as _,
+                            0,
+                            0,
+                            0,
+                            0,
+                        ],
+                    )
+                },
+                Ok([EXPECTED.len(), 0])
+            );
+        }
+        drop(stream);
+
+        let mut buf = [0u8; EXPECTED.len()];
+        let mut control: Rights = Default::default();
+        let mut iov = [iovec {
+            iov_base: buf.as_mut_ptr() as _,
+            iov_len: buf.len(),
+        }];
+        let mut msg = msghdr(&mut iov, &mut control);
+        assert_eq!(
+            unsafe { libc::recvmsg(dest.as_raw_fd(), &mut msg, 0) },
+            EXPECTED.len() as _
+        );
+        assert_eq!(buf, EXPECTED.as_bytes());
+        assert_passed(passed(&control), peer);
+    });
+}
+
 #[test]
 #[serial]
 #[cfg_attr(miri, ignore)]
@@ -1576,6 +2414,56 @@ fn sync_read_close() {
     });
 }
 
+// `#[serial]` cannot parse C string literals, so it is declared outside.
+const EMPTY_PATH: &CStr = c"";
+
+#[test]
+#[serial]
+fn statx() {
+    run_test(2, [0xff; 64], move |i, platform, handler| {
+        const EXPECTED: &str = "statx";
+        let path = temp_dir().join(format!("sallyport-test-statx-{}", i));
+        write!(&mut File::create(&path).unwrap(), "{}", EXPECTED).unwrap();
+
+        let file = File::open(&path).unwrap();
+        let mut statx: sallyport::libc::statx = unsafe { mem::zeroed() };
+        let ret = if i % 2 == 0 {
+            handler
+                .statx(
+                    file.as_raw_fd(),
+                    EMPTY_PATH.to_bytes_with_nul(),
+                    AT_EMPTY_PATH,
+                    STATX_BASIC_STATS,
+                    &mut statx,
+                )
+                .map(|_| [0, 0])
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_statx as _,
+                        file.as_raw_fd() as _,
+                        EMPTY_PATH.as_ptr() as _,
+                        AT_EMPTY_PATH as _,
+                        STATX_BASIC_STATS as _,
+                        &mut statx as *mut _ as _,
+                        0,
+                    ],
+                )
+            }
+        };
+        if cfg!(not(miri)) {
+            assert_eq!(ret, Ok([0, 0]));
+            assert_eq!(statx.stx_mask & STATX_BASIC_STATS, STATX_BASIC_STATS);
+            assert_eq!(statx.stx_size, EXPECTED.len() as _);
+            assert_eq!(statx.stx_mode as u32 & S_IFMT, S_IFREG);
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+        }
+    });
+}
+
 #[test]
 fn uname() {
     run_test(2, [0xff; 16], move |i, platform, handler| {
@@ -1613,6 +2501,51 @@ fn uname() {
     });
 }
 
+#[test]
+fn unlinkat() {
+    run_test(2, [0xff; 32], move |i, platform, handler| {
+        let path = temp_dir().join(format!("sallyport-test-unlinkat-{}", i));
+        let _ = fs::remove_dir(&path);
+        File::create(&path).unwrap();
+        let path_c = CString::new(path.to_str().unwrap()).unwrap();
+
+        let ret = if i % 2 == 0 {
+            handler
+                .unlinkat(AT_FDCWD, path_c.as_bytes_with_nul(), 0)
+                .map(|_| [0, 0])
+        } else {
+            unsafe {
+                handler.syscall(
+                    platform,
+                    [
+                        SYS_unlinkat as _,
+                        AT_FDCWD as _,
+                        path_c.as_ptr() as _,
+                        0,
+                        0,
+                        0,
+                        0,
+                    ],
+                )
+            }
+        };
+        if cfg!(not(miri)) {
+            assert_eq!(ret, Ok([0, 0]));
+            assert!(!path.exists());
+
+            fs::create_dir(&path).unwrap();
+            assert_eq!(
+                handler.unlinkat(AT_FDCWD, path_c.as_bytes_with_nul(), AT_REMOVEDIR),
+                Ok(())
+            );
+            assert!(!path.exists());
+        } else {
+            assert_eq!(ret, Err(ENOSYS));
+            fs::remove_file(&path).unwrap();
+        }
+    });
+}
+
 #[test]
 #[serial]
 fn write() {
@@ -1669,6 +2602,32 @@ fn write() {
     })
 }
 
+#[test]
+#[serial]
+fn write_all() {
+    run_test(1, [0xff; 16], move |_, _, handler| {
+        let expected: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let path = temp_dir().join("sallyport-test-write-all");
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&path)
+            .unwrap();
+
+        if cfg!(not(miri)) {
+            assert_eq!(handler.write_all(file.as_raw_fd(), &expected), Ok(()));
+            let mut got = Vec::new();
+            file.rewind().unwrap();
+            file.read_to_end(&mut got).unwrap();
+            assert_eq!(got, expected);
+        } else {
+            assert_eq!(handler.write_all(file.as_raw_fd(), &expected), Err(ENOSYS));
+        }
+    });
+}
+
 #[test]
 #[serial]
 fn writev() {